@@ -1,16 +1,20 @@
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::process::ExitCode;
 
-use pendon_core::{parse, Options};
-use pendon_plugin_custom::{load_index_from_path, load_spec_from_path, PluginSpec};
-use pendon_plugin_dialog::process as process_dialog;
-use pendon_plugin_img::process as process_img;
-use pendon_plugin_latex::process as process_latex;
-use pendon_plugin_markdown::MarkdownOptions;
-use pendon_plugin_quiz::{process as process_quiz, solid_hints as quiz_solid_hints};
-use pendon_plugin_vicado::{process as process_vicado, solid_hints as vicado_solid_hints};
-use pendon_plugin_wiki::{process_with_options as process_wiki, WikiOptions};
+use pendon_core::{parse, Limits, Options, Parser, PluginRegistry, SlugStrategy};
+use pendon_plugin_codeblock_syntect::SyntectPlugin;
+use pendon_plugin_custom::{load_index_from_path, load_spec_from_path, CustomPlugin, PluginSpec};
+use pendon_plugin_dialog::DialogPlugin;
+use pendon_plugin_extract_heading::{ExtractHeadingOptions, ExtractHeadingPlugin};
+use pendon_plugin_img::ImgPlugin;
+use pendon_plugin_latex::{LatexOptions, LatexPlugin};
+use pendon_plugin_markdown::{MarkdownOptions, MarkdownPlugin};
+use pendon_plugin_micromatter::MicromatterPlugin;
+use pendon_plugin_quiz::QuizPlugin;
+use pendon_plugin_sectionize::{SectionizeOptions, SectionizePlugin};
+use pendon_plugin_vicado::{solid_hints as vicado_solid_hints, VicadoPlugin};
+use pendon_plugin_wiki::{WikiOptions, WikiPlugin};
 use pendon_renderer_json::render_to_string;
 use pendon_renderer_solid::{
     render_solid_with_hints, ComponentTemplate, ImportEntry, SolidRenderHints,
@@ -18,7 +22,6 @@ use pendon_renderer_solid::{
 use pico_args::Arguments;
 use regex::Regex;
 use serde::Deserialize;
-use serde_json;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use walkdir::WalkDir;
@@ -27,15 +30,21 @@ use walkdir::WalkDir;
 struct CliArgs {
     input: Option<String>,
     format: Option<String>,
+    from: Option<String>,
     strict: bool,
     pretty: bool,
     tui: bool,
     max_doc_bytes: Option<usize>,
     max_line_len: Option<usize>,
     max_blank_run: Option<usize>,
+    limits: Limits,
     plugin: Option<String>,
     markdown_allow_html: bool,
+    markdown_extended_autolinks: bool,
+    markdown_callouts: bool,
     wiki_link_prefix: Option<String>,
+    slug: SlugStrategy,
+    validate_events: bool,
 }
 
 fn parse_args() -> Result<CliArgs, String> {
@@ -47,10 +56,25 @@ fn parse_args() -> Result<CliArgs, String> {
     let format: Option<String> = pargs
         .opt_value_from_str(["-f", "--format"])
         .map_err(|e| e.to_string())?;
+    let from: Option<String> = pargs
+        .opt_value_from_str("--from")
+        .map_err(|e| e.to_string())?;
+    if let Some(other) = from
+        .as_deref()
+        .filter(|f| !matches!(*f, "markdown" | "events"))
+    {
+        return Err(format!(
+            "unsupported input format '{}' (expected 'markdown' or 'events')",
+            other
+        ));
+    }
     let strict: bool = pargs.contains("--strict");
     let pretty: bool = pargs.contains("--pretty");
     let tui: bool = pargs.contains("--tui");
     let markdown_allow_html: bool = pargs.contains("--markdown-allow-html");
+    let markdown_extended_autolinks: bool = pargs.contains("--markdown-extended-autolinks");
+    let markdown_callouts: bool = pargs.contains("--markdown-callouts");
+    let validate_events: bool = pargs.contains("--validate-events");
     let max_doc_bytes: Option<usize> = pargs
         .opt_value_from_str("--max-doc-bytes")
         .map_err(|e| e.to_string())?;
@@ -60,12 +84,33 @@ fn parse_args() -> Result<CliArgs, String> {
     let max_blank_run: Option<usize> = pargs
         .opt_value_from_str("--max-blank-run")
         .map_err(|e| e.to_string())?;
+    let limits = Limits {
+        max_depth: pargs
+            .opt_value_from_str("--max-depth")
+            .map_err(|e| e.to_string())?,
+        max_events: pargs
+            .opt_value_from_str("--max-events")
+            .map_err(|e| e.to_string())?,
+        max_attr_bytes: pargs
+            .opt_value_from_str("--max-attr-bytes")
+            .map_err(|e| e.to_string())?,
+        max_formula_len: pargs
+            .opt_value_from_str("--max-formula-len")
+            .map_err(|e| e.to_string())?,
+    };
     let plugin: Option<String> = pargs
         .opt_value_from_str("--plugin")
         .map_err(|e| e.to_string())?;
     let wiki_link_prefix: Option<String> = pargs
         .opt_value_from_str("--wiki-link-prefix")
         .map_err(|e| e.to_string())?;
+    let slug = match pargs
+        .opt_value_from_str::<_, String>("--slug")
+        .map_err(|e| e.to_string())?
+    {
+        Some(name) => parse_slug_strategy(&name)?,
+        None => SlugStrategy::default(),
+    };
 
     // Ensure no unexpected free arguments
     let rest = pargs.finish();
@@ -76,15 +121,30 @@ fn parse_args() -> Result<CliArgs, String> {
     Ok(CliArgs {
         input,
         format,
+        from,
         strict,
         pretty,
         tui,
         max_doc_bytes,
         max_line_len,
         max_blank_run,
+        limits,
         plugin,
         markdown_allow_html,
+        markdown_extended_autolinks,
+        markdown_callouts,
         wiki_link_prefix,
+        slug,
+        validate_events,
+    })
+}
+
+fn parse_slug_strategy(name: &str) -> Result<SlugStrategy, String> {
+    SlugStrategy::from_name(name).ok_or_else(|| {
+        format!(
+            "unsupported slug strategy '{}' (expected 'github' or 'ascii')",
+            name
+        )
     })
 }
 
@@ -105,6 +165,18 @@ fn read_input(args: &CliArgs) -> Result<String, String> {
     }
 }
 
+fn open_input(args: &CliArgs) -> Result<Box<dyn Read>, String> {
+    if let Some(path) = &args.input {
+        match fs::File::open(path) {
+            Ok(f) => Ok(Box::new(BufReader::new(f))),
+            Err(e) => Err(format!("cannot read file '{}': {}", path, e)),
+        }
+    } else {
+        // Stdin is consumed incrementally by the parser; callers should pipe data when not using --input
+        Ok(Box::new(io::stdin().lock()))
+    }
+}
+
 fn main() -> ExitCode {
     // Subcommand: "run" (config-driven batch); internal tools are separated
     if std::env::args().nth(1).as_deref() == Some("run") {
@@ -133,132 +205,134 @@ fn main() -> ExitCode {
         None
     };
 
-    let input = match read_input(&args) {
-        Ok(s) => s,
-        Err(msg) => {
-            eprintln!("Error: {}", msg);
-            return ExitCode::from(2);
+    // Events written by `-f events` skip the parser and resume the pipeline
+    let from_events = args.from.as_deref() == Some("events");
+    // Without plugins, compact events are written as they are parsed so large inputs
+    // never need to be held in memory; the parser enforces `max_events` itself
+    let stream = !from_events
+        && args.plugin.is_none()
+        && format == "events"
+        && !args.pretty
+        && !args.validate_events
+        && Limits {
+            max_events: None,
+            ..args.limits
         }
+        .is_unlimited();
+    let options = Options {
+        strict: args.strict,
+        max_doc_bytes: args.max_doc_bytes,
+        max_line_len: args.max_line_len,
+        max_blank_run: args.max_blank_run,
     };
 
-    if let Some(sp) = maybe_spinner {
-        sp.stop();
-    }
-
-    // Parse to events (MVP: full text as a single Text event)
-    let events = parse(
-        &input,
-        &Options {
-            strict: args.strict,
-            max_doc_bytes: args.max_doc_bytes,
-            max_line_len: args.max_line_len,
-            max_blank_run: args.max_blank_run,
-        },
-    );
+    let input: String;
+    let events: Vec<pendon_core::Event> = if from_events || stream {
+        let reader = match open_input(&args) {
+            Ok(r) => r,
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                return ExitCode::from(2);
+            }
+        };
+        if stream {
+            if let Some(sp) = maybe_spinner {
+                sp.stop();
+            }
+            let parser = Parser::from_reader(reader, &options);
+            return match args.limits.max_events {
+                Some(max) => stream_events(parser.limit_events(max)),
+                None => stream_events(parser),
+            };
+        }
+        let read = pendon_renderer_events::events_from_reader(reader);
+        if let Some(sp) = maybe_spinner {
+            sp.stop();
+        }
+        match read {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Error: invalid events input: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        // Events borrow their text from the input
+        input = match read_input(&args) {
+            Ok(s) => s,
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                return ExitCode::from(2);
+            }
+        };
+        if let Some(sp) = maybe_spinner {
+            sp.stop();
+        }
+        parse(&input, &options)
+    };
 
     let markdown_opts = MarkdownOptions {
         allow_html: args.markdown_allow_html,
+        extended_autolinks: args.markdown_extended_autolinks,
+        callouts: args.markdown_callouts,
+        ..MarkdownOptions::default()
     };
     let wiki_opts = WikiOptions {
         link_prefix: args.wiki_link_prefix.clone(),
     };
 
-    let mut used_custom_specs: Vec<PluginSpec> = Vec::new();
+    let mut registry = builtin_registry(markdown_opts, wiki_opts, None, args.limits, args.slug);
     let mut custom_cache: HashMap<String, PluginSpec> = HashMap::new();
-    let mut builtin_hints: Vec<SolidRenderHints> = Vec::new();
-    let mut used_quiz = false;
-    let mut used_vicado = false;
 
     // Optional plugin processing (supports comma-separated list and toml:foo.toml entries)
-    let events = if let Some(pstr) = args.plugin.as_deref() {
-        let mut ev = events;
-        let mut markdown_ran = false;
-        let mut quiz_pending = false;
-        for name in pstr.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
-            if let Some(path) = name.strip_prefix("toml:") {
-                let spec = match custom_cache.get(path) {
-                    Some(existing) => existing.clone(),
-                    None => match load_spec_from_path(path) {
-                        Ok(s) => {
-                            custom_cache.insert(path.to_string(), s.clone());
-                            s
-                        }
-                        Err(msg) => {
-                            eprintln!("Error: {}", msg);
-                            return ExitCode::from(2);
-                        }
-                    },
-                };
-                track_used_spec(&mut used_custom_specs, spec.clone());
-                ev = pendon_plugin_custom::process(&ev, &spec);
-                continue;
+    let names = plugin_names(args.plugin.as_deref());
+    let used_custom_specs =
+        match register_custom_plugins(&mut registry, &names, &HashMap::new(), &mut custom_cache) {
+            Ok(specs) => specs,
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                return ExitCode::from(2);
             }
-
-            ev = match name {
-                "micromatter" => pendon_plugin_micromatter::process(&ev),
-                "quiz" => {
-                    used_quiz = true;
-                    if markdown_ran {
-                        process_quiz(&ev)
-                    } else {
-                        quiz_pending = true;
-                        ev
-                    }
-                }
-                "dialog" => process_dialog(&ev),
-                "img" => process_img(&ev),
-                "latex" => process_latex(&ev),
-                "wiki" => process_wiki(&ev, wiki_opts.clone()),
-                "vicado" => {
-                    used_vicado = true;
-                    process_vicado(&ev)
-                }
-                "markdown" => {
-                    let processed =
-                        pendon_plugin_markdown::process_with_options(&ev, markdown_opts);
-                    markdown_ran = true;
-                    if quiz_pending {
-                        quiz_pending = false;
-                        process_quiz(&processed)
-                    } else {
-                        processed
-                    }
-                }
-                "sectionize" => pendon_plugin_sectionize::process(&ev),
-                "extract-heading" => pendon_plugin_extract_heading::process(&ev),
-                "syntect" => pendon_plugin_codeblock_syntect::process(&ev),
-                other => {
-                    if let Some(spec) = custom_cache.get(other) {
-                        track_used_spec(&mut used_custom_specs, spec.clone());
-                        pendon_plugin_custom::process(&ev, spec)
-                    } else {
-                        ev
-                    }
-                }
-            };
+        };
+    let names = match known_plugin_names(&registry, &names, args.strict) {
+        Ok(names) => names,
+        Err(msg) => {
+            eprintln!("Error: {}", msg);
+            return ExitCode::from(2);
         }
-        if quiz_pending {
-            ev = process_quiz(&ev);
+    };
+    let mut invalid = args.validate_events
+        && report_invalid_events(
+            if from_events {
+                "reading input"
+            } else {
+                "parse"
+            },
+            &events,
+        );
+    let validated = registry.run_inspect(&names, events, |name, events| {
+        if args.validate_events {
+            invalid |= report_invalid_events(&format!("plugin '{}'", name), events);
+        }
+    });
+    let (events, builtin_hints) = match validated {
+        Ok(out) => out,
+        Err(msg) => {
+            eprintln!("Error: {}", msg);
+            return ExitCode::from(2);
         }
-        ev
-    } else {
-        events
     };
 
-    if used_quiz {
-        builtin_hints.push(quiz_solid_hints());
-    }
-    if used_vicado {
-        builtin_hints.push(vicado_solid_hints());
-    }
-
-    // Determine if any Error diagnostics are present
-    let has_error = events.iter().any(|e| match e {
-        pendon_core::Event::Diagnostic { severity, .. } => {
-            matches!(severity, pendon_core::Severity::Error)
+    // Error diagnostics fail the run; list them so CI logs show their codes
+    let mut has_error = invalid;
+    for ev in &events {
+        if let pendon_core::Event::Diagnostic(diag) = ev {
+            if diag.is_error() {
+                report_diagnostic(diag, None);
+                has_error = true;
+            }
         }
-        _ => false,
-    });
+    }
 
     match format {
         "json" => match render_to_string(&events) {
@@ -319,6 +393,14 @@ fn main() -> ExitCode {
                 ExitCode::SUCCESS
             }
         }
+        "markdown" => {
+            print!("{}", pendon_renderer_markdown::render_markdown(&events));
+            if has_error {
+                ExitCode::from(2)
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
         "solid" => {
             let hints = merge_solid_hints(&used_custom_specs, &builtin_hints);
             let s = match hints.as_ref() {
@@ -334,7 +416,7 @@ fn main() -> ExitCode {
         }
         other => {
             eprintln!(
-                "Error: unsupported format '{}'. Try --format json|events|ast|html|solid",
+                "Error: unsupported format '{}'. Try --format json|events|ast|html|markdown|solid",
                 other
             );
             ExitCode::from(2)
@@ -349,13 +431,20 @@ struct ConfigTask {
     output: String,
     plugin: Option<String>,
     markdown_allow_html: Option<bool>,
+    markdown_extended_autolinks: Option<bool>,
+    markdown_callouts: Option<bool>,
     wiki_link_prefix: Option<String>,
+    slug: Option<String>,
     format: String,
     pretty: Option<bool>,
     strict: Option<bool>,
     max_doc_bytes: Option<usize>,
     max_line_len: Option<usize>,
     max_blank_run: Option<usize>,
+    max_depth: Option<usize>,
+    max_events: Option<usize>,
+    max_attr_bytes: Option<usize>,
+    max_formula_len: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -437,10 +526,28 @@ fn run_from_config() -> ExitCode {
         };
         let task_markdown_opts = MarkdownOptions {
             allow_html: task.markdown_allow_html.unwrap_or(false),
+            extended_autolinks: task.markdown_extended_autolinks.unwrap_or(false),
+            callouts: task.markdown_callouts.unwrap_or(false),
+            ..MarkdownOptions::default()
+        };
+        let task_limits = Limits {
+            max_depth: task.max_depth,
+            max_events: task.max_events,
+            max_attr_bytes: task.max_attr_bytes,
+            max_formula_len: task.max_formula_len,
         };
         let task_wiki_opts = WikiOptions {
             link_prefix: task.wiki_link_prefix.clone(),
         };
+        let task_slug = match task.slug.as_deref().map(parse_slug_strategy) {
+            Some(Ok(slug)) => slug,
+            Some(Err(msg)) => {
+                eprintln!("Error: {}", msg);
+                exit = ExitCode::from(2);
+                continue;
+            }
+            None => SlugStrategy::default(),
+        };
         let mut matched = 0usize;
         let mut total_bytes: usize = 0;
         let mut unique_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
@@ -481,111 +588,51 @@ fn run_from_config() -> ExitCode {
                             max_line_len: task.max_line_len,
                             max_blank_run: task.max_blank_run,
                         };
-                        let mut events = parse(&input_text, &opts);
-                        let mut used_custom_specs: Vec<PluginSpec> = Vec::new();
-                        let mut builtin_hints: Vec<SolidRenderHints> = Vec::new();
-                        let mut used_quiz = false;
-                        let mut used_vicado = false;
-                        let mut markdown_ran = false;
-                        let mut quiz_pending = false;
-                        if let Some(pstr) = task.plugin.as_deref() {
-                            for name in pstr.split(',').map(|s| s.trim()).filter(|s| !s.is_empty())
-                            {
-                                if let Some(path) = name.strip_prefix("toml:") {
-                                    let spec = match custom_cache.get(path) {
-                                        Some(existing) => existing.clone(),
-                                        None => match load_spec_from_path(path) {
-                                            Ok(s) => {
-                                                custom_cache.insert(path.to_string(), s.clone());
-                                                s
-                                            }
-                                            Err(msg) => {
-                                                eprintln!("Error: {}", msg);
-                                                exit = ExitCode::from(2);
-                                                break;
-                                            }
-                                        },
-                                    };
-                                    track_used_spec(&mut used_custom_specs, spec.clone());
-                                    events = pendon_plugin_custom::process(&events, &spec);
-                                    continue;
-                                }
-
-                                match name {
-                                    "micromatter" => {
-                                        events = pendon_plugin_micromatter::process(&events);
-                                    }
-                                    "quiz" => {
-                                        used_quiz = true;
-                                        if let Some(spec) = custom_registry.get("quiz") {
-                                            track_used_spec(&mut used_custom_specs, spec.clone());
-                                        }
-                                        if markdown_ran {
-                                            events = process_quiz(&events);
-                                        } else {
-                                            quiz_pending = true;
-                                        }
-                                    }
-                                    "dialog" => {
-                                        events = process_dialog(&events);
-                                    }
-                                    "img" => {
-                                        events = process_img(&events);
-                                    }
-                                    "latex" => {
-                                        events = process_latex(&events);
-                                    }
-                                    "wiki" => {
-                                        events = process_wiki(&events, task_wiki_opts.clone());
-                                    }
-                                    "vicado" => {
-                                        used_vicado = true;
-                                        events = process_vicado(&events);
-                                    }
-                                    "markdown" => {
-                                        events = pendon_plugin_markdown::process_with_options(
-                                            &events,
-                                            task_markdown_opts,
-                                        );
-                                        markdown_ran = true;
-                                        if quiz_pending {
-                                            quiz_pending = false;
-                                            events = process_quiz(&events);
-                                        }
-                                    }
-                                    "sectionize" => {
-                                        events = pendon_plugin_sectionize::process(&events);
-                                    }
-                                    "extract-heading" => {
-                                        events = pendon_plugin_extract_heading::process(&events);
-                                    }
-                                    "syntect" => {
-                                        events = pendon_plugin_codeblock_syntect::process(&events);
-                                    }
-                                    other => {
-                                        if let Some(spec) = custom_registry.get(other) {
-                                            let spec = spec.clone();
-                                            track_used_spec(&mut used_custom_specs, spec.clone());
-                                            events = pendon_plugin_custom::process(&events, &spec);
-                                        }
-                                    }
-                                }
+                        let events = parse(&input_text, &opts);
+                        let mut registry = builtin_registry(
+                            task_markdown_opts,
+                            task_wiki_opts.clone(),
+                            vicado_hints_override.clone(),
+                            task_limits,
+                            task_slug,
+                        );
+                        let names = plugin_names(task.plugin.as_deref());
+                        let processed = register_custom_plugins(
+                            &mut registry,
+                            &names,
+                            &custom_registry,
+                            &mut custom_cache,
+                        )
+                        .and_then(|specs| {
+                            let names = known_plugin_names(
+                                &registry,
+                                &names,
+                                task.strict.unwrap_or(false),
+                            )?;
+                            registry
+                                .run(&names, events)
+                                .map(|(events, hints)| (events, specs, hints))
+                        });
+                        let (events, used_custom_specs, builtin_hints) = match processed {
+                            Ok(out) => out,
+                            Err(msg) => {
+                                eprintln!("Error: {}", msg);
+                                exit = ExitCode::from(2);
+                                continue;
                             }
-                        }
-                        if quiz_pending {
-                            events = process_quiz(&events);
-                        }
-                        if used_quiz {
-                            builtin_hints.push(quiz_solid_hints());
-                        }
-                        if used_vicado {
-                            if let Some(override_hints) = vicado_hints_override.as_ref() {
-                                builtin_hints.push(override_hints.clone());
-                            } else {
-                                builtin_hints.push(vicado_solid_hints());
+                        };
+                        // A pipeline stopped by a limit has nothing worth writing
+                        let limit_hit = events.iter().find_map(|ev| match ev {
+                            pendon_core::Event::Diagnostic(diag)
+                                if pendon_core::is_limit_exceeded(diag) =>
+                            {
+                                Some(diag)
                             }
-                        }
-                        if exit != ExitCode::SUCCESS {
+                            _ => None,
+                        });
+                        if let Some(diag) = limit_hit {
+                            report_diagnostic(diag, Some(path_str.as_ref()));
+                            exit = ExitCode::from(2);
                             continue;
                         }
                         // CSS options are handled in renderer selection below
@@ -607,6 +654,7 @@ fn run_from_config() -> ExitCode {
                             } else {
                                 pendon_renderer_html::render_html(&events)
                             }),
+                            "markdown" => Ok(pendon_renderer_markdown::render_markdown(&events)),
                             "solid" => {
                                 let hints = merge_solid_hints(&used_custom_specs, &builtin_hints);
                                 Ok(match hints.as_ref() {
@@ -785,6 +833,120 @@ fn substitute_output(pattern: &str, vars: &HashMap<String, String>) -> Result<St
     Ok(out)
 }
 
+fn plugin_names(list: Option<&str>) -> Vec<&str> {
+    list.map(|s| {
+        s.split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn builtin_registry(
+    markdown_opts: MarkdownOptions,
+    wiki_opts: WikiOptions,
+    vicado_hints: Option<SolidRenderHints>,
+    limits: Limits,
+    slug: SlugStrategy,
+) -> PluginRegistry<SolidRenderHints> {
+    let mut registry = PluginRegistry::new();
+    registry.set_limits(limits);
+    registry.register(MicromatterPlugin);
+    registry.register(MarkdownPlugin {
+        options: MarkdownOptions {
+            max_depth: limits.max_depth,
+            max_events: limits.max_events,
+            ..markdown_opts
+        },
+    });
+    registry.register(QuizPlugin);
+    registry.register(DialogPlugin);
+    registry.register(ImgPlugin);
+    registry.register(LatexPlugin {
+        options: LatexOptions {
+            max_formula_len: limits.max_formula_len,
+        },
+    });
+    registry.register(WikiPlugin { options: wiki_opts });
+    registry.register(match vicado_hints {
+        Some(hints) => VicadoPlugin { hints },
+        None => VicadoPlugin::default(),
+    });
+    registry.register(SectionizePlugin {
+        options: SectionizeOptions { slug },
+    });
+    registry.register(ExtractHeadingPlugin {
+        options: ExtractHeadingOptions { slug },
+    });
+    registry.register(SyntectPlugin);
+    registry
+}
+
+// Registers `toml:<path>` entries and named custom specs that no builtin claims.
+// Returns the specs in use, whose Solid hints override builtin ones.
+fn register_custom_plugins(
+    registry: &mut PluginRegistry<SolidRenderHints>,
+    names: &[&str],
+    named_specs: &HashMap<String, PluginSpec>,
+    custom_cache: &mut HashMap<String, PluginSpec>,
+) -> Result<Vec<PluginSpec>, String> {
+    let mut used: Vec<PluginSpec> = Vec::new();
+    for name in names {
+        let spec = if let Some(path) = name.strip_prefix("toml:") {
+            let spec = match custom_cache.get(path) {
+                Some(existing) => existing.clone(),
+                None => {
+                    let spec = load_spec_from_path(path)?;
+                    custom_cache.insert(path.to_string(), spec.clone());
+                    spec
+                }
+            };
+            registry.register(CustomPlugin {
+                name: name.to_string(),
+                spec: spec.clone(),
+            });
+            spec
+        } else if let Some(spec) = named_specs.get(*name) {
+            if !registry.contains(name) {
+                registry.register(CustomPlugin {
+                    name: name.to_string(),
+                    spec: spec.clone(),
+                });
+            }
+            spec.clone()
+        } else {
+            continue;
+        };
+        track_used_spec(&mut used, spec);
+    }
+    Ok(used)
+}
+
+// Unknown names are skipped with a warning so older configs keep working;
+// `strict` turns them into an error
+fn known_plugin_names<'n>(
+    registry: &PluginRegistry<SolidRenderHints>,
+    names: &[&'n str],
+    strict: bool,
+) -> Result<Vec<&'n str>, String> {
+    let mut known = Vec::with_capacity(names.len());
+    for name in names {
+        if registry.contains(name) {
+            known.push(*name);
+        } else if strict {
+            return Err(format!("unknown plugin '{}'", name));
+        } else {
+            let diag = pendon_core::Diagnostic::warning(
+                pendon_core::codes::UNKNOWN_PLUGIN,
+                format!("unknown plugin '{}' skipped", name),
+            );
+            report_diagnostic(&diag, None);
+        }
+    }
+    Ok(known)
+}
+
 fn track_used_spec(list: &mut Vec<PluginSpec>, spec: PluginSpec) {
     if !list.iter().any(|s| s.name == spec.name) {
         list.push(spec);
@@ -974,6 +1136,80 @@ fn load_custom_registry(
     Ok(map)
 }
 
+fn stream_events(parser: impl Iterator<Item = pendon_core::Event<'static>>) -> ExitCode {
+    let mut errors: Vec<pendon_core::Diagnostic> = Vec::new();
+    let events = parser.inspect(|e| {
+        if let pendon_core::Event::Diagnostic(diag) = e {
+            if diag.is_error() {
+                errors.push((**diag).clone());
+            }
+        }
+    });
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let written =
+        pendon_renderer_events::render_events_to_writer(events, &mut out).and_then(|_| {
+            writeln!(out)
+                .and_then(|_| out.flush())
+                .map_err(serde_json::Error::io)
+        });
+    for diag in &errors {
+        report_diagnostic(diag, None);
+    }
+    match written {
+        Ok(()) if !errors.is_empty() => ExitCode::from(2),
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: failed to serialize events JSON: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Print validator findings for one pipeline stage; true when any were found
+fn report_invalid_events(stage: &str, events: &[pendon_core::Event]) -> bool {
+    let problems = pendon_core::validate(events);
+    for problem in &problems {
+        if let pendon_core::Event::Diagnostic(diag) = problem {
+            report_diagnostic(diag, Some(&format!("invalid events after {}", stage)));
+        }
+    }
+    !problems.is_empty()
+}
+
+// One diagnostic on stderr, e.g.
+// `Error[PD0002 blank-run-too-long]: blank run 2 exceeds limit 1 at 3:1`
+// followed by indented help and related-location lines
+fn report_diagnostic(diag: &pendon_core::Diagnostic, context: Option<&str>) {
+    let at = |span: Option<pendon_core::Span>| match span {
+        Some(sp) => format!(" at {}:{}", sp.start.line, sp.start.column),
+        None => String::new(),
+    };
+    let plugin = match &diag.plugin {
+        Some(name) => format!(" ({})", name),
+        None => String::new(),
+    };
+    let context = match context {
+        Some(text) => format!("{}: ", text),
+        None => String::new(),
+    };
+    eprintln!(
+        "{}[{}]{}: {}{}{}",
+        diag.severity.name(),
+        diag.code,
+        plugin,
+        context,
+        diag.message,
+        at(diag.span)
+    );
+    if let Some(help) = &diag.help {
+        eprintln!("  help: {}", help);
+    }
+    for rel in &diag.related {
+        eprintln!("  note: {}{}", rel.message, at(Some(rel.span)));
+    }
+}
+
 fn maybe_pretty(s: &str, pretty: bool) -> String {
     if !pretty {
         return s.to_string();
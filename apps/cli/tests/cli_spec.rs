@@ -128,6 +128,33 @@ fn file_not_found_errors() {
         .stderr(predicate::str::contains("Error: cannot read file"));
 }
 
+#[test]
+fn events_format_streams_from_stdin() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let input = "A\n\n\nB";
+    let output = cmd
+        .args(["-f", "events", "--strict", "--max-blank-run", "1"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let v: Value = serde_json::from_slice(&output).expect("valid JSON");
+    let events = v.as_array().expect("events array");
+    assert_eq!(
+        events.first().and_then(|e| e.get("node")),
+        Some(&Value::from("Document"))
+    );
+    let end = events.last().expect("closing event");
+    assert_eq!(end.get("type"), Some(&Value::from("End")));
+    assert_eq!(end["span"]["end"]["offset"], Value::from(input.len()));
+    assert!(events
+        .iter()
+        .any(|e| e.get("severity") == Some(&Value::from("Error"))));
+}
+
 #[test]
 fn strict_mode_exits_non_zero_on_blank_run_exceed() {
     let mut cmd = cargo_bin_cmd!("pendon");
@@ -139,6 +166,9 @@ fn strict_mode_exits_non_zero_on_blank_run_exceed() {
         .write_stdin(input)
         .assert()
         .failure()
+        .stderr(predicate::str::contains(
+            "Error[PD0002 blank-run-too-long]: blank run 2 exceeds limit 1",
+        ))
         .get_output()
         .stdout
         .clone();
@@ -148,9 +178,14 @@ fn strict_mode_exits_non_zero_on_blank_run_exceed() {
         .get("diagnostics")
         .and_then(|d| d.as_array())
         .expect("diagnostics array");
-    assert!(diagnostics
+    let diag = diagnostics
         .iter()
-        .any(|d| d.get("severity").and_then(|s| s.as_str()) == Some("Error")));
+        .find(|d| d.get("severity").and_then(|s| s.as_str()) == Some("Error"))
+        .expect("error diagnostic");
+    assert_eq!(diag["code"], "PD0002");
+    assert_eq!(diag["name"], "blank-run-too-long");
+    assert!(diag["help"].is_string());
+    assert!(diag.get("plugin").is_none());
 
     // Ensure both A and B text chunks are present in the document
     let mut texts: Vec<String> = Vec::new();
@@ -226,7 +261,7 @@ fn ast_format_heading_has_level_attr() {
         .success()
         .stdout(
             predicate::str::contains("\"type\":\"Heading\"")
-                .and(predicate::str::contains("\"level\":\"2\"")),
+                .and(predicate::str::contains("\"level\":2")),
         );
 }
 
@@ -282,7 +317,7 @@ fn ast_format_ordered_list_with_start_attr() {
         .success()
         .stdout(
             predicate::str::contains("\"type\":\"OrderedList\"")
-                .and(predicate::str::contains("\"start\":\"3\""))
+                .and(predicate::str::contains("\"start\":3"))
                 .and(predicate::str::contains("three"))
                 .and(predicate::str::contains("four")),
         );
@@ -525,11 +560,11 @@ fn ast_frontmatter_payload_is_exposed() {
         .iter()
         .find(|n| n.get("type").and_then(|t| t.as_str()) == Some("Frontmatter"))
         .expect("frontmatter node present");
-    let attrs = fm.get("attrs").and_then(|a| a.get("data"));
-    let data_str = attrs
-        .and_then(|d| d.as_str())
-        .expect("data attribute present");
-    let data: Value = serde_json::from_str(data_str).expect("frontmatter JSON");
+    let data = fm
+        .get("attrs")
+        .and_then(|a| a.get("data"))
+        .filter(|d| d.is_object())
+        .expect("data attribute present as an object");
     assert_eq!(data.get("title").and_then(|t| t.as_str()), Some("Demo"));
     assert_eq!(data.get("draft").and_then(|t| t.as_bool()), Some(false));
     assert_eq!(data.get("views").and_then(|t| t.as_i64()), Some(7));
@@ -570,12 +605,11 @@ fn json_renderer_carries_frontmatter_blockquote_and_table() {
     assert_eq!(v.get("type").and_then(|t| t.as_str()), Some("Document"));
 
     let fm = find_first(&v, "Frontmatter").expect("frontmatter node");
-    let data_str = fm
+    let data = fm
         .get("attrs")
         .and_then(|a| a.get("data"))
-        .and_then(|d| d.as_str())
+        .filter(|d| d.is_object())
         .expect("frontmatter data attr");
-    let data: Value = serde_json::from_str(data_str).expect("frontmatter json");
     assert_eq!(data.get("title").and_then(|t| t.as_str()), Some("Demo"));
 
     let bq = find_first(&v, "Blockquote").expect("blockquote node");
@@ -716,3 +750,172 @@ format = "solid"
     assert!(output.contains("code={\"<div>ok</div>\"}"));
     assert!(!output.contains("code={\"\\n<div>ok</div>\\n\"}"));
 }
+
+#[test]
+fn quiz_listed_before_markdown_still_runs_after_it() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let input = ":::quiz\n\nPick one\n\n- [ ] no\n- [x] yes\n\n:::\n";
+    let output = cmd
+        .args(["--plugin", "quiz,markdown", "--format", "ast"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let s = String::from_utf8(output).unwrap();
+    assert!(s.contains("\"Quiz\""), "{}", s);
+}
+
+#[test]
+fn unknown_plugin_is_skipped_with_a_warning() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    cmd.args(["--plugin", "markdown,nope", "--format", "html"])
+        .write_stdin("*x*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<em>x</em>"))
+        .stderr(predicate::str::contains(
+            "Warning[PD0030 unknown-plugin]: unknown plugin 'nope' skipped",
+        ));
+}
+
+#[test]
+fn unknown_plugin_is_rejected_in_strict_mode() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    cmd.args(["--plugin", "markdown,nope", "--strict"])
+        .write_stdin("x")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown plugin 'nope'"));
+}
+
+#[test]
+fn validate_events_accepts_builtin_pipeline() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let input = "# Title\n\n- a\n- b\n\npara\n\n## Next\n\ntext\n";
+    cmd.args([
+        "--plugin",
+        "markdown,sectionize",
+        "--format",
+        "html",
+        "--validate-events",
+    ])
+    .write_stdin(input)
+    .assert()
+    .success()
+    .stderr("");
+}
+
+#[test]
+fn nesting_past_max_depth_aborts_with_error() {
+    let input: String = (0..500)
+        .map(|i| format!("{}- item\n", "  ".repeat(i)))
+        .collect();
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let output = cmd
+        .args([
+            "--plugin",
+            "markdown",
+            "--format",
+            "ast",
+            "--max-depth",
+            "16",
+        ])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Error[PD0020 nesting-too-deep] (markdown)",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+
+    let v: Value = serde_json::from_slice(&output).expect("valid JSON");
+    assert!(v["children"].as_array().is_none_or(|c| c.is_empty()));
+    assert_eq!(v["diagnostics"][0]["code"], "PD0020");
+}
+
+#[test]
+fn streamed_events_stop_at_max_events() {
+    let input = "para\n\n".repeat(1000);
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let output = cmd
+        .args(["--format", "events", "--max-events", "50"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error[PD0021 too-many-events]"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let v: Value = serde_json::from_slice(&output).expect("valid JSON");
+    let events = v.as_array().expect("events array");
+    assert!(events.len() < 60, "{} events", events.len());
+}
+
+#[test]
+fn events_output_can_resume_the_pipeline() {
+    let input = "# Title\n\n- a\n- b\n\nsome *text*\n";
+    let direct = cargo_bin_cmd!("pendon")
+        .args(["--plugin", "markdown", "--format", "html"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let events = cargo_bin_cmd!("pendon")
+        .args(["--format", "events"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let resumed = cargo_bin_cmd!("pendon")
+        .args([
+            "--from",
+            "events",
+            "--plugin",
+            "markdown",
+            "--format",
+            "html",
+            "--validate-events",
+        ])
+        .write_stdin(events)
+        .assert()
+        .success()
+        .stderr("")
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(
+        String::from_utf8(resumed).unwrap(),
+        String::from_utf8(direct).unwrap()
+    );
+}
+
+#[test]
+fn malformed_events_input_is_rejected() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    cmd.args(["--from", "events"])
+        .write_stdin("[{\"type\":\"Start\"}]")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Error: invalid events input"));
+}
+
+#[test]
+fn markdown_format_writes_normalized_source() {
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let input = "---\ntitle: Hi\n---\n\n# Title\n\n* a\n* [b](u)\n";
+    cmd.args(["--plugin", "micromatter,markdown", "--format", "markdown"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("---\ntitle: Hi\n---\n\n# Title\n\n- a\n- [b](u)\n");
+}
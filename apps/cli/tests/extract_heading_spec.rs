@@ -68,3 +68,25 @@ title: Demo
     assert_eq!(baz.get("text").and_then(|v| v.as_str()), Some("Baz"));
     assert_eq!(baz.get("level").and_then(|v| v.as_u64()), Some(2));
 }
+
+#[test]
+fn github_heading_ids_without_sections_are_unicode_and_unique() {
+    let output = cargo_bin_cmd!("pendon")
+        .args(["--plugin", "markdown,extract-heading", "--format", "solid"])
+        .args(["--slug", "github"])
+        .write_stdin("## Über uns\n\n## Über uns\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let headings = parse_headings_export(&String::from_utf8_lossy(&output));
+    let ids: Vec<&str> = headings
+        .as_array()
+        .expect("headings array")
+        .iter()
+        .filter_map(|h| h.get("id").and_then(|v| v.as_str()))
+        .collect();
+    assert_eq!(ids, ["über-uns", "über-uns-1"]);
+}
@@ -58,3 +58,14 @@ fn html_ignores_frontmatter_block() {
     assert!(!contains("---").eval(&out));
     assert!(contains("<h1>Hello</h1>").eval(&out));
 }
+
+#[test]
+fn html_attribute_blocks_drop_script_attributes() {
+    let input = "![i](x){onerror=\"alert(1)\" width=50}\n\npara {onclick=\"alert(2)\" style=\"x\" .ok data-k=v}\n";
+    let (_code, out, _err) = run_cli(input, &["--plugin", "markdown", "--format", "html"]);
+    assert!(contains("<img alt=\"i\" src=\"x\" width=\"50\" />").eval(&out));
+    assert!(contains("<p class=\"ok\" data-k=\"v\">para</p>").eval(&out));
+    assert!(!contains("onerror").eval(&out), "{out}");
+    assert!(!contains("onclick").eval(&out), "{out}");
+    assert!(!contains("style").eval(&out), "{out}");
+}
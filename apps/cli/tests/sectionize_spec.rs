@@ -392,3 +392,63 @@ title: Demo
         "heading should appear in first or second section"
     );
 }
+
+fn section_ids(v: &Value) -> Vec<String> {
+    v.get("children")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|s| s.get("attrs")?.get("id")?.as_str().map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn section_ids_follow_slug_strategy() {
+    let input = "## Über uns\n\nx\n\n## はじめに\n\ny\n\n## はじめに\n\nz\n";
+    let run = |extra: &[&str]| {
+        let output = cargo_bin_cmd!("pendon")
+            .args(["--plugin", "markdown,sectionize", "--format", "ast"])
+            .args(extra)
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        section_ids(&parse_ast(&output))
+    };
+
+    assert_eq!(run(&[]), ["ber-uns", "section", "section-2"]);
+    assert_eq!(
+        run(&["--slug", "github"]),
+        ["über-uns", "はじめに", "はじめに-1"]
+    );
+}
+
+#[test]
+fn keeps_footnotes_outside_sections() {
+    let input = "## Body\n\nText[^n].\n\n[^n]: Note.\n";
+
+    let mut cmd = cargo_bin_cmd!("pendon");
+    let output = cmd
+        .arg("--plugin")
+        .arg("markdown,sectionize")
+        .arg("--format")
+        .arg("ast")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let v = parse_ast(&output);
+    let kinds: Vec<&str> = v
+        .get("children")
+        .and_then(|c| c.as_array())
+        .expect("children")
+        .iter()
+        .filter_map(|n| n.get("type").and_then(|t| t.as_str()))
+        .collect();
+    assert_eq!(kinds, vec!["Section", "FootnoteDefinition"]);
+}
@@ -71,3 +71,17 @@ fn solid_exports_frontmatter_object() {
     assert!(contains("<h1>Hello</h1>").eval(&out));
     assert!(!contains("---").eval(&out));
 }
+
+#[test]
+fn solid_attribute_blocks_drop_script_props() {
+    let input = "para {innerHTML=\"<img src=x onerror=alert(1)>\" ref=\"x\" textContent=t .ok}\n\n[a](/x){onClick=\"go()\" title=t}\n";
+    let (_code, out, _err) = run_cli(input, &["--plugin", "markdown", "--format", "solid"]);
+    assert!(contains("<p class=\"ok\">para").eval(&out), "{out}");
+    assert!(
+        contains("<a href=\"/x\" title=\"t\">a</a>").eval(&out),
+        "{out}"
+    );
+    for prop in ["innerHTML", " ref=", "textContent", "onClick", "onerror"] {
+        assert!(!contains(prop).eval(&out), "{prop} in {out}");
+    }
+}
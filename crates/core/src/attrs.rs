@@ -0,0 +1,149 @@
+/// Attributes of a Pandoc/kramdown-style block `{#id .class key="value"}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttrBlock {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    /// `key=value` pairs in source order.
+    pub pairs: Vec<(String, String)>,
+}
+
+impl AttrBlock {
+    /// Name/value pairs to attach to a node: `id`, `class` (space
+    /// separated), then the pairs.
+    pub fn attributes(&self) -> Vec<(String, String)> {
+        let mut out = Vec::with_capacity(self.pairs.len() + 2);
+        if let Some(id) = &self.id {
+            out.push(("id".to_string(), id.clone()));
+        }
+        if !self.classes.is_empty() {
+            out.push(("class".to_string(), self.classes.join(" ")));
+        }
+        out.extend(self.pairs.iter().cloned());
+        out
+    }
+}
+
+/// Whether an attribute block may set `name` on an element: `id`, `class`,
+/// `data-*` and a few presentational attributes. Anything else, notably
+/// `on*` handlers, `style` and framework props such as `innerHTML` or `ref`,
+/// is dropped so a document cannot script the page it renders into.
+pub fn is_safe_attr(name: &str) -> bool {
+    const SAFE: &[&str] = &[
+        "id", "class", "title", "lang", "dir", "width", "height", "target", "rel", "hreflang",
+    ];
+    SAFE.contains(&name)
+        || name
+            .strip_prefix("data-")
+            .is_some_and(|rest| !rest.is_empty())
+}
+
+/// Parse the inside of an attribute block (without the braces): blank
+/// separated `#id`, `.class` and `key=value` tokens, values optionally
+/// quoted. A leading kramdown `:` is allowed. Anything else, or nothing at
+/// all, is not an attribute block.
+pub fn parse_attrs(inner: &str) -> Option<AttrBlock> {
+    let chars: Vec<char> = inner.trim().chars().collect();
+    let mut block = AttrBlock::default();
+    let mut i = usize::from(chars.first() == Some(&':'));
+    let mut any = false;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        any = true;
+        match chars[i] {
+            '#' => {
+                let (id, next) = take_while(&chars, i + 1, is_id_char)?;
+                block.id = Some(id);
+                i = next;
+            }
+            '.' => {
+                let (class, next) = take_while(&chars, i + 1, is_name_char)?;
+                block.classes.push(class);
+                i = next;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '-' => {
+                let (key, next) = take_while(&chars, i, is_name_char)?;
+                if chars.get(next) != Some(&'=') {
+                    return None;
+                }
+                let (value, next) = take_value(&chars, next + 1)?;
+                match key.as_str() {
+                    "id" => block.id = Some(value),
+                    "class" => block
+                        .classes
+                        .extend(value.split_whitespace().map(str::to_string)),
+                    _ => block.pairs.push((key, value)),
+                }
+                i = next;
+            }
+            _ => return None,
+        }
+        // Tokens are separated by blanks
+        if chars.get(i).is_some_and(|c| !c.is_whitespace()) {
+            return None;
+        }
+    }
+    any.then_some(block)
+}
+
+/// A `{...}` attribute block closing `text` (trailing blanks ignored): the
+/// text before it, with trailing blanks removed, and its attributes.
+pub fn trailing_attrs(text: &str) -> Option<(&str, AttrBlock)> {
+    let trimmed = text.trim_end();
+    let body = trimmed.strip_suffix('}')?;
+    // Values may hold braces, so try each opening brace from the right
+    body.rmatch_indices('{')
+        .find_map(|(open, _)| parse_attrs(&body[open + 1..]).map(|block| (open, block)))
+        .map(|(open, block)| (trimmed[..open].trim_end(), block))
+}
+
+/// A `{...}` attribute block opening `text`: its attributes and the byte
+/// length it spans.
+pub fn leading_attrs(text: &str) -> Option<(AttrBlock, usize)> {
+    let rest = text.strip_prefix('{')?;
+    rest.match_indices('}')
+        .find_map(|(close, _)| parse_attrs(&rest[..close]).map(|block| (block, close + 2)))
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | ':')
+}
+
+fn take_while(chars: &[char], from: usize, keep: fn(char) -> bool) -> Option<(String, usize)> {
+    let len = chars[from..].iter().take_while(|c| keep(**c)).count();
+    (len > 0).then(|| (chars[from..from + len].iter().collect(), from + len))
+}
+
+// A quoted value (`\` escapes the quote) or a run of non-blank chars
+fn take_value(chars: &[char], from: usize) -> Option<(String, usize)> {
+    let quote = *chars.get(from)?;
+    if quote != '"' && quote != '\'' {
+        let len = chars[from..]
+            .iter()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '{' | '}'))
+            .count();
+        return (len > 0).then(|| (chars[from..from + len].iter().collect(), from + len));
+    }
+    let mut value = String::new();
+    let mut i = from + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1).is_some_and(|c| *c == quote || *c == '\\') => {
+                value.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return Some((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    None
+}
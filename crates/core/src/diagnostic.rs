@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+/// Stable identifier of a kind of diagnostic, e.g. `PD0001 line-too-long`.
+///
+/// `id` never changes once published and is what tooling should match on;
+/// `name` is a readable slug for the same code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiagnosticCode {
+    pub id: Cow<'static, str>,
+    pub name: Cow<'static, str>,
+}
+
+impl DiagnosticCode {
+    pub const fn new(id: &'static str, name: &'static str) -> Self {
+        DiagnosticCode {
+            id: Cow::Borrowed(id),
+            name: Cow::Borrowed(name),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.id, self.name)
+    }
+}
+
+/// Codes emitted by the core parser, validator and pipeline limits.
+///
+/// `PD00xx` is reserved for core, `PD01xx` for built-in plugins. Third-party
+/// plugins should use their own prefix.
+pub mod codes {
+    use super::DiagnosticCode;
+
+    pub const LINE_TOO_LONG: DiagnosticCode = DiagnosticCode::new("PD0001", "line-too-long");
+    pub const BLANK_RUN_TOO_LONG: DiagnosticCode =
+        DiagnosticCode::new("PD0002", "blank-run-too-long");
+    pub const DOCUMENT_TOO_LARGE: DiagnosticCode =
+        DiagnosticCode::new("PD0003", "document-too-large");
+    pub const INVALID_UTF8: DiagnosticCode = DiagnosticCode::new("PD0004", "invalid-utf8");
+    pub const READ_FAILED: DiagnosticCode = DiagnosticCode::new("PD0005", "read-failed");
+    pub const MULTIPLE_ROOTS: DiagnosticCode = DiagnosticCode::new("PD0010", "multiple-roots");
+    pub const MISMATCHED_END: DiagnosticCode = DiagnosticCode::new("PD0011", "mismatched-end");
+    pub const UNMATCHED_END: DiagnosticCode = DiagnosticCode::new("PD0012", "unmatched-end");
+    pub const OUTSIDE_NODE: DiagnosticCode = DiagnosticCode::new("PD0013", "outside-node");
+    pub const UNCLOSED_NODE: DiagnosticCode = DiagnosticCode::new("PD0014", "unclosed-node");
+    pub const NESTING_TOO_DEEP: DiagnosticCode = DiagnosticCode::new("PD0020", "nesting-too-deep");
+    pub const TOO_MANY_EVENTS: DiagnosticCode = DiagnosticCode::new("PD0021", "too-many-events");
+    pub const ATTRIBUTE_TOO_LARGE: DiagnosticCode =
+        DiagnosticCode::new("PD0022", "attribute-too-large");
+    pub const FORMULA_TOO_LONG: DiagnosticCode = DiagnosticCode::new("PD0023", "formula-too-long");
+    pub const UNKNOWN_PLUGIN: DiagnosticCode = DiagnosticCode::new("PD0030", "unknown-plugin");
+}
+
+/// Secondary location that explains a diagnostic, e.g. where a node opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Related {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Payload of [`Event::Diagnostic`](crate::Event::Diagnostic).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    /// Name of the plugin that emitted the diagnostic; `None` for the parser.
+    pub plugin: Option<String>,
+    pub message: String,
+    pub span: Option<Span>,
+    /// Suggestion for resolving the problem.
+    pub help: Option<String>,
+    pub related: Vec<Related>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            plugin: None,
+            message: message.into(),
+            span: None,
+            help: None,
+            related: Vec::new(),
+        }
+    }
+
+    pub fn error(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Warning, code, message)
+    }
+
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_plugin(mut self, plugin: impl Into<String>) -> Self {
+        self.plugin = Some(plugin.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_related(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.related.push(Related {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Whether `\` followed by `ch` is a backslash escape: any ASCII punctuation.
+pub fn is_escapable(ch: char) -> bool {
+    ch.is_ascii_punctuation()
+}
+
+/// Decode the entity reference `input` starts with (`&amp;`, `&#35;`,
+/// `&#x2014;`), returning its text and byte length. Named references must be
+/// HTML5 entities and every form needs its closing `;`. Invalid code points
+/// decode to U+FFFD.
+pub fn entity_at(input: &str) -> Option<(Cow<'static, str>, usize)> {
+    let body = input.strip_prefix('&')?;
+    let end = body.find(';')?;
+    let name = &body[..end];
+    let len = end + 2;
+    if let Some(num) = name.strip_prefix('#') {
+        let (digits, radix, max) = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 6),
+            None => (num, 10, 7),
+        };
+        if digits.is_empty() || digits.len() > max || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        let ch = u32::from_str_radix(digits, radix)
+            .ok()
+            .filter(|code| *code != 0)
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        return Some((Cow::Owned(ch.to_string()), len));
+    }
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let text = named_entities().get(&input[..len])?;
+    Some((Cow::Borrowed(*text), len))
+}
+
+// HTML5 named references, `&` and `;` included, and the text they decode to
+fn named_entities() -> &'static HashMap<&'static str, &'static str> {
+    static TABLE: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        entities::ENTITIES
+            .iter()
+            .map(|e| (e.entity, e.characters))
+            .collect()
+    })
+}
+
+/// Decode backslash escapes and entity references in `input`, as in link
+/// destinations, titles and fence info strings. Borrows when there is
+/// nothing to decode.
+pub fn unescape(input: &str) -> Cow<'_, str> {
+    if !input.contains(['\\', '&']) {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(ch) = rest.chars().next() {
+        match ch {
+            '\\' => match rest[1..].chars().next().filter(|c| is_escapable(*c)) {
+                Some(escaped) => {
+                    out.push(escaped);
+                    rest = &rest[1 + escaped.len_utf8()..];
+                    continue;
+                }
+                None => out.push('\\'),
+            },
+            '&' => {
+                if let Some((text, len)) = entity_at(rest) {
+                    out.push_str(&text);
+                    rest = &rest[len..];
+                    continue;
+                }
+                out.push('&');
+            }
+            other => out.push(other),
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    Cow::Owned(out)
+}
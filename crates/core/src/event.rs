@@ -1,17 +1,23 @@
 use std::borrow::Cow;
 
+use crate::{AttrValue, Diagnostic, Span};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NodeKind {
     Document,
     Frontmatter,
     Paragraph,
     Blockquote,
+    Callout,
     CodeFence,
     Heading,
     ThematicBreak,
     BulletList,
     OrderedList,
     ListItem,
+    DefinitionList,
+    DefinitionTerm,
+    DefinitionDescription,
     Table,
     TableHead,
     TableBody,
@@ -19,6 +25,7 @@ pub enum NodeKind {
     TableCell,
     Section,
     HtmlBlock,
+    FootnoteDefinition,
     // Inline nodes
     Emphasis,
     Strong,
@@ -26,6 +33,8 @@ pub enum NodeKind {
     Link,
     Bold,
     Italic,
+    Strikethrough,
+    FootnoteReference,
     HtmlInline,
     Image,
     // Custom node kinds (e.g., Component, user-defined)
@@ -39,12 +48,16 @@ impl NodeKind {
             NodeKind::Frontmatter => Cow::Borrowed("Frontmatter"),
             NodeKind::Paragraph => Cow::Borrowed("Paragraph"),
             NodeKind::Blockquote => Cow::Borrowed("Blockquote"),
+            NodeKind::Callout => Cow::Borrowed("Callout"),
             NodeKind::CodeFence => Cow::Borrowed("CodeFence"),
             NodeKind::Heading => Cow::Borrowed("Heading"),
             NodeKind::ThematicBreak => Cow::Borrowed("ThematicBreak"),
             NodeKind::BulletList => Cow::Borrowed("BulletList"),
             NodeKind::OrderedList => Cow::Borrowed("OrderedList"),
             NodeKind::ListItem => Cow::Borrowed("ListItem"),
+            NodeKind::DefinitionList => Cow::Borrowed("DefinitionList"),
+            NodeKind::DefinitionTerm => Cow::Borrowed("DefinitionTerm"),
+            NodeKind::DefinitionDescription => Cow::Borrowed("DefinitionDescription"),
             NodeKind::Table => Cow::Borrowed("Table"),
             NodeKind::TableHead => Cow::Borrowed("TableHead"),
             NodeKind::TableBody => Cow::Borrowed("TableBody"),
@@ -52,45 +65,119 @@ impl NodeKind {
             NodeKind::TableCell => Cow::Borrowed("TableCell"),
             NodeKind::Section => Cow::Borrowed("Section"),
             NodeKind::HtmlBlock => Cow::Borrowed("HtmlBlock"),
+            NodeKind::FootnoteDefinition => Cow::Borrowed("FootnoteDefinition"),
             NodeKind::Emphasis => Cow::Borrowed("Emphasis"),
             NodeKind::Strong => Cow::Borrowed("Strong"),
             NodeKind::InlineCode => Cow::Borrowed("InlineCode"),
             NodeKind::Link => Cow::Borrowed("Link"),
             NodeKind::Bold => Cow::Borrowed("Bold"),
             NodeKind::Italic => Cow::Borrowed("Italic"),
+            NodeKind::Strikethrough => Cow::Borrowed("Strikethrough"),
+            NodeKind::FootnoteReference => Cow::Borrowed("FootnoteReference"),
             NodeKind::HtmlInline => Cow::Borrowed("HtmlInline"),
             NodeKind::Image => Cow::Borrowed("Image"),
             NodeKind::Custom(name) => Cow::Owned(name.clone()),
         }
     }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Span {
-    pub start: usize,
-    pub end: usize,
-}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Severity {
-    Warning,
-    Error,
+    /// Inverse of [`NodeKind::name`]; names of no builtin kind become `Custom`.
+    pub fn from_name(name: &str) -> NodeKind {
+        match name {
+            "Document" => NodeKind::Document,
+            "Frontmatter" => NodeKind::Frontmatter,
+            "Paragraph" => NodeKind::Paragraph,
+            "Blockquote" => NodeKind::Blockquote,
+            "Callout" => NodeKind::Callout,
+            "CodeFence" => NodeKind::CodeFence,
+            "Heading" => NodeKind::Heading,
+            "ThematicBreak" => NodeKind::ThematicBreak,
+            "BulletList" => NodeKind::BulletList,
+            "OrderedList" => NodeKind::OrderedList,
+            "ListItem" => NodeKind::ListItem,
+            "DefinitionList" => NodeKind::DefinitionList,
+            "DefinitionTerm" => NodeKind::DefinitionTerm,
+            "DefinitionDescription" => NodeKind::DefinitionDescription,
+            "Table" => NodeKind::Table,
+            "TableHead" => NodeKind::TableHead,
+            "TableBody" => NodeKind::TableBody,
+            "TableRow" => NodeKind::TableRow,
+            "TableCell" => NodeKind::TableCell,
+            "Section" => NodeKind::Section,
+            "HtmlBlock" => NodeKind::HtmlBlock,
+            "FootnoteDefinition" => NodeKind::FootnoteDefinition,
+            "Emphasis" => NodeKind::Emphasis,
+            "Strong" => NodeKind::Strong,
+            "InlineCode" => NodeKind::InlineCode,
+            "Link" => NodeKind::Link,
+            "Bold" => NodeKind::Bold,
+            "Italic" => NodeKind::Italic,
+            "Strikethrough" => NodeKind::Strikethrough,
+            "FootnoteReference" => NodeKind::FootnoteReference,
+            "HtmlInline" => NodeKind::HtmlInline,
+            "Image" => NodeKind::Image,
+            other => NodeKind::Custom(other.to_string()),
+        }
+    }
 }
 
+// Text and attribute payloads borrow from the source when possible, so events
+// passed through a plugin untouched are cloned without allocating
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Event {
-    StartNode(NodeKind),
-    EndNode(NodeKind),
-    Text(String),
+pub enum Event<'a> {
+    // Every event carries the source range it was derived from, when known
+    StartNode(NodeKind, Option<Span>),
+    EndNode(NodeKind, Option<Span>),
+    Text(Cow<'a, str>, Option<Span>),
     // Node attribute attached to the nearest open node
     Attribute {
-        name: String,
-        value: String,
-    },
-    // Non-fatal diagnostic event; does not affect renderer concatenation
-    Diagnostic {
-        severity: Severity,
-        message: String,
+        name: Cow<'a, str>,
+        value: AttrValue<'a>,
         span: Option<Span>,
     },
+    // Non-fatal diagnostic event; does not affect renderer concatenation.
+    // Boxed so the rarely used payload does not grow every event
+    Diagnostic(Box<Diagnostic>),
+}
+
+impl<'a> Event<'a> {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Event::StartNode(_, span) | Event::EndNode(_, span) | Event::Text(_, span) => *span,
+            Event::Attribute { span, .. } => *span,
+            Event::Diagnostic(diag) => diag.span,
+        }
+    }
+
+    /// Replace the span carried by this event.
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        match &mut self {
+            Event::StartNode(_, slot) | Event::EndNode(_, slot) | Event::Text(_, slot) => {
+                *slot = span
+            }
+            Event::Attribute { span: slot, .. } => *slot = span,
+            Event::Diagnostic(diag) => diag.span = span,
+        }
+        self
+    }
+
+    /// Detach the event from the buffer it borrows from.
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Event::StartNode(kind, span) => Event::StartNode(kind, span),
+            Event::EndNode(kind, span) => Event::EndNode(kind, span),
+            Event::Text(text, span) => Event::Text(Cow::Owned(text.into_owned()), span),
+            Event::Attribute { name, value, span } => Event::Attribute {
+                name: Cow::Owned(name.into_owned()),
+                value: value.into_owned(),
+                span,
+            },
+            Event::Diagnostic(diag) => Event::Diagnostic(diag),
+        }
+    }
+}
+
+impl From<Diagnostic> for Event<'_> {
+    fn from(diag: Diagnostic) -> Self {
+        Event::Diagnostic(Box::new(diag))
+    }
 }
@@ -1,6 +1,67 @@
 use std::collections::HashMap;
 
+use crate::attrs::{trailing_attrs, AttrBlock};
+
+/// How heading text becomes an anchor id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlugStrategy {
+    /// GitHub's algorithm: lowercase, keep Unicode letters, digits, `_` and
+    /// `-`, turn each space into `-`; duplicates get `-1`, `-2`, ...
+    GitHub,
+    /// ASCII letters and digits only, with runs of ` -_.` collapsed into one
+    /// `-`; duplicates get `-2`, `-3`, ...
+    #[default]
+    Ascii,
+}
+
+impl SlugStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SlugStrategy::GitHub => "github",
+            SlugStrategy::Ascii => "ascii",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SlugStrategy> {
+        match name {
+            "github" => Some(SlugStrategy::GitHub),
+            "ascii" => Some(SlugStrategy::Ascii),
+            _ => None,
+        }
+    }
+}
+
+/// [`slugify_with`] using the default [`SlugStrategy::Ascii`].
 pub fn slugify(input: &str) -> String {
+    slugify_with(input, SlugStrategy::default())
+}
+
+/// Anchor id for `input`; text with nothing to keep becomes `section`.
+pub fn slugify_with(input: &str, strategy: SlugStrategy) -> String {
+    let out = match strategy {
+        SlugStrategy::GitHub => github_slug(input),
+        SlugStrategy::Ascii => ascii_slug(input),
+    };
+    if out.is_empty() {
+        "section".to_string()
+    } else {
+        out
+    }
+}
+
+fn github_slug(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.trim().chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() || ch == '_' || ch == '-' {
+            out.push(ch);
+        } else if ch == ' ' {
+            out.push('-');
+        }
+    }
+    out
+}
+
+fn ascii_slug(input: &str) -> String {
     let mut out = String::new();
     let mut last_dash = false;
     for ch in input.chars() {
@@ -8,41 +69,25 @@ pub fn slugify(input: &str) -> String {
         if lower.is_ascii_alphanumeric() {
             out.push(lower);
             last_dash = false;
-        } else if matches!(lower, ' ' | '-' | '_' | '.') {
-            if !last_dash && !out.is_empty() {
-                out.push('-');
-                last_dash = true;
-            }
+        } else if matches!(lower, ' ' | '-' | '_' | '.') && !last_dash && !out.is_empty() {
+            out.push('-');
+            last_dash = true;
         }
     }
     if out.ends_with('-') {
         out.pop();
     }
-    if out.is_empty() {
-        "section".to_string()
-    } else {
-        out
-    }
+    out
 }
 
+/// Split a trailing attribute block off heading text, returning the text
+/// and the block's `#id` if it has one (`Title {#intro .lead}`).
 pub fn extract_id(text: &str) -> (String, Option<String>) {
     let trimmed = text.trim_end();
-    if trimmed.ends_with('}') {
-        if let Some(start) = trimmed.rfind("{#") {
-            if start + 2 < trimmed.len() - 1 {
-                let candidate = &trimmed[start + 2..trimmed.len() - 1];
-                if !candidate.is_empty()
-                    && candidate
-                        .chars()
-                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
-                {
-                    let before = trimmed[..start].trim_end();
-                    return (before.to_string(), Some(candidate.to_string()));
-                }
-            }
-        }
+    match trailing_attrs(trimmed) {
+        Some((before, AttrBlock { id: Some(id), .. })) => (before.to_string(), Some(id)),
+        _ => (trimmed.to_string(), None),
     }
-    (trimmed.to_string(), None)
 }
 
 pub fn strip_trailing_id(text: &str) -> (String, bool) {
@@ -50,13 +95,40 @@ pub fn strip_trailing_id(text: &str) -> (String, bool) {
     (clean, id.is_some())
 }
 
+/// [`ensure_unique_with`] using the default [`SlugStrategy::Ascii`]
+/// suffixes, matching [`slugify`].
 pub fn ensure_unique(base: String, used: &mut HashMap<String, usize>) -> String {
-    let counter = used.entry(base.clone()).or_insert(0);
-    if *counter == 0 {
-        *counter = 1;
-        base
-    } else {
-        *counter += 1;
-        format!("{}-{}", base, *counter)
+    ensure_unique_with(base, used, SlugStrategy::default())
+}
+
+/// Return `base`, or `base` with the strategy's numeric suffix if an earlier
+/// call already handed it out. `used` tracks ids across one document.
+pub fn ensure_unique_with(
+    base: String,
+    used: &mut HashMap<String, usize>,
+    strategy: SlugStrategy,
+) -> String {
+    match strategy {
+        SlugStrategy::Ascii => {
+            let counter = used.entry(base.clone()).or_insert(0);
+            if *counter == 0 {
+                *counter = 1;
+                base
+            } else {
+                *counter += 1;
+                format!("{}-{}", base, *counter)
+            }
+        }
+        // Like github-slugger: skip suffixed ids that a heading already took
+        SlugStrategy::GitHub => {
+            let mut id = base.clone();
+            while used.contains_key(&id) {
+                let count = used.entry(base.clone()).or_insert(0);
+                *count += 1;
+                id = format!("{}-{}", base, count);
+            }
+            used.insert(id.clone(), 0);
+            id
+        }
     }
 }
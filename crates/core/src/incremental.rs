@@ -0,0 +1,258 @@
+use std::ops::Range;
+
+use crate::parser::LineState;
+use crate::{
+    events_span, join_spans, parse, propagate_node_spans, tokenize_spanned_at, Event, NodeKind,
+    Options, Position, Span,
+};
+
+/// Replacement of the byte `range` of the previous source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        TextEdit {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// The source that results from applying the edit to `text`, or `None`
+    /// if the range is reversed, runs past the end of `text` or splits a
+    /// character.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        if self.range.start > self.range.end {
+            return None;
+        }
+        let head = text.get(..self.range.start)?;
+        let tail = text.get(self.range.end..)?;
+        let mut out = String::with_capacity(text.len() + self.replacement.len());
+        out.push_str(head);
+        out.push_str(&self.replacement);
+        out.push_str(tail);
+        Some(out)
+    }
+
+    // Byte offset in the new source where the unchanged tail begins
+    fn new_end(&self) -> usize {
+        self.range.start + self.replacement.len()
+    }
+}
+
+/// Updated event stream returned by [`reparse`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reparse<'a> {
+    pub events: Vec<Event<'a>>,
+    /// Indices into `events` that were freshly parsed; everything outside was
+    /// carried over from the previous stream (with spans shifted).
+    pub reparsed: Range<usize>,
+}
+
+/// Update the result of [`parse`] after `edit`, reparsing only the top-level
+/// blocks the edit touches.
+///
+/// `previous` must be the `parse` output for the source before the edit,
+/// with the same `options`; `source` is the text after it. The returned
+/// events equal `parse(source, options)`. Streams that do not line up with
+/// the edit, and documents checked against `max_doc_bytes`, are parsed in
+/// full. An edit whose range is reversed, or whose replacement does not fit
+/// `source` at character boundaries, is rejected with `None`.
+///
+/// Only the core parse is updated. Plugins such as `markdown` read the
+/// whole stream, so their output must be recomputed from `events` by the
+/// caller, e.g. with [`PluginRegistry::run`](crate::PluginRegistry::run).
+pub fn reparse<'a>(
+    previous: &[Event<'_>],
+    edit: &TextEdit,
+    source: &'a str,
+    options: &Options,
+) -> Option<Reparse<'a>> {
+    if edit.range.start > edit.range.end || source.get(edit.range.start..edit.new_end()).is_none() {
+        return None;
+    }
+    Some(splice(previous, edit, source, options).unwrap_or_else(|| {
+        let events = parse(source, options);
+        let reparsed = 0..events.len();
+        Reparse { events, reparsed }
+    }))
+}
+
+fn splice<'a>(
+    previous: &[Event<'_>],
+    edit: &TextEdit,
+    source: &'a str,
+    options: &Options,
+) -> Option<Reparse<'a>> {
+    if options.max_doc_bytes.is_some() {
+        return None;
+    }
+    let (first, rest) = previous.split_first()?;
+    let (last, body) = rest.split_last()?;
+    let old_len = match (first, last) {
+        (Event::StartNode(NodeKind::Document, _), Event::EndNode(NodeKind::Document, Some(sp))) => {
+            sp.end.offset
+        }
+        _ => return None,
+    };
+    let TextEdit { range, replacement } = edit;
+    let new_end = edit.new_end();
+    if range.start > range.end
+        || range.end > old_len
+        || source.len() != old_len - range.len() + replacement.len()
+        || source.get(range.start..new_end) != Some(replacement.as_str())
+    {
+        return None;
+    }
+
+    let bounds = boundaries(body)?;
+    // Resume at the last boundary before the edit that starts a line
+    let (restart, idx) = bounds
+        .iter()
+        .rev()
+        .find(|(pos, _)| pos.offset <= range.start && line_start(source, pos.offset))
+        .copied()
+        .unwrap_or((Position::default(), 0));
+    let prefix = &body[..idx];
+
+    let mut state = LineState::resume(restart, newline_run(source, restart.offset).1);
+    let mut fresh: Vec<Event<'a>> = Vec::new();
+    let mut tail: Option<(usize, Position)> = None;
+    let mut pos = restart;
+    for line in lines(&source[restart.offset..]) {
+        for token in tokenize_spanned_at(line, pos) {
+            // Token spans, unlike `Position::advance`, count a lone CR as a line break
+            pos = token.span.end;
+            state.token(token, options, &mut fresh);
+        }
+        if !line.ends_with(['\n', '\r']) || pos.offset <= new_end || !state.at_block_boundary() {
+            continue;
+        }
+        // Past the edit, the parse can rejoin the previous stream at a line
+        // where both had no open block and saw the same blank run
+        if newline_run(source, pos.offset).0 <= new_end {
+            continue;
+        }
+        let old_offset = pos.offset - new_end + range.end;
+        if let Ok(at) = bounds.binary_search_by_key(&old_offset, |(p, _)| p.offset) {
+            tail = Some((bounds[at].1, bounds[at].0));
+            break;
+        }
+    }
+
+    let suffix: Vec<Event<'a>> = match tail {
+        Some((at, old_pos)) => {
+            let offset = pos.offset as isize - old_pos.offset as isize;
+            let lines = pos.line as isize - old_pos.line as isize;
+            body[at..]
+                .iter()
+                .map(|ev| {
+                    let span = ev.span().map(|sp| {
+                        Span::new(shift(sp.start, offset, lines), shift(sp.end, offset, lines))
+                    });
+                    ev.clone().into_owned().with_span(span)
+                })
+                .collect()
+        }
+        None => {
+            state.close_all(&mut fresh);
+            Vec::new()
+        }
+    };
+    propagate_node_spans(&mut fresh);
+
+    let start = 1 + prefix.len();
+    let mut events = Vec::with_capacity(start + fresh.len() + suffix.len() + 1);
+    events.push(Event::StartNode(NodeKind::Document, None));
+    events.extend(prefix.iter().map(|ev| ev.clone().into_owned()));
+    let reparsed = start..start + fresh.len();
+    events.extend(fresh);
+    events.extend(suffix);
+    // Same widening `parse` applies to the document node
+    let doc_span = join_spans(
+        Some(Span::covering(Position::default(), source)),
+        events_span(&events[1..]),
+    );
+    events[0] = Event::StartNode(NodeKind::Document, doc_span);
+    events.push(Event::EndNode(NodeKind::Document, doc_span));
+    Some(Reparse { events, reparsed })
+}
+
+// Positions where the previous stream had no open block, with the index of
+// the first event emitted from there. Every event needs a span.
+fn boundaries(body: &[Event]) -> Option<Vec<(Position, usize)>> {
+    let mut out: Vec<(Position, usize)> = Vec::new();
+    let mut depth = 0usize;
+    for (idx, ev) in body.iter().enumerate() {
+        let span = ev.span()?;
+        let fresh_offset = out.last().is_none_or(|(p, _)| p.offset < span.start.offset);
+        if depth == 0 && fresh_offset && matches!(ev, Event::StartNode(..) | Event::Text(..)) {
+            out.push((span.start, idx));
+        }
+        match ev {
+            Event::StartNode(..) => depth += 1,
+            Event::EndNode(..) => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+    }
+    Some(out)
+}
+
+// Lines including their terminator; LF, CRLF and lone CR all end a line,
+// as in the lexer
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    let mut from = 0;
+    std::iter::from_fn(move || {
+        if from >= bytes.len() {
+            return None;
+        }
+        let start = from;
+        from = match bytes[start..]
+            .iter()
+            .position(|b| matches!(b, b'\n' | b'\r'))
+        {
+            Some(at) if bytes[start + at] == b'\r' && bytes.get(start + at + 1) == Some(&b'\n') => {
+                start + at + 2
+            }
+            Some(at) => start + at + 1,
+            None => bytes.len(),
+        };
+        Some(&text[start..from])
+    })
+}
+
+fn line_start(source: &str, offset: usize) -> bool {
+    let bytes = source.as_bytes();
+    match offset.checked_sub(1).map(|at| bytes[at]) {
+        None => true,
+        Some(b'\n') => true,
+        Some(b'\r') => bytes.get(offset) != Some(&b'\n'),
+        Some(_) => false,
+    }
+}
+
+// Start offset and length of the run of line breaks ending at `offset`
+fn newline_run(source: &str, offset: usize) -> (usize, usize) {
+    let bytes = source.as_bytes();
+    let (mut at, mut count) = (offset, 0);
+    while at > 0 && matches!(bytes[at - 1], b'\n' | b'\r') {
+        if bytes[at - 1] == b'\n' && at > 1 && bytes[at - 2] == b'\r' {
+            at -= 1;
+        }
+        at -= 1;
+        count += 1;
+    }
+    (at, count)
+}
+
+fn shift(pos: Position, offset: isize, lines: isize) -> Position {
+    Position::new(
+        pos.offset.wrapping_add_signed(offset),
+        pos.line.wrapping_add_signed(lines),
+        pos.column,
+    )
+}
@@ -1,33 +1,88 @@
+use crate::{Position, Span};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Token<'a> {
     Text(&'a str),
     Newline,
     // Detectors (not yet used by parser; reserved for future)
     FenceBackticks(usize), // count of backticks at line start
+    FenceTildes(usize),    // count of tildes (3+) at line start
     Hashes(usize),         // count of '#'
+    // 1-3 spaces of indentation before a fence run
+    Indent(usize),
+}
+
+/// A token together with the source range it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpannedToken<'a> {
+    pub token: Token<'a>,
+    pub span: Span,
 }
 
 pub fn tokenize<'a>(input: &'a str) -> Vec<Token<'a>> {
+    tokenize_spanned(input)
+        .into_iter()
+        .map(|t| t.token)
+        .collect()
+}
+
+pub fn tokenize_spanned<'a>(input: &'a str) -> Vec<SpannedToken<'a>> {
+    tokenize_spanned_at(input, Position::default())
+}
+
+/// Tokenize `input` as if it began at `start` in a larger document. `start`
+/// must be at a line boundary so line-start detectors behave as in a full pass.
+pub fn tokenize_spanned_at<'a>(input: &'a str, start: Position) -> Vec<SpannedToken<'a>> {
     let bytes = input.as_bytes();
     let mut i = 0;
     let mut out = Vec::with_capacity(8 + input.len() / 8);
+    let mut pos = start;
+    // Tokens are always cut at ASCII bytes, so `start..i` is a char boundary range
+    let mut push = |out: &mut Vec<SpannedToken<'a>>, token: Token<'a>, start: usize, end: usize| {
+        let from = pos;
+        pos = if matches!(token, Token::Newline) {
+            Position::new(from.offset + (end - start), from.line + 1, 1)
+        } else {
+            from.advance(&input[start..end])
+        };
+        out.push(SpannedToken {
+            token,
+            span: Span::new(from, pos),
+        });
+    };
+
+    let run = |from: usize, b: u8| bytes[from..].iter().take_while(|c| **c == b).count();
 
     let mut line_start = true;
     while i < bytes.len() {
         match bytes[i] {
+            b' ' if line_start && {
+                let spaces = run(i, b' ');
+                spaces <= 3
+                    && i + spaces < bytes.len()
+                    && matches!(bytes[i + spaces], b'`' | b'~')
+                    && run(i + spaces, bytes[i + spaces]) >= 3
+            } =>
+            {
+                // Indented fence: keep `line_start` so the run is still detected
+                let spaces = run(i, b' ');
+                push(&mut out, Token::Indent(spaces), i, i + spaces);
+                i += spaces;
+            }
             b'\r' => {
                 // CRLF -> single newline
+                let start = i;
                 if i + 1 < bytes.len() && bytes[i + 1] == b'\n' {
                     i += 2;
                 } else {
                     i += 1;
                 }
-                out.push(Token::Newline);
+                push(&mut out, Token::Newline, start, i);
                 line_start = true;
             }
             b'\n' => {
                 i += 1;
-                out.push(Token::Newline);
+                push(&mut out, Token::Newline, i - 1, i);
                 line_start = true;
             }
             b'`' if line_start => {
@@ -38,12 +93,18 @@ pub fn tokenize<'a>(input: &'a str) -> Vec<Token<'a>> {
                 }
                 let count = i - start;
                 if count >= 3 {
-                    out.push(Token::FenceBackticks(count));
+                    push(&mut out, Token::FenceBackticks(count), start, i);
                 } else {
-                    out.push(Token::Text(&input[start..i]));
+                    push(&mut out, Token::Text(&input[start..i]), start, i);
                 }
                 line_start = false;
             }
+            b'~' if line_start && run(i, b'~') >= 3 => {
+                let start = i;
+                i += run(i, b'~');
+                push(&mut out, Token::FenceTildes(i - start), start, i);
+                line_start = false;
+            }
             b'#' if line_start => {
                 // Detect heading hashes run
                 let start = i;
@@ -51,10 +112,10 @@ pub fn tokenize<'a>(input: &'a str) -> Vec<Token<'a>> {
                     i += 1;
                 }
                 let count = i - start;
-                out.push(Token::Hashes(count));
+                push(&mut out, Token::Hashes(count), start, i);
                 // consume following single space if present as text for now
                 if i < bytes.len() && bytes[i] == b' ' {
-                    out.push(Token::Text(" "));
+                    push(&mut out, Token::Text(" "), i, i + 1);
                     i += 1;
                 }
                 line_start = false;
@@ -71,7 +132,7 @@ pub fn tokenize<'a>(input: &'a str) -> Vec<Token<'a>> {
                     }
                 }
                 if start != i {
-                    out.push(Token::Text(&input[start..i]));
+                    push(&mut out, Token::Text(&input[start..i]), start, i);
                 }
                 line_start = false;
             }
@@ -1,11 +1,34 @@
+mod attrs;
+mod diagnostic;
+mod escape;
 mod event;
 mod heading;
+mod incremental;
 mod lexer;
+mod limits;
+mod math;
 mod options;
 mod parser;
+mod plugin;
+mod span;
+mod validate;
+mod value;
 
+pub use attrs::{is_safe_attr, leading_attrs, parse_attrs, trailing_attrs, AttrBlock};
+pub use diagnostic::{codes, Diagnostic, DiagnosticCode, Related, Severity};
+pub use escape::{entity_at, is_escapable, unescape};
 pub use event::*;
-pub use heading::{ensure_unique, extract_id, slugify, strip_trailing_id};
+pub use heading::{
+    ensure_unique, ensure_unique_with, extract_id, slugify, slugify_with, strip_trailing_id,
+    SlugStrategy,
+};
+pub use incremental::{reparse, Reparse, TextEdit};
 pub use lexer::*;
+pub use limits::{aborted_document, depth_exceeded, is_limit_exceeded, too_many_events, Limits};
+pub use math::{math_region_end, toggle_display_math_on_line};
 pub use options::*;
-pub use parser::parse;
+pub use parser::{parse, Parser};
+pub use plugin::{Plugin, PluginRegistry};
+pub use span::{events_span, join_spans, propagate_node_spans, Position, Span};
+pub use validate::validate;
+pub use value::AttrValue;
@@ -0,0 +1,128 @@
+use crate::{codes, AttrValue, Diagnostic, Event, NodeKind, Span};
+
+/// Hard caps for untrusted input; `None` disables a check.
+///
+/// Unlike the `Options` limits, which only report, exceeding one of these
+/// aborts the pipeline: [`PluginRegistry::run`](crate::PluginRegistry::run)
+/// replaces the stream with an empty document holding a single `Error`
+/// diagnostic and skips the remaining plugins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Deepest node nesting allowed, counting `Document` as 1.
+    pub max_depth: Option<usize>,
+    /// Total events in the stream. [`Parser::limit_events`](crate::Parser::limit_events)
+    /// and the `markdown` plugin stop as soon as they pass it.
+    pub max_events: Option<usize>,
+    /// Bytes of text in a single attribute value, including map keys.
+    pub max_attr_bytes: Option<usize>,
+    /// Characters in a single math formula; enforced by the math plugins.
+    pub max_formula_len: Option<usize>,
+}
+
+impl Limits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Limits::default()
+    }
+
+    /// First limit `events` exceed, as an `Error` diagnostic.
+    pub fn check(&self, events: &[Event]) -> Option<Diagnostic> {
+        if let Some(max) = self.max_events {
+            if events.len() > max {
+                return Some(Diagnostic {
+                    message: format!("event count {} exceeds limit {}", events.len(), max),
+                    ..too_many_events(max, events.first().and_then(Event::span))
+                });
+            }
+        }
+        if self.max_depth.is_none() && self.max_attr_bytes.is_none() {
+            return None;
+        }
+        let mut depth = 0usize;
+        for ev in events {
+            match ev {
+                Event::StartNode(..) => {
+                    depth += 1;
+                    if let Some(max) = self.max_depth.filter(|max| depth > *max) {
+                        return Some(depth_exceeded(max, ev.span()));
+                    }
+                }
+                Event::EndNode(..) => depth = depth.saturating_sub(1),
+                Event::Attribute { name, value, span } => {
+                    let bytes = attr_bytes(value);
+                    if let Some(max) = self.max_attr_bytes.filter(|max| bytes > *max) {
+                        let message = format!(
+                            "attribute '{}' is {} bytes, exceeding limit {}",
+                            name, bytes, max
+                        );
+                        return Some(
+                            Diagnostic::error(codes::ATTRIBUTE_TOO_LARGE, message)
+                                .with_span(*span)
+                                .with_help("raise `max_attr_bytes`"),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// `Error` diagnostic for nesting deeper than `max`, shared with plugins
+/// that stop nesting early.
+pub fn depth_exceeded(max: usize, span: Option<Span>) -> Diagnostic {
+    Diagnostic::error(
+        codes::NESTING_TOO_DEEP,
+        format!("nesting depth exceeds limit {}", max),
+    )
+    .with_span(span)
+    .with_help("flatten nested lists, quotes or blocks, or raise `max_depth`")
+}
+
+/// `Error` diagnostic for a stream of more than `max` events, shared with
+/// stages that stop emitting early.
+pub fn too_many_events(max: usize, span: Option<Span>) -> Diagnostic {
+    Diagnostic::error(
+        codes::TOO_MANY_EVENTS,
+        format!("event count exceeds limit {}", max),
+    )
+    .with_span(span)
+    .with_help("raise `max_events` or shorten the document")
+}
+
+/// Whether `diag` reports an exceeded [`Limits`] entry.
+pub fn is_limit_exceeded(diag: &Diagnostic) -> bool {
+    [
+        codes::NESTING_TOO_DEEP,
+        codes::TOO_MANY_EVENTS,
+        codes::ATTRIBUTE_TOO_LARGE,
+        codes::FORMULA_TOO_LONG,
+    ]
+    .contains(&diag.code)
+}
+
+/// Empty document spanning the one `events` opens, holding only `diag`.
+/// Stages that hit a limit return this instead of a partial stream.
+pub fn aborted_document<'a>(events: &[Event], diag: Diagnostic) -> Vec<Event<'a>> {
+    let span = match events.first() {
+        Some(first @ Event::StartNode(NodeKind::Document, _)) => first.span(),
+        _ => None,
+    };
+    vec![
+        Event::StartNode(NodeKind::Document, span),
+        diag.into(),
+        Event::EndNode(NodeKind::Document, span),
+    ]
+}
+
+// Text bytes held by a value; scalars count as their JSON length would
+fn attr_bytes(value: &AttrValue) -> usize {
+    match value {
+        AttrValue::Str(s) => s.len(),
+        AttrValue::Int(_) | AttrValue::Float(_) | AttrValue::Bool(_) | AttrValue::Null => {
+            value.to_string().len()
+        }
+        AttrValue::List(items) => items.iter().map(attr_bytes).sum(),
+        AttrValue::Map(entries) => entries.iter().map(|(k, v)| k.len() + attr_bytes(v)).sum(),
+    }
+}
@@ -1,4 +1,5 @@
-/// Math delimiter scanning — rules match plugin-latex.
+//! Math delimiter scanning shared by plugin-markdown and the Markdown
+//! renderer — rules match plugin-latex.
 
 /// Toggle `open` for each unescaped `$$` pair found on `line`.
 pub fn toggle_display_math_on_line(line: &str, open: &mut bool) {
@@ -46,10 +47,12 @@ pub fn math_region_end(chars: &[char], cursor: usize) -> Option<usize> {
         let mut end = cursor + 1;
         if end < len && chars[end] != ' ' && chars[end] != '\n' {
             while end < len {
-                if chars[end] == '$' {
-                    if end > start + 1 && chars[end - 1] != ' ' && chars[end - 1] != '\n' {
-                        return Some(end + 1);
-                    }
+                if chars[end] == '$'
+                    && end > start + 1
+                    && chars[end - 1] != ' '
+                    && chars[end - 1] != '\n'
+                {
+                    return Some(end + 1);
                 }
                 end += 1;
             }
@@ -58,45 +61,3 @@ pub fn math_region_end(chars: &[char], cursor: usize) -> Option<usize> {
 
     None
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn chars(s: &str) -> Vec<char> {
-        s.chars().collect()
-    }
-
-    #[test]
-    fn inline_math_region() {
-        let c = chars("$O_{m}$");
-        assert_eq!(math_region_end(&c, 0), Some(7));
-    }
-
-    #[test]
-    fn display_math_region() {
-        let c = chars("$$x = y$$");
-        assert_eq!(math_region_end(&c, 0), Some(9));
-    }
-
-    #[test]
-    fn dollar_amount_is_not_math() {
-        let c = chars("$100");
-        assert_eq!(math_region_end(&c, 0), None);
-    }
-
-    #[test]
-    fn escaped_dollar_is_not_math() {
-        let c = chars("\\$5");
-        assert_eq!(math_region_end(&c, 0), None);
-    }
-
-    #[test]
-    fn toggles_display_math_state() {
-        let mut open = false;
-        super::toggle_display_math_on_line("$$\\begin{aligned}", &mut open);
-        assert!(open);
-        super::toggle_display_math_on_line("\\end{aligned}$$", &mut open);
-        assert!(!open);
-    }
-}
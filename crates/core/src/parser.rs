@@ -1,174 +1,624 @@
-use crate::{tokenize, Event, NodeKind, Options, Severity};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use crate::{
+    codes, propagate_node_spans, tokenize_spanned, tokenize_spanned_at, too_many_events, unescape,
+    AttrValue, Diagnostic, Event, NodeKind, Options, Position, Severity, Span, SpannedToken, Token,
+};
+
+// Bytes pulled from a reader per refill.
+const READ_CHUNK: usize = 64 * 1024;
 
 // MVP parser: emit Document start/end and Text chunks split at newlines.
 // Normalizes CRLF to LF while preserving content semantics.
-pub fn parse(input: &str, options: &Options) -> Vec<Event> {
+// Text and attributes borrow from `input`.
+pub fn parse<'a>(input: &'a str, options: &Options) -> Vec<Event<'a>> {
     let mut events = Vec::with_capacity(3 + input.len() / 8);
-    events.push(Event::StartNode(NodeKind::Document));
-
-    if !input.is_empty() {
-        if let Some(limit) = options.max_doc_bytes {
-            if input.len() > limit {
-                events.push(Event::Diagnostic {
-                    severity: if options.strict {
-                        Severity::Error
-                    } else {
-                        Severity::Warning
-                    },
-                    message: format!("document bytes {} exceed limit {}", input.len(), limit),
-                    span: None,
-                });
-            }
+    let doc_span = Some(Span::covering(Position::default(), input));
+    events.push(Event::StartNode(NodeKind::Document, doc_span));
+
+    if let Some(limit) = options.max_doc_bytes {
+        if input.len() > limit {
+            let message = format!("document bytes {} exceed limit {}", input.len(), limit);
+            events.push(
+                Diagnostic::new(severity(options), codes::DOCUMENT_TOO_LARGE, message)
+                    .with_span(doc_span)
+                    .with_help("raise `max_doc_bytes` or split the document")
+                    .into(),
+            );
         }
+    }
+
+    let mut state = LineState::new();
+    for token in tokenize_spanned(input) {
+        state.token(token, options, &mut events);
+    }
+    state.close_all(&mut events);
+
+    events.push(Event::EndNode(NodeKind::Document, doc_span));
+    propagate_node_spans(&mut events);
+    events
+}
 
-        let tokens = tokenize(input);
-        let mut in_paragraph = false;
-        let mut in_heading = false;
-        let mut in_code_fence = false;
-        let mut blank_run = 0usize;
-        let mut current_line_len = 0usize;
-        let mut just_opened_code_fence = false;
+/// Pull-based parser producing the same events as [`parse`] without holding
+/// the whole document in memory.
+///
+/// Input arrives either from an [`io::Read`] ([`Parser::from_reader`]) or in
+/// chunks ([`Parser::feed`] / [`Parser::finish`]). Events are released one
+/// top-level block at a time, so memory stays bounded by the largest block.
+/// Because the document length is unknown while streaming, the opening
+/// `Document` event carries a zero-width span; the closing one spans the
+/// whole input. Events own their text, since the input buffer is reused.
+pub struct Parser<R = io::Empty> {
+    reader: Option<R>,
+    options: Options,
+    state: LineState,
+    // Undecoded input not yet ending on a line boundary
+    pending: Vec<u8>,
+    // Events of the block currently open; released once it closes
+    block: Vec<Event<'static>>,
+    ready: VecDeque<Event<'static>>,
+    bytes_seen: usize,
+    size_reported: bool,
+    utf8_reported: bool,
+    input_done: bool,
+    finished: bool,
+    max_events: Option<usize>,
+    emitted: usize,
+    // Nodes yielded but not yet closed, tracked only under `max_events`
+    open: Vec<NodeKind>,
+}
+
+impl Parser {
+    /// Parser fed with [`Parser::feed`]. Iterating yields the events that are
+    /// complete so far; call [`Parser::finish`] once input is exhausted.
+    pub fn new(options: &Options) -> Self {
+        Parser::with_reader(None, options)
+    }
 
-        let open_paragraph = |events: &mut Vec<Event>, in_paragraph: &mut bool| {
-            if !*in_paragraph {
-                events.push(Event::StartNode(NodeKind::Paragraph));
-                *in_paragraph = true;
+    pub fn feed(&mut self, chunk: &str) {
+        self.feed_bytes(chunk.as_bytes());
+    }
+
+    pub fn feed_bytes(&mut self, chunk: &[u8]) {
+        if self.finished {
+            return;
+        }
+        self.pending.extend_from_slice(chunk);
+        self.bytes_seen += chunk.len();
+        self.process(false);
+    }
+
+    /// Mark the end of input; remaining events become available.
+    pub fn finish(&mut self) {
+        self.input_done = true;
+    }
+}
+
+impl<R: Read> Parser<R> {
+    pub fn from_reader(reader: R, options: &Options) -> Self {
+        Parser::with_reader(Some(reader), options)
+    }
+
+    fn fill(&mut self) {
+        let Some(reader) = self.reader.as_mut() else {
+            return;
+        };
+        let start = self.pending.len();
+        self.pending.resize(start + READ_CHUNK, 0);
+        let read = loop {
+            match reader.read(&mut self.pending[start..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                other => break other,
+            }
+        };
+        match read {
+            Ok(0) => {
+                self.pending.truncate(start);
+                self.input_done = true;
             }
+            Ok(n) => {
+                self.pending.truncate(start + n);
+                self.bytes_seen += n;
+                self.process(false);
+            }
+            Err(e) => {
+                self.pending.truncate(start);
+                let message = format!("failed to read input: {}", e);
+                self.block.push(
+                    Diagnostic::error(codes::READ_FAILED, message)
+                        .with_span(Some(self.state.point()))
+                        .into(),
+                );
+                self.input_done = true;
+            }
+        }
+    }
+}
+
+impl<R> Parser<R> {
+    fn with_reader(reader: Option<R>, options: &Options) -> Self {
+        let mut ready = VecDeque::new();
+        let origin = Position::default();
+        ready.push_back(Event::StartNode(
+            NodeKind::Document,
+            Some(Span::new(origin, origin)),
+        ));
+        Parser {
+            reader,
+            options: *options,
+            state: LineState::new(),
+            pending: Vec::new(),
+            block: Vec::new(),
+            ready,
+            bytes_seen: 0,
+            size_reported: false,
+            utf8_reported: false,
+            input_done: false,
+            finished: false,
+            max_events: None,
+            emitted: 0,
+            open: Vec::new(),
+        }
+    }
+
+    /// Stop after `max` events (see [`Limits::max_events`](crate::Limits)):
+    /// the rest of the input is dropped, and a `too-many-events` error
+    /// followed by the closing events of every open node ends the stream.
+    pub fn limit_events(mut self, max: usize) -> Self {
+        self.max_events = Some(max);
+        self
+    }
+
+    // Count `ev` against `max_events`, ending the stream once it is passed
+    fn limit(&mut self, ev: Event<'static>) -> Event<'static> {
+        let Some(max) = self.max_events else {
+            return ev;
         };
-        let close_paragraph = |events: &mut Vec<Event>, in_paragraph: &mut bool| {
-            if *in_paragraph {
-                events.push(Event::EndNode(NodeKind::Paragraph));
-                *in_paragraph = false;
+        if self.emitted < max {
+            self.emitted += 1;
+            match &ev {
+                Event::StartNode(kind, _) => self.open.push(kind.clone()),
+                Event::EndNode(..) => {
+                    self.open.pop();
+                }
+                _ => {}
+            }
+            return ev;
+        }
+        self.max_events = None;
+        self.reader = None;
+        self.pending.clear();
+        self.block.clear();
+        self.ready.clear();
+        self.input_done = true;
+        self.finished = true;
+        let span = ev.span();
+        while let Some(kind) = self.open.pop() {
+            self.ready.push_back(Event::EndNode(kind, span));
+        }
+        too_many_events(max, span).into()
+    }
+
+    // Tokenize every complete line in `pending` (everything when `last`)
+    fn process(&mut self, last: bool) {
+        let cut = if last {
+            self.pending.len()
+        } else {
+            match self.pending.iter().rposition(|b| *b == b'\n') {
+                Some(idx) => idx + 1,
+                None => 0,
             }
         };
+        if cut > 0 {
+            let rest = self.pending.split_off(cut);
+            let bytes = std::mem::replace(&mut self.pending, rest);
+            let text = match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(err) => {
+                    let text = String::from_utf8_lossy(err.as_bytes()).into_owned();
+                    if !self.utf8_reported {
+                        self.utf8_reported = true;
+                        let message = "input is not valid UTF-8; invalid bytes were replaced";
+                        self.block.push(
+                            Diagnostic::error(codes::INVALID_UTF8, message)
+                                .with_span(Some(Span::covering(self.state.pos, &text)))
+                                .with_help("save the input as UTF-8")
+                                .into(),
+                        );
+                    }
+                    text
+                }
+            };
+            let mut fresh: Vec<Event> = Vec::new();
+            for token in tokenize_spanned_at(&text, self.state.pos) {
+                self.state.token(token, &self.options, &mut fresh);
+                self.block.extend(fresh.drain(..).map(Event::into_owned));
+                if self.state.at_block_boundary() {
+                    self.release_block();
+                }
+            }
+        }
+        if let Some(limit) = self.options.max_doc_bytes {
+            if !self.size_reported && self.bytes_seen > limit {
+                self.size_reported = true;
+                let message = format!("document bytes exceed limit {}", limit);
+                let span = Span::new(Position::default(), self.state.pos);
+                self.block.push(
+                    Diagnostic::new(severity(&self.options), codes::DOCUMENT_TOO_LARGE, message)
+                        .with_span(Some(span))
+                        .with_help("raise `max_doc_bytes` or split the document")
+                        .into(),
+                );
+            }
+        }
+        if self.state.at_block_boundary() {
+            self.release_block();
+        }
+    }
 
-        for tk in tokens {
-            match tk {
-                // Treat immediate text after an opening code fence as info string (lang)
-                crate::Token::Text(s) if just_opened_code_fence && current_line_len == 0 => {
+    fn release_block(&mut self) {
+        propagate_node_spans(&mut self.block);
+        self.ready.extend(self.block.drain(..));
+    }
+
+    fn finalize(&mut self) {
+        self.process(true);
+        self.state.close_all(&mut self.block);
+        self.release_block();
+        let doc = Span::new(Position::default(), self.state.pos);
+        self.ready
+            .push_back(Event::EndNode(NodeKind::Document, Some(doc)));
+        self.finished = true;
+    }
+}
+
+impl<R: Read> Iterator for Parser<R> {
+    type Item = Event<'static>;
+
+    fn next(&mut self) -> Option<Event<'static>> {
+        loop {
+            if let Some(ev) = self.ready.pop_front() {
+                return Some(self.limit(ev));
+            }
+            if self.finished {
+                return None;
+            }
+            if self.input_done {
+                self.finalize();
+            } else if self.reader.is_some() {
+                self.fill();
+            } else {
+                // Chunk-fed and waiting for more input
+                return None;
+            }
+        }
+    }
+}
+
+fn severity(options: &Options) -> Severity {
+    if options.strict {
+        Severity::Error
+    } else {
+        Severity::Warning
+    }
+}
+
+// Line-oriented parse state carried across tokens and input chunks
+pub(crate) struct LineState {
+    pos: Position,
+    in_paragraph: bool,
+    in_heading: bool,
+    fence: Option<Fence>,
+    blank_run: usize,
+    current_line_len: usize,
+    line_start: Position,
+    // Indentation seen before a fence run on the current line
+    pending_indent: Option<(usize, Span)>,
+    // Fence run whose meaning depends on the rest of its line
+    pending_fence: Option<PendingFence>,
+}
+
+// An open code fence: closed by a run of the same marker at least as long
+#[derive(Clone, Copy)]
+struct Fence {
+    marker: u8,
+    len: usize,
+    indent: usize,
+}
+
+#[derive(Clone, Copy)]
+struct PendingFence {
+    marker: u8,
+    len: usize,
+    indent: Option<(usize, Span)>,
+    span: Span,
+}
+
+// Structural markers get a zero-width span at the point they open or close
+fn at(pos: Position) -> Option<Span> {
+    Some(Span::new(pos, pos))
+}
+
+impl LineState {
+    fn new() -> Self {
+        LineState {
+            pos: Position::default(),
+            in_paragraph: false,
+            in_heading: false,
+            fence: None,
+            blank_run: 0,
+            current_line_len: 0,
+            line_start: Position::default(),
+            pending_indent: None,
+            pending_fence: None,
+        }
+    }
+
+    // State at the start of a line outside any block, after `blank_run`
+    // consecutive newlines
+    pub(crate) fn resume(pos: Position, blank_run: usize) -> Self {
+        LineState {
+            pos,
+            line_start: pos,
+            blank_run,
+            ..LineState::new()
+        }
+    }
+
+    fn point(&self) -> Span {
+        Span::new(self.pos, self.pos)
+    }
+
+    pub(crate) fn at_block_boundary(&self) -> bool {
+        !self.in_paragraph && !self.in_heading && self.fence.is_none()
+    }
+
+    fn open_paragraph(&mut self, events: &mut Vec<Event<'_>>, pos: Position) {
+        if !self.in_paragraph {
+            events.push(Event::StartNode(NodeKind::Paragraph, at(pos)));
+            self.in_paragraph = true;
+        }
+    }
+
+    fn close_paragraph(&mut self, events: &mut Vec<Event<'_>>, pos: Position) {
+        if self.in_paragraph {
+            events.push(Event::EndNode(NodeKind::Paragraph, at(pos)));
+            self.in_paragraph = false;
+        }
+    }
+
+    pub(crate) fn close_all(&mut self, events: &mut Vec<Event<'_>>) {
+        if let Some(pending) = self.pending_fence.take() {
+            self.resolve_fence(pending, None, events);
+        }
+        let end = self.pos;
+        self.close_paragraph(events, end);
+        if self.in_heading {
+            events.push(Event::EndNode(NodeKind::Heading, at(end)));
+            self.in_heading = false;
+        }
+        if self.fence.take().is_some() {
+            events.push(Event::EndNode(NodeKind::CodeFence, at(end)));
+        }
+    }
+
+    // Literal text inside a code fence, or on a line that only looked like one
+    fn push_text<'a>(&mut self, text: Cow<'a, str>, span: Span, events: &mut Vec<Event<'a>>) {
+        if text.is_empty() {
+            return;
+        }
+        self.current_line_len = self.current_line_len.saturating_add(text.len());
+        events.push(Event::Text(text, Some(span)));
+        self.blank_run = 0;
+    }
+
+    // Decide what a fence run means once the rest of its line (`info`) is known
+    fn resolve_fence<'a>(
+        &mut self,
+        pending: PendingFence,
+        info: Option<(&'a str, Span)>,
+        events: &mut Vec<Event<'a>>,
+    ) {
+        let PendingFence {
+            marker,
+            len,
+            indent,
+            span,
+        } = pending;
+        let rest = info.map_or("", |(s, _)| s);
+        let run = || Cow::Owned((marker as char).to_string().repeat(len));
+        match self.fence {
+            Some(open) if open.marker == marker && len >= open.len && rest.trim().is_empty() => {
+                events.push(Event::EndNode(NodeKind::CodeFence, Some(span)));
+                self.fence = None;
+                self.blank_run = 0;
+            }
+            Some(open) => {
+                // Not a closing fence: the whole line is content, minus the
+                // indentation of the opening fence
+                if let Some((n, indent_span)) = indent {
+                    if n > open.indent {
+                        let text = " ".repeat(n - open.indent);
+                        let sub = indent_span.slice(&" ".repeat(n), open.indent..n);
+                        self.push_text(text.into(), sub, events);
+                    }
+                }
+                self.push_text(run(), span, events);
+                if let Some((s, sp)) = info {
+                    self.push_text(s.into(), sp, events);
+                }
+            }
+            // Backtick fences may not carry backticks in their info string
+            None if marker == b'~' || !rest.contains('`') => {
+                let start = indent.map_or(span.start, |(_, sp)| sp.start);
+                self.close_paragraph(events, start);
+                events.push(Event::StartNode(NodeKind::CodeFence, Some(span)));
+                if let Some((s, sp)) = info {
+                    // Treat text after an opening code fence as info string (lang)
                     let info = s.trim();
                     if !info.is_empty() {
+                        let lead = s.len() - s.trim_start().len();
                         events.push(Event::Attribute {
-                            name: "lang".to_string(),
-                            value: info.to_string(),
+                            name: "lang".into(),
+                            value: unescape(info).into(),
+                            span: Some(sp.slice(s, lead..lead + info.len())),
                         });
                     }
-                    // do not emit as content, and do not open paragraph
-                    blank_run = 0;
                 }
-                crate::Token::Text(s) => {
-                    if s.is_empty() {
-                        continue;
-                    }
-                    // Thematic break hint: line composed of 3+ hyphens at start
-                    if current_line_len == 0 {
-                        let trimmed = s.trim();
-                        if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-') {
-                            events.push(Event::StartNode(NodeKind::ThematicBreak));
-                            events.push(Event::Text(s.to_string()));
-                            events.push(Event::EndNode(NodeKind::ThematicBreak));
-                            blank_run = 0;
-                            current_line_len = current_line_len.saturating_add(s.len());
-                            continue;
-                        }
-                    }
-                    open_paragraph(&mut events, &mut in_paragraph);
-                    current_line_len = current_line_len.saturating_add(s.len());
-                    events.push(Event::Text(s.to_string()));
-                    blank_run = 0;
-                }
-                crate::Token::Newline => {
-                    // Always preserve newline text for fidelity
-                    events.push(Event::Text("\n".to_string()));
-                    if in_heading {
-                        events.push(Event::EndNode(NodeKind::Heading));
-                        in_heading = false;
-                    }
-                    // reset any one-line state
-                    just_opened_code_fence = false;
-                    if let Some(max) = options.max_line_len {
-                        if current_line_len > max {
-                            events.push(Event::Diagnostic {
-                                severity: if options.strict {
-                                    Severity::Error
-                                } else {
-                                    Severity::Warning
-                                },
-                                message: format!(
-                                    "line length {} exceeds limit {}",
-                                    current_line_len, max
-                                ),
-                                span: None,
-                            });
-                        }
-                    }
-                    current_line_len = 0;
-                    blank_run += 1;
-                    if let Some(max_blank) = options.max_blank_run {
-                        if blank_run == max_blank + 1 {
-                            events.push(Event::Diagnostic {
-                                severity: if options.strict {
-                                    Severity::Error
-                                } else {
-                                    Severity::Warning
-                                },
-                                message: format!(
-                                    "blank run {} exceeds limit {}",
-                                    blank_run, max_blank
-                                ),
-                                span: None,
-                            });
-                        }
-                    }
-                    if blank_run >= 2 {
-                        close_paragraph(&mut events, &mut in_paragraph);
+                self.fence = Some(Fence {
+                    marker,
+                    len,
+                    indent: indent.map_or(0, |(n, _)| n),
+                });
+                self.blank_run = 0;
+            }
+            None => {
+                let start = indent.map_or(span.start, |(_, sp)| sp.start);
+                self.open_paragraph(events, start);
+                if let Some((n, indent_span)) = indent {
+                    self.push_text(" ".repeat(n).into(), indent_span, events);
+                }
+                self.push_text(run(), span, events);
+                if let Some((s, sp)) = info {
+                    self.push_text(s.into(), sp, events);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn token<'a>(
+        &mut self,
+        token: SpannedToken<'a>,
+        options: &Options,
+        events: &mut Vec<Event<'a>>,
+    ) {
+        let SpannedToken { token: tk, span } = token;
+        let sp = Some(span);
+        self.pos = span.end;
+        if let Some(pending) = self.pending_fence.take() {
+            if let Token::Text(s) = tk {
+                // The rest of the fence line is consumed by the fence itself
+                self.resolve_fence(pending, Some((s, span)), events);
+                return;
+            }
+            self.resolve_fence(pending, None, events);
+        }
+        match tk {
+            Token::Text(s) if self.fence.is_some() => {
+                let (mut s, mut span) = (s, span);
+                if self.current_line_len == 0 {
+                    // Content lines lose up to as much indentation as the opening fence had
+                    let indent = self.fence.map_or(0, |f| f.indent);
+                    let strip = s.bytes().take(indent).take_while(|b| *b == b' ').count();
+                    span = span.slice(s, strip..s.len());
+                    s = &s[strip..];
+                }
+                self.push_text(s.into(), span, events);
+            }
+            Token::Text(s) => {
+                if s.is_empty() {
+                    return;
+                }
+                // Thematic break hint: line composed of 3+ hyphens at start,
+                // indented less than an indented code block
+                if self.current_line_len == 0 {
+                    let trimmed = s.trim();
+                    let indent = s.len() - s.trim_start_matches(' ').len();
+                    if indent < 4 && trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-') {
+                        events.push(Event::StartNode(NodeKind::ThematicBreak, sp));
+                        events.push(Event::Text(s.into(), sp));
+                        events.push(Event::EndNode(NodeKind::ThematicBreak, sp));
+                        self.blank_run = 0;
+                        self.current_line_len = self.current_line_len.saturating_add(s.len());
+                        return;
                     }
                 }
-                crate::Token::FenceBackticks(n) => {
-                    // Code fence markers: toggle node, do not emit backtick text
-                    if n >= 3 && current_line_len == 0 {
-                        if in_code_fence {
-                            events.push(Event::EndNode(NodeKind::CodeFence));
-                            in_code_fence = false;
-                        } else {
-                            events.push(Event::StartNode(NodeKind::CodeFence));
-                            in_code_fence = true;
-                            just_opened_code_fence = true;
-                        }
-                        // fence line should not open/close paragraph nor add text
-                        blank_run = 0;
-                    } else {
-                        // fallback: treat as literal backticks
-                        open_paragraph(&mut events, &mut in_paragraph);
-                        events.push(Event::Text("`".repeat(n)));
-                        blank_run = 0;
+                self.open_paragraph(events, span.start);
+                self.current_line_len = self.current_line_len.saturating_add(s.len());
+                events.push(Event::Text(s.into(), sp));
+                self.blank_run = 0;
+            }
+            Token::Newline => {
+                // Always preserve newline text for fidelity
+                events.push(Event::Text("\n".into(), sp));
+                if self.in_heading {
+                    // The heading's paragraph ends with its line so nodes nest
+                    self.close_paragraph(events, span.start);
+                    events.push(Event::EndNode(NodeKind::Heading, at(span.start)));
+                    self.in_heading = false;
+                }
+                if let Some(max) = options.max_line_len {
+                    if self.current_line_len > max {
+                        let message = format!(
+                            "line length {} exceeds limit {}",
+                            self.current_line_len, max
+                        );
+                        events.push(
+                            Diagnostic::new(severity(options), codes::LINE_TOO_LONG, message)
+                                .with_span(Some(Span::new(self.line_start, span.start)))
+                                .with_help("wrap the line or raise `max_line_len`")
+                                .into(),
+                        );
                     }
                 }
-                crate::Token::Hashes(n) => {
-                    if current_line_len == 0 {
-                        events.push(Event::StartNode(NodeKind::Heading));
-                        // attach heading level attribute
-                        events.push(Event::Attribute {
-                            name: "level".to_string(),
-                            value: n.to_string(),
-                        });
-                        in_heading = true;
+                self.current_line_len = 0;
+                self.line_start = span.end;
+                self.blank_run += 1;
+                if let Some(max_blank) = options.max_blank_run {
+                    if self.blank_run == max_blank + 1 {
+                        let message =
+                            format!("blank run {} exceeds limit {}", self.blank_run, max_blank);
+                        events.push(
+                            Diagnostic::new(severity(options), codes::BLANK_RUN_TOO_LONG, message)
+                                .with_span(sp)
+                                .with_help("remove blank lines or raise `max_blank_run`")
+                                .into(),
+                        );
                     }
-                    open_paragraph(&mut events, &mut in_paragraph);
-                    events.push(Event::Text("#".repeat(n)));
-                    blank_run = 0;
+                }
+                if self.blank_run >= 2 {
+                    self.close_paragraph(events, span.end);
                 }
             }
-        }
-        close_paragraph(&mut events, &mut in_paragraph);
-        if in_heading {
-            events.push(Event::EndNode(NodeKind::Heading));
-        }
-        if in_code_fence {
-            events.push(Event::EndNode(NodeKind::CodeFence));
+            Token::Indent(n) => {
+                self.pending_indent = Some((n, span));
+            }
+            Token::FenceBackticks(len) | Token::FenceTildes(len) => {
+                // Code fence markers are never emitted as text; whether the run
+                // opens, closes or is content depends on the rest of the line
+                let marker = if matches!(tk, Token::FenceTildes(_)) {
+                    b'~'
+                } else {
+                    b'`'
+                };
+                self.pending_fence = Some(PendingFence {
+                    marker,
+                    len,
+                    indent: self.pending_indent.take(),
+                    span,
+                });
+            }
+            Token::Hashes(n) if self.fence.is_some() => {
+                self.push_text("#".repeat(n).into(), span, events);
+            }
+            Token::Hashes(n) => {
+                if self.current_line_len == 0 {
+                    self.close_paragraph(events, span.start);
+                    events.push(Event::StartNode(NodeKind::Heading, at(span.start)));
+                    // attach heading level attribute
+                    events.push(Event::Attribute {
+                        name: "level".into(),
+                        value: AttrValue::Int(n as i64),
+                        span: sp,
+                    });
+                    self.in_heading = true;
+                }
+                self.open_paragraph(events, span.start);
+                events.push(Event::Text("#".repeat(n).into(), sp));
+                self.blank_run = 0;
+            }
         }
     }
-
-    events.push(Event::EndNode(NodeKind::Document));
-    events
 }
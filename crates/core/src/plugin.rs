@@ -0,0 +1,167 @@
+use crate::{aborted_document, is_limit_exceeded, Diagnostic, Event, Limits};
+
+/// A named event transform that can be composed into a pipeline.
+///
+/// `H` is the renderer hint type a plugin may contribute (e.g. Solid
+/// templates and imports); plugins without hints can implement `Plugin<H>`
+/// for every `H`.
+pub trait Plugin<H = ()> {
+    fn name(&self) -> &str;
+
+    /// Plugins that must run before this one when both are in a pipeline.
+    fn after(&self) -> &[&str] {
+        &[]
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>>;
+
+    /// Renderer hints contributed when the plugin is part of a pipeline.
+    fn hints(&self) -> Option<H> {
+        None
+    }
+}
+
+/// Plugins addressable by name; later registrations replace earlier ones.
+pub struct PluginRegistry<H = ()> {
+    plugins: Vec<Box<dyn Plugin<H>>>,
+    limits: Limits,
+}
+
+impl<H> Default for PluginRegistry<H> {
+    fn default() -> Self {
+        PluginRegistry {
+            plugins: Vec::new(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<H> PluginRegistry<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits enforced on the parsed input and after every stage.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn register(&mut self, plugin: impl Plugin<H> + 'static) {
+        self.register_boxed(Box::new(plugin));
+    }
+
+    pub fn register_boxed(&mut self, plugin: Box<dyn Plugin<H>>) {
+        match self.plugins.iter().position(|p| p.name() == plugin.name()) {
+            Some(idx) => self.plugins[idx] = plugin,
+            None => self.plugins.push(plugin),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Plugin<H>> {
+        self.plugins
+            .iter()
+            .find(|p| p.name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.plugins.iter().map(|p| p.name())
+    }
+
+    /// Resolve requested plugin names into run order.
+    ///
+    /// Requested order is kept except where a plugin's [`Plugin::after`]
+    /// constraints name another requested plugin that must run first.
+    /// Duplicates are dropped; unknown names and cycles are errors.
+    pub fn pipeline(&self, names: &[&str]) -> Result<Vec<&dyn Plugin<H>>, String> {
+        let mut requested: Vec<&dyn Plugin<H>> = Vec::with_capacity(names.len());
+        for name in names {
+            let plugin = self
+                .get(name)
+                .ok_or_else(|| format!("unknown plugin '{}'", name))?;
+            if !requested.iter().any(|p| p.name() == plugin.name()) {
+                requested.push(plugin);
+            }
+        }
+
+        let mut ordered: Vec<&dyn Plugin<H>> = Vec::with_capacity(requested.len());
+        while !requested.is_empty() {
+            let ready = requested.iter().position(|p| {
+                p.after()
+                    .iter()
+                    .all(|dep| !requested.iter().any(|q| q.name() == *dep))
+            });
+            match ready {
+                Some(idx) => ordered.push(requested.remove(idx)),
+                None => {
+                    let names: Vec<&str> = requested.iter().map(|p| p.name()).collect();
+                    return Err(format!(
+                        "plugin ordering cycle between: {}",
+                        names.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(ordered)
+    }
+
+    /// Run the resolved pipeline, returning the events and collected hints.
+    ///
+    /// When the input or a stage's output exceeds the registry's [`Limits`],
+    /// or a stage reports an exceeded limit itself, the remaining stages are
+    /// skipped and the events are replaced by an empty document carrying the
+    /// `Error` diagnostic.
+    pub fn run<'a>(
+        &self,
+        names: &[&str],
+        events: Vec<Event<'a>>,
+    ) -> Result<(Vec<Event<'a>>, Vec<H>), String> {
+        self.run_inspect(names, events, |_, _| {})
+    }
+
+    /// Like [`PluginRegistry::run`], calling `inspect` with each plugin's
+    /// name and output as soon as that stage finishes.
+    pub fn run_inspect<'a>(
+        &self,
+        names: &[&str],
+        events: Vec<Event<'a>>,
+        mut inspect: impl FnMut(&str, &[Event<'a>]),
+    ) -> Result<(Vec<Event<'a>>, Vec<H>), String> {
+        let pipeline = self.pipeline(names)?;
+        let mut events = events;
+        let mut hints = Vec::new();
+        if let Some(diag) = self.limits.check(&events) {
+            return Ok((aborted_document(&events, diag), hints));
+        }
+        for plugin in pipeline {
+            events = plugin.process(&events);
+            hints.extend(plugin.hints());
+            let exceeded = self
+                .limits
+                .check(&events)
+                .map(|diag| diag.with_plugin(plugin.name()));
+            if let Some(diag) = exceeded.or_else(|| reported_limit(&events)) {
+                events = aborted_document(&events, diag);
+                inspect(plugin.name(), &events);
+                break;
+            }
+            inspect(plugin.name(), &events);
+        }
+        Ok((events, hints))
+    }
+}
+
+fn reported_limit(events: &[Event]) -> Option<Diagnostic> {
+    events.iter().find_map(|ev| match ev {
+        Event::Diagnostic(diag) if is_limit_exceeded(diag) => Some((**diag).clone()),
+        _ => None,
+    })
+}
@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use crate::{Event, NodeKind};
+
+/// A location in the source text. `line` and `column` are 1-based; `column`
+/// counts characters, `offset` counts bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(offset: usize, line: usize, column: usize) -> Self {
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// Position reached after reading `text` starting at `self`.
+    pub fn advance(self, text: &str) -> Position {
+        let mut pos = self;
+        for ch in text.chars() {
+            pos.offset += ch.len_utf8();
+            if ch == '\n' {
+                pos.line += 1;
+                pos.column = 1;
+            } else {
+                pos.column += 1;
+            }
+        }
+        pos
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new(0, 1, 1)
+    }
+}
+
+/// Half-open source range `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+
+    /// Span covering `text` when it begins at `start`.
+    pub fn covering(start: Position, text: &str) -> Self {
+        Span::new(start, start.advance(text))
+    }
+
+    /// Smallest span containing both `self` and `other`.
+    pub fn join(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Sub-span for the byte `range` of `text`, where `text` is the content
+    /// this span covers. Out-of-range bounds are clamped to `text`.
+    pub fn slice(&self, text: &str, range: Range<usize>) -> Span {
+        let from = floor_char_boundary(text, range.start);
+        let to = floor_char_boundary(text, range.end.max(range.start));
+        let start = self.start.advance(&text[..from]);
+        Span::new(start, start.advance(&text[from..to]))
+    }
+}
+
+fn floor_char_boundary(text: &str, idx: usize) -> usize {
+    let mut idx = idx.min(text.len());
+    while !text.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Join two optional spans, keeping whichever side is known.
+pub fn join_spans(a: Option<Span>, b: Option<Span>) -> Option<Span> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.join(b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Union of the spans carried by `events`.
+pub fn events_span(events: &[Event]) -> Option<Span> {
+    events
+        .iter()
+        .fold(None, |acc, ev| join_spans(acc, ev.span()))
+}
+
+/// Widen every node's `StartNode`/`EndNode` span so it covers its own marker
+/// spans plus everything emitted between them. Both events of a pair end up
+/// carrying the same span. Unmatched events are left untouched.
+pub fn propagate_node_spans(events: &mut [Event]) {
+    let mut open: Vec<(usize, &NodeKind, Option<Span>)> = Vec::new();
+    let mut resolved: Vec<(usize, usize, Option<Span>)> = Vec::new();
+    for (idx, ev) in events.iter().enumerate() {
+        match ev {
+            Event::StartNode(kind, span) => open.push((idx, kind, *span)),
+            Event::EndNode(kind, span) => {
+                // Match the nearest open node of the same kind; the core parser
+                // may interleave heading and paragraph markers.
+                let Some(at) = open.iter().rposition(|(_, k, _)| *k == kind) else {
+                    continue;
+                };
+                let (start_idx, _, acc) = open.remove(at);
+                let acc = join_spans(acc, *span);
+                resolved.push((start_idx, idx, acc));
+                if let Some(parent) = at.checked_sub(1).map(|p| &mut open[p]) {
+                    parent.2 = join_spans(parent.2, acc);
+                }
+            }
+            other => {
+                if let Some(parent) = open.last_mut() {
+                    parent.2 = join_spans(parent.2, other.span());
+                }
+            }
+        }
+    }
+    for (start_idx, end_idx, span) in resolved {
+        if span.is_none() {
+            continue;
+        }
+        for idx in [start_idx, end_idx] {
+            if let Event::StartNode(_, slot) | Event::EndNode(_, slot) = &mut events[idx] {
+                *slot = span;
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{codes, Diagnostic, Event, NodeKind, Span};
+
+/// Check that `events` form a single well-nested tree.
+///
+/// Every `StartNode` must be closed by an `EndNode` of the same kind, nodes
+/// must close in reverse order of opening, and text and attributes may only
+/// appear inside an open node. Diagnostics are accepted anywhere. Problems
+/// are returned as `Error` diagnostics; an empty result means the stream is
+/// well formed.
+pub fn validate(events: &[Event]) -> Vec<Event<'static>> {
+    let mut out: Vec<Event<'static>> = Vec::new();
+    let mut open: Vec<(&NodeKind, Option<Span>)> = Vec::new();
+    let mut roots = 0usize;
+    for ev in events {
+        match ev {
+            Event::StartNode(kind, span) => {
+                if open.is_empty() {
+                    roots += 1;
+                    if roots > 1 {
+                        let message = format!("{} starts a second root node", kind.name());
+                        out.push(
+                            Diagnostic::error(codes::MULTIPLE_ROOTS, message)
+                                .with_span(*span)
+                                .into(),
+                        );
+                    }
+                }
+                open.push((kind, *span));
+            }
+            Event::EndNode(kind, span) => match open.iter().rposition(|(k, _)| *k == kind) {
+                Some(at) => {
+                    // Nodes opened after the one being closed were left dangling
+                    for (inner, opened) in open.drain(at + 1..).rev() {
+                        let message = format!(
+                            "end of {} while {} is still open",
+                            kind.name(),
+                            inner.name()
+                        );
+                        let mut diag =
+                            Diagnostic::error(codes::MISMATCHED_END, message).with_span(*span);
+                        if let Some(opened) = opened {
+                            diag =
+                                diag.with_related(opened, format!("{} opened here", inner.name()));
+                        }
+                        out.push(diag.into());
+                    }
+                    open.pop();
+                }
+                None => {
+                    let message = format!("end of {} without a matching start", kind.name());
+                    out.push(
+                        Diagnostic::error(codes::UNMATCHED_END, message)
+                            .with_span(*span)
+                            .into(),
+                    );
+                }
+            },
+            Event::Text(_, span) if open.is_empty() => out.push(
+                Diagnostic::error(codes::OUTSIDE_NODE, "text outside any node")
+                    .with_span(*span)
+                    .into(),
+            ),
+            Event::Attribute { name, span, .. } if open.is_empty() => {
+                let message = format!("attribute '{}' outside any node", name);
+                out.push(
+                    Diagnostic::error(codes::OUTSIDE_NODE, message)
+                        .with_span(*span)
+                        .into(),
+                );
+            }
+            _ => {}
+        }
+    }
+    for (kind, span) in open.into_iter().rev() {
+        let message = format!("{} is never closed", kind.name());
+        out.push(
+            Diagnostic::error(codes::UNCLOSED_NODE, message)
+                .with_span(span)
+                .into(),
+        );
+    }
+    out
+}
@@ -0,0 +1,309 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// Value of an [`Event::Attribute`](crate::Event::Attribute).
+///
+/// Most attributes are plain strings; plugins that attach structured data
+/// (frontmatter, heading trees, quiz choices) use lists and maps so renderers
+/// can emit real JSON instead of re-parsing an encoded string. Map entries
+/// keep their insertion order. The `serde` feature maps values to and from
+/// plain JSON values.
+#[derive(Debug, Clone)]
+pub enum AttrValue<'a> {
+    Str(Cow<'a, str>),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+    List(Vec<AttrValue<'a>>),
+    Map(Vec<(Cow<'a, str>, AttrValue<'a>)>),
+}
+
+impl<'a> AttrValue<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttrValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AttrValue::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttrValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Whether a flag attribute is set, stored as `true` or as the string
+    /// `"1"` older plugins emit.
+    pub fn as_flag(&self) -> bool {
+        match self {
+            AttrValue::Bool(b) => *b,
+            AttrValue::Str(s) => s == "1",
+            _ => false,
+        }
+    }
+
+    /// Entry `key` of a map value.
+    pub fn get(&self, key: &str) -> Option<&AttrValue<'a>> {
+        match self {
+            AttrValue::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Detach the value from the buffer it borrows from.
+    pub fn into_owned(self) -> AttrValue<'static> {
+        match self {
+            AttrValue::Str(s) => AttrValue::Str(Cow::Owned(s.into_owned())),
+            AttrValue::Int(n) => AttrValue::Int(n),
+            AttrValue::Float(f) => AttrValue::Float(f),
+            AttrValue::Bool(b) => AttrValue::Bool(b),
+            AttrValue::Null => AttrValue::Null,
+            AttrValue::List(items) => {
+                AttrValue::List(items.into_iter().map(AttrValue::into_owned).collect())
+            }
+            AttrValue::Map(entries) => AttrValue::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Cow::Owned(k.into_owned()), v.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// Floats compare by bit pattern so that `Event` can stay `Eq`
+impl PartialEq for AttrValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttrValue::Str(a), AttrValue::Str(b)) => a == b,
+            (AttrValue::Int(a), AttrValue::Int(b)) => a == b,
+            (AttrValue::Float(a), AttrValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (AttrValue::Bool(a), AttrValue::Bool(b)) => a == b,
+            (AttrValue::Null, AttrValue::Null) => true,
+            (AttrValue::List(a), AttrValue::List(b)) => a == b,
+            (AttrValue::Map(a), AttrValue::Map(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AttrValue<'_> {}
+
+impl PartialEq<str> for AttrValue<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for AttrValue<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl<'a> From<&'a str> for AttrValue<'a> {
+    fn from(s: &'a str) -> Self {
+        AttrValue::Str(Cow::Borrowed(s))
+    }
+}
+
+impl From<String> for AttrValue<'_> {
+    fn from(s: String) -> Self {
+        AttrValue::Str(Cow::Owned(s))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for AttrValue<'a> {
+    fn from(s: Cow<'a, str>) -> Self {
+        AttrValue::Str(s)
+    }
+}
+
+impl From<i64> for AttrValue<'_> {
+    fn from(n: i64) -> Self {
+        AttrValue::Int(n)
+    }
+}
+
+impl From<bool> for AttrValue<'_> {
+    fn from(b: bool) -> Self {
+        AttrValue::Bool(b)
+    }
+}
+
+impl<'a> From<Vec<AttrValue<'a>>> for AttrValue<'a> {
+    fn from(items: Vec<AttrValue<'a>>) -> Self {
+        AttrValue::List(items)
+    }
+}
+
+/// Strings print as-is; every other value prints as compact JSON.
+impl fmt::Display for AttrValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::Str(s) => f.write_str(s),
+            other => write_json(other, f),
+        }
+    }
+}
+
+fn write_json(value: &AttrValue, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match value {
+        AttrValue::Str(s) => write_json_str(s, f),
+        AttrValue::Int(n) => write!(f, "{}", n),
+        AttrValue::Float(x) if x.is_finite() => write!(f, "{:?}", x),
+        AttrValue::Float(_) | AttrValue::Null => f.write_str("null"),
+        AttrValue::Bool(b) => write!(f, "{}", b),
+        AttrValue::List(items) => {
+            f.write_str("[")?;
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(",")?;
+                }
+                write_json(item, f)?;
+            }
+            f.write_str("]")
+        }
+        AttrValue::Map(entries) => {
+            f.write_str("{")?;
+            for (idx, (key, item)) in entries.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(",")?;
+                }
+                write_json_str(key, f)?;
+                f.write_str(":")?;
+                write_json(item, f)?;
+            }
+            f.write_str("}")
+        }
+    }
+}
+
+fn write_json_str(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::AttrValue;
+    use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+    use std::borrow::Cow;
+    use std::fmt;
+
+    impl Serialize for AttrValue<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                AttrValue::Str(s) => serializer.serialize_str(s),
+                AttrValue::Int(n) => serializer.serialize_i64(*n),
+                AttrValue::Float(x) => serializer.serialize_f64(*x),
+                AttrValue::Bool(b) => serializer.serialize_bool(*b),
+                AttrValue::Null => serializer.serialize_unit(),
+                AttrValue::List(items) => {
+                    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                    for item in items {
+                        seq.serialize_element(item)?;
+                    }
+                    seq.end()
+                }
+                AttrValue::Map(entries) => {
+                    let mut map = serializer.serialize_map(Some(entries.len()))?;
+                    for (key, item) in entries {
+                        map.serialize_entry(key.as_ref(), item)?;
+                    }
+                    map.end()
+                }
+            }
+        }
+    }
+
+    /// Deserializes from any self-describing format (e.g. a JSON value).
+    impl<'de> Deserialize<'de> for AttrValue<'static> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(ValueVisitor)
+        }
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = AttrValue<'static>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a JSON-compatible attribute value")
+        }
+
+        fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E> {
+            Ok(AttrValue::Bool(b))
+        }
+
+        fn visit_i64<E>(self, n: i64) -> Result<Self::Value, E> {
+            Ok(AttrValue::Int(n))
+        }
+
+        fn visit_u64<E>(self, n: u64) -> Result<Self::Value, E> {
+            Ok(i64::try_from(n).map_or(AttrValue::Float(n as f64), AttrValue::Int))
+        }
+
+        fn visit_f64<E>(self, x: f64) -> Result<Self::Value, E> {
+            Ok(AttrValue::Float(x))
+        }
+
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E> {
+            Ok(AttrValue::Str(Cow::Owned(s.to_string())))
+        }
+
+        fn visit_string<E>(self, s: String) -> Result<Self::Value, E> {
+            Ok(AttrValue::Str(Cow::Owned(s)))
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(AttrValue::Null)
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(AttrValue::Null)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            AttrValue::deserialize(d)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(AttrValue::List(items))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some((key, item)) = map.next_entry::<String, AttrValue<'static>>()? {
+                entries.push((Cow::Owned(key), item));
+            }
+            Ok(AttrValue::Map(entries))
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use pendon_core::{
+    extract_id, is_safe_attr, leading_attrs, parse_attrs, trailing_attrs, AttrBlock,
+};
+
+#[test]
+fn parses_ids_classes_and_pairs() {
+    let block = parse_attrs(r#"#intro .lead .wide data-x="a b" lang=en title='it\'s'"#).unwrap();
+    assert_eq!(block.id.as_deref(), Some("intro"));
+    assert_eq!(block.classes, ["lead", "wide"]);
+    assert_eq!(
+        block.pairs,
+        [
+            ("data-x".to_string(), "a b".to_string()),
+            ("lang".to_string(), "en".to_string()),
+            ("title".to_string(), "it's".to_string()),
+        ]
+    );
+    assert_eq!(
+        block.attributes()[..2],
+        [
+            ("id".to_string(), "intro".to_string()),
+            ("class".to_string(), "lead wide".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn rejects_anything_but_attribute_tokens() {
+    assert_eq!(parse_attrs(""), None);
+    assert_eq!(parse_attrs("1-3"), None);
+    assert_eq!(parse_attrs("word"), None);
+    assert_eq!(parse_attrs("#a#b"), None);
+    assert_eq!(parse_attrs("k=\"open"), None);
+    assert_eq!(
+        parse_attrs(": .note"),
+        Some(AttrBlock {
+            classes: vec!["note".to_string()],
+            ..AttrBlock::default()
+        })
+    );
+}
+
+#[test]
+fn finds_blocks_at_either_end_of_text() {
+    let (before, block) = trailing_attrs("Title {#top .x}  ").unwrap();
+    assert_eq!(before, "Title");
+    assert_eq!(block.id.as_deref(), Some("top"));
+    assert_eq!(trailing_attrs("js {1-3}"), None);
+    assert_eq!(trailing_attrs("f() { return }"), None);
+
+    let (block, len) = leading_attrs("{k=\"}\"} rest").unwrap();
+    assert_eq!(block.pairs, [("k".to_string(), "}".to_string())]);
+    assert_eq!(len, 7);
+}
+
+#[test]
+fn extract_id_reads_the_trailing_block() {
+    assert_eq!(
+        extract_id("Baz {#custom-baz}"),
+        ("Baz".to_string(), Some("custom-baz".to_string()))
+    );
+    assert_eq!(
+        extract_id("Baz {.wide #v1.2}"),
+        ("Baz".to_string(), Some("v1.2".to_string()))
+    );
+    assert_eq!(extract_id("Baz {.wide}"), ("Baz {.wide}".to_string(), None));
+}
+
+#[test]
+fn only_inert_attribute_names_are_safe() {
+    for name in ["id", "class", "title", "target", "data-x", "data-on-click"] {
+        assert!(is_safe_attr(name), "{name}");
+    }
+    for name in [
+        "onclick",
+        "onError",
+        "style",
+        "innerHTML",
+        "ref",
+        "textContent",
+        "data-",
+    ] {
+        assert!(!is_safe_attr(name), "{name}");
+    }
+}
@@ -0,0 +1,34 @@
+use pendon_core::{entity_at, is_escapable, parse, unescape, AttrValue, Event, Options};
+
+#[test]
+fn backslash_escapes_only_ascii_punctuation() {
+    assert!(is_escapable('*') && is_escapable('\\') && is_escapable('~'));
+    assert!(!is_escapable('a') && !is_escapable(' ') && !is_escapable('é'));
+    assert_eq!(unescape("\\*a\\* \\b c\\"), "*a* \\b c\\");
+}
+
+#[test]
+fn entities_decode_named_and_numeric_references() {
+    assert_eq!(unescape("&amp; &copy; &#65;&#x42;"), "& © AB");
+    assert_eq!(entity_at("&#0;x"), Some(("\u{FFFD}".into(), 4)));
+    assert_eq!(entity_at("&#x110000;"), Some(("\u{FFFD}".into(), 10)));
+}
+
+#[test]
+fn unknown_or_unterminated_entities_stay_literal() {
+    assert_eq!(
+        unescape("&nope; &copy &#; &#12345678;"),
+        "&nope; &copy &#; &#12345678;"
+    );
+    assert_eq!(unescape("\\&amp;"), "&amp;");
+}
+
+#[test]
+fn fence_info_string_is_unescaped() {
+    let events = parse("```c\\+\\+\nx\n```\n", &Options::default());
+    let lang = events.iter().find_map(|ev| match ev {
+        Event::Attribute { name, value, .. } if name == "lang" => Some(value.clone()),
+        _ => None,
+    });
+    assert_eq!(lang, Some(AttrValue::from("c++")));
+}
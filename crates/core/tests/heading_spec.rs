@@ -0,0 +1,50 @@
+use pendon_core::{ensure_unique, ensure_unique_with, slugify, slugify_with, SlugStrategy};
+use std::collections::HashMap;
+
+#[test]
+fn github_slugs_keep_unicode_letters() {
+    let github = SlugStrategy::GitHub;
+    assert_eq!(slugify_with("Über uns", github), "über-uns");
+    assert_eq!(slugify_with("はじめに", github), "はじめに");
+    assert_eq!(slugify_with("Cara Pakai (v2.0)", github), "cara-pakai-v20");
+    assert_eq!(
+        slugify_with("snake_case -- API", github),
+        "snake_case----api"
+    );
+    assert_eq!(slugify_with("!!!", github), "section");
+}
+
+#[test]
+fn default_strategy_keeps_legacy_ascii_slugs() {
+    assert_eq!(SlugStrategy::default(), SlugStrategy::Ascii);
+    assert_eq!(slugify("Über uns"), "ber-uns");
+    assert_eq!(slugify("はじめに"), "section");
+    assert_eq!(slugify("snake_case -- API"), "snake-case-api");
+}
+
+#[test]
+fn duplicate_ids_follow_the_strategy() {
+    let mut used = HashMap::new();
+    let github: Vec<String> = ["foo-1", "foo", "foo", "foo"]
+        .into_iter()
+        .map(|id| ensure_unique_with(id.to_string(), &mut used, SlugStrategy::GitHub))
+        .collect();
+    assert_eq!(github, ["foo-1", "foo", "foo-2", "foo-3"]);
+
+    let mut used = HashMap::new();
+    let ascii: Vec<String> = ["foo", "foo", "foo"]
+        .into_iter()
+        .map(|id| ensure_unique_with(id.to_string(), &mut used, SlugStrategy::Ascii))
+        .collect();
+    assert_eq!(ascii, ["foo", "foo-2", "foo-3"]);
+}
+
+#[test]
+fn ensure_unique_suffixes_like_slugify() {
+    let mut used = HashMap::new();
+    let ids: Vec<String> = ["A b", "A b"]
+        .into_iter()
+        .map(|text| ensure_unique(slugify(text), &mut used))
+        .collect();
+    assert_eq!(ids, ["a-b", "a-b-2"]);
+}
@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+use pendon_core::{parse, reparse, Event, NodeKind, Options, TextEdit};
+
+const DOC: &str = "# Title\n\nFirst paragraph\nstill first\n\n\n---\n\n```rust\nfn main() {}\n```\n\nLast one\r\n\r\n\r\n~~~\nx\n";
+
+fn check(before: &str, edit: &TextEdit, options: &Options) -> usize {
+    let previous = parse(before, options);
+    let after = edit.apply(before).unwrap();
+    let updated = reparse(&previous, edit, &after, options).unwrap();
+    assert_eq!(
+        updated.events,
+        parse(&after, options),
+        "edit {:?} of {:?}",
+        edit,
+        before
+    );
+    updated.reparsed.len()
+}
+
+#[test]
+fn edit_inside_paragraph_only_reparses_that_block() {
+    let options = Options::default();
+    let at = DOC.find("still").unwrap();
+    let edit = TextEdit::new(at..at + 5, "yet");
+    let total = parse(&edit.apply(DOC).unwrap(), &options).len();
+    let reparsed = check(DOC, &edit, &options);
+    assert!(reparsed > 0 && reparsed < total / 2);
+}
+
+#[test]
+fn opening_a_fence_reparses_following_blocks() {
+    let options = Options::default();
+    // Only the shorter `~~~` follows, so the fence runs to the end
+    let edit = TextEdit::new(0..0, "~~~~\n");
+    let after = edit.apply(DOC).unwrap();
+    let updated = reparse(&parse(DOC, &options), &edit, &after, &options).unwrap();
+    assert_eq!(updated.events, parse(&after, &options));
+    assert_eq!(updated.reparsed, 1..updated.events.len() - 1);
+    assert!(matches!(
+        updated.events[1],
+        Event::StartNode(NodeKind::CodeFence, _)
+    ));
+}
+
+#[test]
+fn every_small_edit_matches_full_parse() {
+    let variants = [
+        Options::default(),
+        Options {
+            max_line_len: Some(8),
+            max_blank_run: Some(1),
+            ..Options::default()
+        },
+    ];
+    let replacements = ["", "x", "\n", "\n\n", "```", "~~~~\n", "# ", "---\n", "\r"];
+    for options in &variants {
+        for start in (0..=DOC.len()).filter(|i| DOC.is_char_boundary(*i)) {
+            for len in [0, 1, 3] {
+                let end = (start + len).min(DOC.len());
+                for replacement in replacements {
+                    check(DOC, &TextEdit::new(start..end, replacement), options);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mismatched_source_falls_back_to_full_parse() {
+    let options = Options::default();
+    let previous = parse(DOC, &options);
+    let updated = reparse(
+        &previous,
+        &TextEdit::new(0..1, "#"),
+        "unrelated\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(updated.events, parse("unrelated\n", &options));
+    assert_eq!(updated.reparsed, 0..updated.events.len());
+}
+
+#[test]
+fn malformed_edits_are_rejected() {
+    assert_eq!(
+        TextEdit::new(Range { start: 3, end: 1 }, "x").apply("abcdef"),
+        None
+    );
+    assert_eq!(TextEdit::new(2..9, "").apply("abc"), None);
+    assert_eq!(TextEdit::new(1..2, "").apply("éa"), None);
+    assert_eq!(
+        TextEdit::new(1..2, "-").apply("abc").as_deref(),
+        Some("a-c")
+    );
+
+    let options = Options::default();
+    let previous = parse(DOC, &options);
+    for edit in [
+        TextEdit::new(Range { start: 5, end: 2 }, ""),
+        TextEdit::new(DOC.len() + 1..DOC.len() + 1, ""),
+        TextEdit::new(0..0, "x".repeat(DOC.len() + 1)),
+    ] {
+        assert_eq!(reparse(&previous, &edit, DOC, &options), None, "{edit:?}");
+    }
+    // The edit would split the first character of the new source
+    assert_eq!(
+        reparse(&previous, &TextEdit::new(1..1, ""), "éx", &options),
+        None
+    );
+}
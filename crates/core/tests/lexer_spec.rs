@@ -1,4 +1,4 @@
-use pendon_core::{tokenize, Token};
+use pendon_core::{tokenize, tokenize_spanned, Position, Token};
 
 #[test]
 fn crlf_normalizes_to_single_newline_token() {
@@ -6,9 +6,10 @@ fn crlf_normalizes_to_single_newline_token() {
     let kinds: Vec<_> = toks
         .iter()
         .map(|t| match t {
-            Token::Text(s) => (&"T"[..], s.len()),
+            Token::Text(s) => ("T", s.len()),
             Token::Newline => ("N", 0),
-            Token::FenceBackticks(n) => ("F", *n),
+            Token::FenceBackticks(n) | Token::FenceTildes(n) => ("F", *n),
+            Token::Indent(n) => ("I", *n),
             Token::Hashes(n) => ("H", *n),
         })
         .collect();
@@ -24,11 +25,49 @@ fn detects_hashes_and_backticks_at_line_start() {
     let mut has_fence = 0;
     for t in toks {
         match t {
-            Token::Hashes(n) if n == 3 => has_hashes = true,
-            Token::FenceBackticks(n) if n == 3 => has_fence += 1,
+            Token::Hashes(3) => has_hashes = true,
+            Token::FenceBackticks(3) => has_fence += 1,
             _ => {}
         }
     }
     assert!(has_hashes);
     assert_eq!(has_fence, 2);
 }
+
+#[test]
+fn detects_tilde_fences_and_fence_indentation() {
+    let toks = tokenize("  ~~~~ sh\n~~\n    ```");
+    assert_eq!(toks[0], Token::Indent(2));
+    assert_eq!(toks[1], Token::FenceTildes(4));
+    // Two tildes are text; four spaces of indentation are not a fence
+    assert!(matches!(toks[4], Token::Text("~~")));
+    assert!(!toks
+        .iter()
+        .any(|t| matches!(t, Token::Indent(4) | Token::FenceBackticks(_))));
+}
+
+#[test]
+fn spanned_tokens_record_byte_and_line_positions() {
+    let toks = tokenize_spanned("## A\nB");
+    let spans: Vec<_> = toks.iter().map(|t| (t.span.start, t.span.end)).collect();
+    assert_eq!(
+        spans,
+        vec![
+            (Position::new(0, 1, 1), Position::new(2, 1, 3)),
+            (Position::new(2, 1, 3), Position::new(3, 1, 4)),
+            (Position::new(3, 1, 4), Position::new(4, 1, 5)),
+            (Position::new(4, 1, 5), Position::new(5, 2, 1)),
+            (Position::new(5, 2, 1), Position::new(6, 2, 2)),
+        ]
+    );
+}
+
+#[test]
+fn escaped_markers_at_line_start_stay_text() {
+    let toks = tokenize("\\# a\n\\```\n\\~~~");
+    assert!(!toks.iter().any(|t| matches!(
+        t,
+        Token::Hashes(_) | Token::FenceBackticks(_) | Token::FenceTildes(_)
+    )));
+    assert!(matches!(toks[0], Token::Text(s) if s.starts_with('\\')));
+}
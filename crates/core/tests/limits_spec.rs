@@ -0,0 +1,81 @@
+use pendon_core::{codes, parse, validate, AttrValue, Event, Limits, NodeKind, Options, Parser};
+
+fn nested(depth: usize) -> Vec<Event<'static>> {
+    let mut events = Vec::new();
+    for _ in 0..depth {
+        events.push(Event::StartNode(NodeKind::Blockquote, None));
+    }
+    for _ in 0..depth {
+        events.push(Event::EndNode(NodeKind::Blockquote, None));
+    }
+    events
+}
+
+#[test]
+fn unlimited_accepts_everything() {
+    let limits = Limits::default();
+    assert!(limits.is_unlimited());
+    assert!(limits.check(&nested(500)).is_none());
+}
+
+#[test]
+fn depth_counts_open_nodes() {
+    let limits = Limits {
+        max_depth: Some(4),
+        ..Limits::default()
+    };
+    assert!(limits.check(&nested(4)).is_none());
+    let diag = limits.check(&nested(5)).expect("too deep");
+    assert_eq!(diag.code, codes::NESTING_TOO_DEEP);
+}
+
+#[test]
+fn event_count_and_attribute_size() {
+    let events = parse("one\n\ntwo\n", &Options::default());
+    let limits = Limits {
+        max_events: Some(events.len() - 1),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limits.check(&events).map(|d| d.code),
+        Some(codes::TOO_MANY_EVENTS)
+    );
+
+    let attr = |value: AttrValue<'static>| {
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::Attribute {
+                name: "data".into(),
+                value,
+                span: None,
+            },
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    };
+    let limits = Limits {
+        max_attr_bytes: Some(8),
+        ..Limits::default()
+    };
+    assert!(limits.check(&attr("12345678".into())).is_none());
+    let nested = AttrValue::Map(vec![("key".into(), AttrValue::List(vec!["abcdef".into()]))]);
+    let diag = limits.check(&attr(nested)).expect("too large");
+    assert_eq!(diag.code, codes::ATTRIBUTE_TOO_LARGE);
+}
+
+#[test]
+fn streaming_parser_stops_at_event_limit() {
+    let src = "one\n\ntwo\n\nthree\n";
+    let all: Vec<Event> = Parser::from_reader(src.as_bytes(), &Options::default()).collect();
+    let streamed: Vec<Event> = Parser::from_reader(src.as_bytes(), &Options::default())
+        .limit_events(all.len())
+        .collect();
+    assert_eq!(streamed, all);
+
+    let cut: Vec<Event> = Parser::from_reader(src.as_bytes(), &Options::default())
+        .limit_events(3)
+        .collect();
+    assert_eq!(&cut[..3], &all[..3]);
+    assert!(matches!(&cut[3], Event::Diagnostic(d) if d.code == codes::TOO_MANY_EVENTS));
+    assert!(validate(&cut).is_empty(), "{cut:?}");
+    assert!(cut.len() < all.len());
+}
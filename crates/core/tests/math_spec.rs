@@ -0,0 +1,38 @@
+use pendon_core::{math_region_end, toggle_display_math_on_line};
+
+fn chars(s: &str) -> Vec<char> {
+    s.chars().collect()
+}
+
+#[test]
+fn inline_math_region() {
+    let c = chars("$O_{m}$");
+    assert_eq!(math_region_end(&c, 0), Some(7));
+}
+
+#[test]
+fn display_math_region() {
+    let c = chars("$$x = y$$");
+    assert_eq!(math_region_end(&c, 0), Some(9));
+}
+
+#[test]
+fn dollar_amount_is_not_math() {
+    let c = chars("$100");
+    assert_eq!(math_region_end(&c, 0), None);
+}
+
+#[test]
+fn escaped_dollar_is_not_math() {
+    let c = chars("\\$5");
+    assert_eq!(math_region_end(&c, 0), None);
+}
+
+#[test]
+fn toggles_display_math_state() {
+    let mut open = false;
+    toggle_display_math_on_line("$$\\begin{aligned}", &mut open);
+    assert!(open);
+    toggle_display_math_on_line("\\end{aligned}$$", &mut open);
+    assert!(!open);
+}
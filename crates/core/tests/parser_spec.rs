@@ -1,9 +1,13 @@
-use pendon_core::{parse, Event, NodeKind, Options, Severity};
+use std::borrow::Cow;
+
+use pendon_core::{
+    codes, parse, AttrValue, Event, NodeKind, Options, Parser, Position, Severity, Span,
+};
 
 fn render_concat(events: &[Event]) -> String {
     let mut s = String::new();
     for ev in events {
-        if let Event::Text(t) = ev {
+        if let Event::Text(t, _) = ev {
             s.push_str(t);
         }
     }
@@ -13,11 +17,12 @@ fn render_concat(events: &[Event]) -> String {
 #[test]
 fn empty_input_emits_only_document() {
     let ev = parse("", &Options::default());
+    let empty = Some(Span::new(Position::default(), Position::default()));
     assert_eq!(
         ev,
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::EndNode(NodeKind::Document)
+            Event::StartNode(NodeKind::Document, empty),
+            Event::EndNode(NodeKind::Document, empty)
         ]
     );
 }
@@ -28,11 +33,11 @@ fn single_line_text() {
     assert_eq!(render_concat(&ev), "Hello");
     assert!(matches!(
         ev.first(),
-        Some(Event::StartNode(NodeKind::Document))
+        Some(Event::StartNode(NodeKind::Document, _))
     ));
     assert!(matches!(
         ev.last(),
-        Some(Event::EndNode(NodeKind::Document))
+        Some(Event::EndNode(NodeKind::Document, _))
     ));
 }
 
@@ -43,16 +48,22 @@ fn single_newline_split() {
     // One paragraph
     let p_starts = ev
         .iter()
-        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph, _)))
         .count();
     let p_ends = ev
         .iter()
-        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph, _)))
         .count();
     assert_eq!((p_starts, p_ends), (1, 1));
-    assert!(ev.iter().any(|e| matches!(e, Event::Text(t) if t == "A")));
-    assert!(ev.iter().any(|e| matches!(e, Event::Text(t) if t == "\n")));
-    assert!(ev.iter().any(|e| matches!(e, Event::Text(t) if t == "B")));
+    assert!(ev
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "A")));
+    assert!(ev
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "\n")));
+    assert!(ev
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "B")));
 }
 
 #[test]
@@ -61,17 +72,17 @@ fn double_newline_split() {
     assert_eq!(render_concat(&ev), "A\n\nB");
     let count_newlines = ev
         .iter()
-        .filter(|e| matches!(e, Event::Text(t) if t == "\n"))
+        .filter(|e| matches!(e, Event::Text(t, _) if t == "\n"))
         .count();
     assert_eq!(count_newlines, 2);
     // Two paragraphs
     let p_starts = ev
         .iter()
-        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph, _)))
         .count();
     let p_ends = ev
         .iter()
-        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph, _)))
         .count();
     assert_eq!((p_starts, p_ends), (2, 2));
 }
@@ -96,7 +107,7 @@ fn blank_run_limit_emits_diagnostic_but_preserves_text() {
     // One diagnostic when run first exceeds limit
     let diag_count = ev
         .iter()
-        .filter(|e| matches!(e, Event::Diagnostic { .. }))
+        .filter(|e| matches!(e, Event::Diagnostic(_)))
         .count();
     assert_eq!(diag_count, 1);
 }
@@ -107,11 +118,11 @@ fn leading_and_trailing_blank_lines() {
     assert_eq!(render_concat(&ev), "\n\nA\n\n");
     let p_starts = ev
         .iter()
-        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::StartNode(NodeKind::Paragraph, _)))
         .count();
     let p_ends = ev
         .iter()
-        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph)))
+        .filter(|e| matches!(e, Event::EndNode(NodeKind::Paragraph, _)))
         .count();
     assert_eq!((p_starts, p_ends), (1, 1));
 }
@@ -127,7 +138,255 @@ fn strict_escalates_blank_run_to_error() {
             max_blank_run: Some(1),
         },
     );
+    assert!(ev
+        .iter()
+        .any(|e| matches!(e, Event::Diagnostic(d) if d.severity == Severity::Error)));
+}
+
+#[test]
+fn every_event_carries_a_span() {
+    let ev = parse(
+        "# Title\n\nBody text\n```rs\ncode\n```\n",
+        &Options::default(),
+    );
+    assert!(ev.iter().all(|e| e.span().is_some()));
+}
+
+#[test]
+fn text_spans_track_lines_and_columns() {
+    let ev = parse("A\r\néB", &Options::default());
+    let span = ev
+        .iter()
+        .find_map(|e| match e {
+            Event::Text(t, span) if t == "éB" => *span,
+            _ => None,
+        })
+        .unwrap();
+    // CRLF counts as two bytes but a single line break; columns count chars
+    assert_eq!(span.start, Position::new(3, 2, 1));
+    assert_eq!(span.end, Position::new(6, 2, 3));
+}
+
+#[test]
+fn node_spans_cover_their_content() {
+    let ev = parse("A\nB\n\nC", &Options::default());
+    let paragraph = ev
+        .iter()
+        .find_map(|e| match e {
+            Event::StartNode(NodeKind::Paragraph, span) => *span,
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(paragraph.start.offset, 0);
+    assert_eq!(paragraph.end.offset, 5);
+    let document = ev.first().and_then(|e| e.span()).unwrap();
+    assert_eq!((document.start.offset, document.end.offset), (0, 6));
+}
+
+#[test]
+fn line_length_diagnostic_points_at_the_line() {
+    let opts = Options {
+        max_line_len: Some(3),
+        ..Options::default()
+    };
+    let ev = parse("ok\ntoo long\n", &opts);
+    let span = ev
+        .iter()
+        .find_map(|e| match e {
+            Event::Diagnostic(d) => {
+                assert_eq!(d.code, codes::LINE_TOO_LONG);
+                assert!(d.plugin.is_none() && d.help.is_some());
+                d.span
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(span.start, Position::new(3, 2, 1));
+    assert_eq!(span.end, Position::new(11, 2, 9));
+}
+
+#[test]
+fn chunked_parser_matches_batch_parse() {
+    let input = "# Title\r\n\nSome text — here\n```rust\nfn x() {}\n```\n\n- - -\ntail";
+    let opts = Options::default();
+    let expected = parse(input, &opts);
+    for split in [1, 3, 8, 9, 20, input.len() - 1] {
+        let mut parser = Parser::new(&opts);
+        let mut out: Vec<Event> = Vec::new();
+        // Split at arbitrary bytes, including mid-CRLF and inside multi-byte chars
+        let (head, rest) = input.as_bytes().split_at(split);
+        parser.feed_bytes(head);
+        out.extend(parser.by_ref());
+        parser.feed_bytes(rest);
+        parser.finish();
+        out.extend(parser);
+        // Only the opening Document span differs: it is zero-width while streaming
+        assert_eq!(out[1..], expected[1..], "split at {}", split);
+    }
+}
+
+#[test]
+fn reader_parser_matches_batch_parse() {
+    let input = "para one\ncontinued\n\n## Two\n\n```\ncode\n```\n";
+    let opts = Options::default();
+    let streamed: Vec<Event> = Parser::from_reader(input.as_bytes(), &opts).collect();
+    let expected = parse(input, &opts);
+    assert_eq!(streamed.len(), expected.len());
+    assert_eq!(streamed[1..], expected[1..]);
+    let opening = streamed[0].span().unwrap();
+    assert_eq!(opening.end.offset, 0);
+}
+
+#[test]
+fn chunked_parser_releases_closed_blocks_early() {
+    let mut parser = Parser::new(&Options::default());
+    parser.feed("first\n\nsec");
+    let early: Vec<Event> = parser.by_ref().collect();
+    assert!(early
+        .iter()
+        .any(|e| matches!(e, Event::EndNode(NodeKind::Paragraph, _))));
+    assert!(!early
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "sec")));
+    parser.feed("ond\n");
+    parser.finish();
+    let rest: Vec<Event> = parser.collect();
+    assert!(rest
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "second")));
+    assert!(matches!(
+        rest.last(),
+        Some(Event::EndNode(NodeKind::Document, _))
+    ));
+}
+
+#[test]
+fn invalid_utf8_from_reader_is_reported() {
+    let bytes: &[u8] = b"ok\n\xff\xfe\n";
+    let ev: Vec<Event> = Parser::from_reader(bytes, &Options::default()).collect();
     assert!(ev.iter().any(
-        |e| matches!(e, Event::Diagnostic { severity, .. } if matches!(severity, Severity::Error))
+        |e| matches!(e, Event::Diagnostic(d) if d.is_error() && d.code == codes::INVALID_UTF8)
     ));
 }
+
+#[test]
+fn batch_parse_borrows_text_from_input() {
+    let input = String::from("```rust\nlet x = 1;\n```\n");
+    let ev = parse(&input, &Options::default());
+    let borrowed = ev.iter().all(|e| match e {
+        Event::Text(text, _) if text != "\n" => matches!(text, Cow::Borrowed(_)),
+        Event::Attribute { name, value, .. } if name == "lang" => {
+            matches!(value, AttrValue::Str(Cow::Borrowed(_)))
+        }
+        _ => true,
+    });
+    assert!(borrowed);
+    // Detached copies outlive the source buffer
+    let owned: Vec<Event<'static>> = ev.into_iter().map(Event::into_owned).collect();
+    drop(input);
+    assert!(owned
+        .iter()
+        .any(|e| matches!(e, Event::Text(t, _) if t == "let x = 1;")));
+}
+
+// Text of each code fence in order, with its lang attribute
+fn fences(events: &[Event]) -> Vec<(Option<String>, String)> {
+    let mut out = Vec::new();
+    let mut current: Option<(Option<String>, String)> = None;
+    for ev in events {
+        match ev {
+            Event::StartNode(NodeKind::CodeFence, _) => current = Some((None, String::new())),
+            Event::EndNode(NodeKind::CodeFence, _) => out.extend(current.take()),
+            Event::Attribute { name, value, .. } if name == "lang" => {
+                if let Some((lang, _)) = current.as_mut() {
+                    *lang = Some(value.to_string());
+                }
+            }
+            Event::Text(t, _) => {
+                if let Some((_, body)) = current.as_mut() {
+                    body.push_str(t);
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[test]
+fn longer_fence_keeps_shorter_runs_as_content() {
+    let ev = parse(
+        "````md\n```rust\n# not a heading\n```\n````\n",
+        &Options::default(),
+    );
+    assert_eq!(
+        fences(&ev),
+        vec![(
+            Some("md".to_string()),
+            "\n```rust\n# not a heading\n```\n".to_string()
+        )]
+    );
+    assert!(!ev
+        .iter()
+        .any(|e| matches!(e, Event::StartNode(NodeKind::Heading, _))));
+}
+
+#[test]
+fn tilde_fences_only_close_on_tildes() {
+    let ev = parse("~~~\n```\n~~~~\nafter\n", &Options::default());
+    assert_eq!(fences(&ev), vec![(None, "\n```\n".to_string())]);
+    assert!(render_concat(&ev).ends_with("after\n"));
+}
+
+#[test]
+fn indented_fence_strips_content_indentation() {
+    let ev = parse("  ```\n    a\n b\n  ```\n", &Options::default());
+    assert_eq!(fences(&ev), vec![(None, "\n  a\nb\n".to_string())]);
+}
+
+#[test]
+fn unclosed_fence_runs_to_end_of_document() {
+    let ev = parse("```\ncode\n``", &Options::default());
+    assert_eq!(fences(&ev), vec![(None, "\ncode\n``".to_string())]);
+}
+
+#[test]
+fn backtick_info_string_with_backtick_is_not_a_fence() {
+    let ev = parse("``` a`b\n", &Options::default());
+    assert!(fences(&ev).is_empty());
+    assert_eq!(render_concat(&ev), "``` a`b\n");
+}
+
+#[test]
+fn fence_closes_open_paragraph() {
+    let ev = parse("text\n```\ncode\n```\n", &Options::default());
+    let kinds: Vec<_> = ev
+        .iter()
+        .filter_map(|e| match e {
+            Event::StartNode(k, _) => Some(format!("+{:?}", k)),
+            Event::EndNode(k, _) => Some(format!("-{:?}", k)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "+Document",
+            "+Paragraph",
+            "-Paragraph",
+            "+CodeFence",
+            "-CodeFence",
+            "-Document"
+        ]
+    );
+}
+
+#[test]
+fn hyphens_indented_four_spaces_are_not_a_thematic_break() {
+    let ev = parse("   ---\n\n    ---\n", &Options::default());
+    let breaks = ev
+        .iter()
+        .filter(|e| matches!(e, Event::StartNode(NodeKind::ThematicBreak, _)))
+        .count();
+    assert_eq!(breaks, 1);
+}
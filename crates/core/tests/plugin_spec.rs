@@ -0,0 +1,112 @@
+use pendon_core::{codes, Event, Limits, NodeKind, Plugin, PluginRegistry};
+
+struct Tag(&'static str, &'static [&'static str]);
+
+impl Plugin for Tag {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn after(&self) -> &[&str] {
+        self.1
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        let mut out = events.to_vec();
+        out.push(Event::Text(self.0.into(), None));
+        out
+    }
+}
+
+fn registry() -> PluginRegistry {
+    let mut reg = PluginRegistry::new();
+    reg.register(Tag("markdown", &[]));
+    reg.register(Tag("quiz", &["markdown"]));
+    reg.register(Tag("img", &[]));
+    reg
+}
+
+fn order(reg: &PluginRegistry, names: &[&str]) -> Vec<String> {
+    reg.pipeline(names)
+        .unwrap()
+        .iter()
+        .map(|p| p.name().to_string())
+        .collect()
+}
+
+#[test]
+fn after_constraints_reorder_only_when_both_requested() {
+    let reg = registry();
+    assert_eq!(
+        order(&reg, &["quiz", "img", "markdown"]),
+        ["img", "markdown", "quiz"]
+    );
+    assert_eq!(order(&reg, &["quiz", "img"]), ["quiz", "img"]);
+}
+
+#[test]
+fn unknown_plugins_and_cycles_are_errors() {
+    let mut reg = registry();
+    assert!(reg.pipeline(&["nope"]).is_err());
+    reg.register(Tag("markdown", &["quiz"]));
+    assert!(reg.pipeline(&["markdown", "quiz"]).is_err());
+}
+
+#[test]
+fn run_applies_plugins_in_pipeline_order() {
+    let reg = registry();
+    let events = vec![Event::StartNode(NodeKind::Document, None)];
+    let (out, hints) = reg.run(&["quiz", "markdown"], events).unwrap();
+    let texts: Vec<&str> = out
+        .iter()
+        .filter_map(|e| match e {
+            Event::Text(t, _) => Some(t.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, ["markdown", "quiz"]);
+    assert!(hints.is_empty());
+}
+
+#[test]
+fn run_inspect_sees_every_stage_in_order() {
+    let reg = registry();
+    let mut seen = Vec::new();
+    let (events, _) = reg
+        .run_inspect(&["quiz", "markdown"], Vec::new(), |name, events| {
+            seen.push((name.to_string(), events.len()))
+        })
+        .unwrap();
+    assert_eq!(seen, [("markdown".to_string(), 1), ("quiz".to_string(), 2)]);
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn exceeding_a_limit_aborts_the_remaining_stages() {
+    let mut reg = registry();
+    reg.set_limits(Limits {
+        max_events: Some(3),
+        ..Limits::default()
+    });
+    let events = vec![
+        Event::StartNode(NodeKind::Document, None),
+        Event::EndNode(NodeKind::Document, None),
+    ];
+    let mut stages = Vec::new();
+    let (out, _) = reg
+        .run_inspect(&["markdown", "img", "quiz"], events, |name, _| {
+            stages.push(name.to_string())
+        })
+        .unwrap();
+    assert_eq!(stages, ["markdown", "img"]);
+    assert_eq!(out.len(), 3);
+    match &out[1] {
+        Event::Diagnostic(d) => {
+            assert!(d.is_error());
+            assert_eq!(d.code, codes::TOO_MANY_EVENTS);
+            assert_eq!(d.plugin.as_deref(), Some("img"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(out[2], Event::EndNode(NodeKind::Document, _)));
+}
@@ -0,0 +1,86 @@
+use pendon_core::{
+    codes, parse, validate, Diagnostic, Event, NodeKind, Options, Position, Severity, Span,
+};
+
+fn diagnostics(events: &[Event]) -> Vec<Diagnostic> {
+    validate(events)
+        .into_iter()
+        .map(|ev| match ev {
+            Event::Diagnostic(d) if d.severity == Severity::Error => *d,
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect()
+}
+
+fn messages(events: &[Event]) -> Vec<String> {
+    diagnostics(events).into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn parser_output_is_well_formed() {
+    let input = "intro\n# Title\nmore\n\n```\n# code\n```\n---\n\ntail";
+    assert!(messages(&parse(input, &Options::default())).is_empty());
+}
+
+#[test]
+fn reports_unbalanced_and_mismatched_nodes() {
+    let events: Vec<Event<'static>> = vec![
+        Event::StartNode(NodeKind::Document, None),
+        Event::StartNode(NodeKind::Paragraph, None),
+        Event::StartNode(NodeKind::Strong, None),
+        Event::EndNode(NodeKind::Paragraph, None),
+        Event::EndNode(NodeKind::Heading, None),
+        Event::StartNode(NodeKind::Blockquote, None),
+    ];
+    assert_eq!(
+        messages(&events),
+        [
+            "end of Paragraph while Strong is still open",
+            "end of Heading without a matching start",
+            "Blockquote is never closed",
+            "Document is never closed",
+        ]
+    );
+}
+
+#[test]
+fn reports_text_and_attributes_outside_nodes() {
+    let events: Vec<Event<'static>> = vec![
+        Event::Attribute {
+            name: "level".into(),
+            value: "1".into(),
+            span: None,
+        },
+        Event::StartNode(NodeKind::Document, None),
+        Event::EndNode(NodeKind::Document, None),
+        Event::Text("stray".into(), None),
+        Event::StartNode(NodeKind::Paragraph, None),
+        Event::EndNode(NodeKind::Paragraph, None),
+    ];
+    assert_eq!(
+        messages(&events),
+        [
+            "attribute 'level' outside any node",
+            "text outside any node",
+            "Paragraph starts a second root node",
+        ]
+    );
+}
+
+#[test]
+fn mismatched_end_points_back_at_the_open_node() {
+    let opened = Span::new(Position::new(2, 1, 3), Position::new(4, 1, 5));
+    let events: Vec<Event<'static>> = vec![
+        Event::StartNode(NodeKind::Document, None),
+        Event::StartNode(NodeKind::Paragraph, None),
+        Event::StartNode(NodeKind::Strong, Some(opened)),
+        Event::EndNode(NodeKind::Paragraph, None),
+        Event::EndNode(NodeKind::Document, None),
+    ];
+    let diags = diagnostics(&events);
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].code, codes::MISMATCHED_END);
+    assert_eq!(diags[0].related.len(), 1);
+    assert_eq!(diags[0].related[0].span, opened);
+    assert_eq!(diags[0].related[0].message, "Strong opened here");
+}
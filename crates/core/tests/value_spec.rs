@@ -0,0 +1,33 @@
+use pendon_core::AttrValue;
+
+#[test]
+fn strings_display_raw_and_other_values_as_json() {
+    assert_eq!(AttrValue::from("a \"b\"").to_string(), "a \"b\"");
+    assert_eq!(AttrValue::Int(-4).to_string(), "-4");
+    assert_eq!(AttrValue::Float(f64::NAN).to_string(), "null");
+
+    let value = AttrValue::Map(vec![
+        ("text".into(), "line\n\"q\"".into()),
+        ("ok".into(), true.into()),
+        (
+            "items".into(),
+            AttrValue::List(vec![AttrValue::Float(1.5), AttrValue::Null]),
+        ),
+    ]);
+    assert_eq!(
+        value.to_string(),
+        r#"{"text":"line\n\"q\"","ok":true,"items":[1.5,null]}"#
+    );
+}
+
+#[test]
+fn map_lookup_and_accessors() {
+    let value = AttrValue::Map(vec![
+        ("level".into(), AttrValue::Int(2)),
+        ("id".into(), "intro".into()),
+    ]);
+    assert_eq!(value.get("level").and_then(AttrValue::as_int), Some(2));
+    assert!(value.get("id").is_some_and(|v| *v == "intro"));
+    assert!(value.get("missing").is_none());
+    assert_eq!(AttrValue::from("x").as_int(), None);
+}
@@ -1,3 +1,5 @@
+use pendon_core::{is_safe_attr, parse_attrs, trailing_attrs};
+
 use self::LineKind::{Delete, Insert, Plain};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +22,9 @@ pub struct ParsedInfo {
     pub inline_patterns: Vec<String>,
     pub line_ranges: Vec<LineRange>,
     pub pre_classes: Vec<String>,
+    /// `id` and `key=value` attributes of a trailing `{...}` block, less
+    /// those `is_safe_attr` rejects.
+    pub attrs: Vec<(String, String)>,
 }
 
 pub fn parse_info_string(raw: &str) -> ParsedInfo {
@@ -27,6 +32,18 @@ pub fn parse_info_string(raw: &str) -> ParsedInfo {
     let mut inline_patterns: Vec<String> = Vec::new();
     let mut line_ranges: Vec<LineRange> = Vec::new();
     let mut pre_classes: Vec<String> = Vec::new();
+    let mut attrs: Vec<(String, String)> = Vec::new();
+
+    // `js {1} {#demo .wide}`: the attribute block ends the info string
+    let raw = match trailing_attrs(raw) {
+        Some((rest, block)) => {
+            pre_classes.extend(block.classes.iter().cloned());
+            attrs.extend(block.id.map(|id| ("id".to_string(), id)));
+            attrs.extend(block.pairs.into_iter().filter(|(k, _)| is_safe_attr(k)));
+            rest
+        }
+        None => raw,
+    };
 
     let bytes: Vec<char> = raw.chars().collect();
     let mut i = 0usize;
@@ -60,9 +77,11 @@ pub fn parse_info_string(raw: &str) -> ParsedInfo {
             continue;
         }
 
-        if let Some(rest) = token.strip_prefix(".") {
-            pre_classes.push(rest.to_string());
-            continue;
+        if token.starts_with('.') {
+            if let Some(block) = parse_attrs(&token) {
+                pre_classes.extend(block.classes);
+                continue;
+            }
         }
         if let Some(rest) = token.strip_prefix("ins=") {
             if let Some((range, _)) = parse_range_str(rest, Insert) {
@@ -94,6 +113,7 @@ pub fn parse_info_string(raw: &str) -> ParsedInfo {
         inline_patterns,
         line_ranges,
         pre_classes,
+        attrs,
     }
 }
 
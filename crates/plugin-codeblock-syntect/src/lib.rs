@@ -1,4 +1,4 @@
-use pendon_core::{Event, NodeKind};
+use pendon_core::{join_spans, Event, NodeKind, Plugin};
 
 mod highlight;
 mod info;
@@ -6,48 +6,46 @@ mod inline;
 mod mappers;
 mod wrap;
 
-#[cfg(test)]
-mod tests {
-    use super::highlight::highlight_output;
-    use super::info::parse_info_string;
-
-    #[test]
-    fn line_wrappers_ignore_pre_classes() {
-        let info = parse_info_string("js .wrap {1} {3-5}");
-        let code = "import { log } from \"console\";\nconst data = [10, 20, null];\nasync function* hitung(a, b = 5) {\n  for (let x of data) {\n    if (x?.val ?? true) yield (a + b) * x;\n  }\n}\n";
-        let out = highlight_output(code, &info, None);
-
-        assert!(out.contains("<p class=\"mark\">"));
-        assert!(out.contains("<p>const data"));
-        assert!(!out.contains("<p class=\"wrap"));
-    }
-}
-
 use highlight::highlight_output;
 use info::parse_info_string;
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
     while i < events.len() {
         match &events[i] {
-            Event::StartNode(NodeKind::CodeFence) => {
+            Event::StartNode(NodeKind::CodeFence, _) => {
                 // Collect attrs + inner text until EndNode(CodeFence)
                 let mut j = i + 1;
                 let mut raw_info: Option<String> = None;
                 let mut debug: Option<String> = None;
+                let mut classes: Vec<String> = Vec::new();
+                // Attributes the highlighter does not consume (`id`, ...)
+                let mut kept: Vec<Event> = Vec::new();
                 let mut inner = String::new();
+                let mut lang_span = None;
+                let mut inner_span = None;
                 while j < events.len() {
+                    let ev_span = events[j].span();
                     match &events[j] {
-                        Event::Attribute { name, value } => {
+                        Event::Attribute { name, value, .. } => {
                             if name == "lang" {
-                                raw_info = Some(value.clone());
+                                raw_info = Some(value.to_string());
+                                lang_span = ev_span;
                             } else if name == "syntect_debug" {
-                                debug = Some(value.clone());
+                                debug = Some(value.to_string());
+                            } else if name == "class" {
+                                classes
+                                    .extend(value.to_string().split_whitespace().map(String::from));
+                            } else if name != "raw_html" {
+                                kept.push(events[j].clone());
                             }
                         }
-                        Event::Text(t) => inner.push_str(t),
-                        Event::EndNode(NodeKind::CodeFence) => break,
+                        Event::Text(t, _) => {
+                            inner.push_str(t);
+                            inner_span = join_spans(inner_span, ev_span);
+                        }
+                        Event::EndNode(NodeKind::CodeFence, _) => break,
                         _ => {}
                     }
                     j += 1;
@@ -70,30 +68,44 @@ pub fn process(events: &[Event]) -> Vec<Event> {
                     .map(parse_info_string)
                     .unwrap_or_default();
                 let highlighted = highlight_output(&inner, &parsed_info, debug_mode.as_deref());
-                out.push(Event::StartNode(NodeKind::CodeFence));
+                // The highlighted fence keeps the spans of the fence it replaces
+                let fence_span = join_spans(events[i].span(), events[j].span());
+                out.push(Event::StartNode(NodeKind::CodeFence, fence_span));
                 if let Some(l) = parsed_info
                     .lang
                     .clone()
                     .or_else(|| raw_info.as_ref().cloned())
                 {
                     out.push(Event::Attribute {
-                        name: "lang".to_string(),
-                        value: l,
+                        name: "lang".into(),
+                        value: l.into(),
+                        span: lang_span,
                     });
                 }
-                if !parsed_info.pre_classes.is_empty() {
+                classes.extend(parsed_info.pre_classes.iter().cloned());
+                if !classes.is_empty() {
                     out.push(Event::Attribute {
-                        name: "class".to_string(),
-                        value: parsed_info.pre_classes.join(" "),
+                        name: "class".into(),
+                        value: classes.join(" ").into(),
+                        span: lang_span,
+                    });
+                }
+                out.extend(kept);
+                for (name, value) in &parsed_info.attrs {
+                    out.push(Event::Attribute {
+                        name: name.clone().into(),
+                        value: value.clone().into(),
+                        span: lang_span,
                     });
                 }
                 // Mark as raw HTML payload for downstream renderer
                 out.push(Event::Attribute {
-                    name: "raw_html".to_string(),
-                    value: "1".to_string(),
+                    name: "raw_html".into(),
+                    value: "1".into(),
+                    span: fence_span,
                 });
-                out.push(Event::Text(highlighted));
-                out.push(Event::EndNode(NodeKind::CodeFence));
+                out.push(Event::Text(highlighted.into(), inner_span));
+                out.push(Event::EndNode(NodeKind::CodeFence, fence_span));
                 i = j + 1;
             }
             _ => {
@@ -104,3 +116,68 @@ pub fn process(events: &[Event]) -> Vec<Event> {
     }
     out
 }
+
+/// Registers [`process`] as the `syntect` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyntectPlugin;
+
+impl<H> Plugin<H> for SyntectPlugin {
+    fn name(&self) -> &str {
+        "syntect"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlight::highlight_output;
+    use super::info::parse_info_string;
+
+    #[test]
+    fn line_wrappers_ignore_pre_classes() {
+        let info = parse_info_string("js .wrap {1} {3-5}");
+        let code = "import { log } from \"console\";\nconst data = [10, 20, null];\nasync function* hitung(a, b = 5) {\n  for (let x of data) {\n    if (x?.val ?? true) yield (a + b) * x;\n  }\n}\n";
+        let out = highlight_output(code, &info, None);
+
+        assert!(out.contains("<p class=\"mark\">"));
+        assert!(out.contains("<p>const data"));
+        assert!(!out.contains("<p class=\"wrap"));
+    }
+
+    #[test]
+    fn attribute_block_in_info_string_is_kept_on_the_fence() {
+        use pendon_core::{Event, NodeKind};
+
+        let events = vec![
+            Event::StartNode(NodeKind::CodeFence, None),
+            Event::Attribute {
+                name: "lang".into(),
+                value: "js .wrap {1} {#demo .wide data-x=1}".into(),
+                span: None,
+            },
+            Event::Text("let a = 1;\n".into(), None),
+            Event::EndNode(NodeKind::CodeFence, None),
+        ];
+        let attrs: Vec<(String, String)> = super::process(&events)
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Attribute { name, value, .. } => Some((name.to_string(), value.to_string())),
+                _ => None,
+            })
+            .collect();
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert_eq!(
+            attrs,
+            [
+                pair("lang", "js"),
+                pair("class", "wide wrap"),
+                pair("id", "demo"),
+                pair("data-x", "1"),
+                pair("raw_html", "1"),
+            ]
+        );
+    }
+}
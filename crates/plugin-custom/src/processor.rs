@@ -5,12 +5,30 @@ mod codefence;
 mod util;
 
 use crate::specs::PluginSpec;
-use pendon_core::Event;
+pub use attrs::{INVALID_ATTRIBUTE, MISSING_ATTRIBUTE};
+use pendon_core::{Event, Plugin};
 
-pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>], spec: &PluginSpec) -> Vec<Event<'a>> {
     match spec.matcher.parse_hint.as_deref() {
         Some("blockquote-sigil") => blockquote::process(events, spec),
         Some("codefence-viewer") | Some("codefence-lang") => codefence::process(events, spec),
         _ => block::process(events, spec),
     }
 }
+
+/// A loaded spec registered as a plugin under `name`.
+#[derive(Debug, Clone)]
+pub struct CustomPlugin {
+    pub name: String,
+    pub spec: PluginSpec,
+}
+
+impl<H> Plugin<H> for CustomPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events, &self.spec)
+    }
+}
@@ -1,12 +1,16 @@
 use crate::specs::{AttrType, PluginSpec};
-use pendon_core::{Event, Severity};
+use pendon_core::{Diagnostic, DiagnosticCode, Event, Span};
 use regex::Captures;
 use std::collections::BTreeMap;
 
-pub fn collect_attrs(
+pub const INVALID_ATTRIBUTE: DiagnosticCode = DiagnosticCode::new("PD0111", "invalid-attribute");
+pub const MISSING_ATTRIBUTE: DiagnosticCode = DiagnosticCode::new("PD0112", "missing-attribute");
+
+pub fn collect_attrs<'a>(
     spec: &PluginSpec,
     caps: Option<&Captures>,
-) -> (BTreeMap<String, String>, Vec<Event>) {
+    span: Option<Span>,
+) -> (BTreeMap<String, String>, Vec<Event<'a>>) {
     let mut out: BTreeMap<String, String> = BTreeMap::new();
     let mut diags: Vec<Event> = Vec::new();
 
@@ -44,26 +48,31 @@ pub fn collect_attrs(
                     out.insert(attr.name.clone(), parsed);
                 }
                 None => {
-                    diags.push(Event::Diagnostic {
-                        severity: Severity::Error,
-                        message: format!(
-                            "[plugin-custom:{}] attribute '{}' failed to parse as {}",
-                            spec.name, attr.name, attr.r#type
-                        ),
-                        span: None,
-                    });
+                    let message = format!(
+                        "attribute '{}' failed to parse as {}",
+                        attr.name, attr.r#type
+                    );
+                    diags.push(
+                        Diagnostic::error(INVALID_ATTRIBUTE, message)
+                            .with_plugin(spec.name.clone())
+                            .with_span(span)
+                            .into(),
+                    );
                 }
             },
             None => {
                 if attr.required {
-                    diags.push(Event::Diagnostic {
-                        severity: Severity::Error,
-                        message: format!(
-                            "[plugin-custom:{}] missing required attribute '{}'",
-                            spec.name, attr.name
-                        ),
-                        span: None,
-                    });
+                    let message = format!("missing required attribute '{}'", attr.name);
+                    diags.push(
+                        Diagnostic::error(MISSING_ATTRIBUTE, message)
+                            .with_plugin(spec.name.clone())
+                            .with_span(span)
+                            .with_help(format!(
+                                "set `{}` in the block or give it a default",
+                                attr.name
+                            ))
+                            .into(),
+                    );
                 }
             }
         }
@@ -1,9 +1,9 @@
 use crate::processor::{attrs, util};
 use crate::specs::PluginSpec;
-use pendon_core::{Event, NodeKind};
+use pendon_core::{events_span, join_spans, Event, NodeKind, Span};
 use std::collections::BTreeMap;
 
-pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>], spec: &PluginSpec) -> Vec<Event<'a>> {
     let Some(detector) = util::build_start_detector(spec) else {
         return events.to_vec();
     };
@@ -19,13 +19,14 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
 
     for ev in events.iter() {
         if active.is_none() {
-            if let Event::Text(line) = ev {
+            if let Event::Text(line, span) = ev {
                 if detector.is_match(line.trim()) {
                     let captures = detector.captures(line.trim());
-                    let (attrs, diags) = attrs::collect_attrs(spec, captures.as_ref());
+                    let (attrs, diags) = attrs::collect_attrs(spec, captures.as_ref(), *span);
                     out.extend(diags);
                     let mut block = ActiveBlock::new(spec.clone(), attrs);
-                    if matches!(out.last(), Some(Event::StartNode(NodeKind::Paragraph))) {
+                    block.span = *span;
+                    if matches!(out.last(), Some(Event::StartNode(NodeKind::Paragraph, _))) {
                         out.pop();
                         block.skip_para_close = true;
                     }
@@ -38,8 +39,9 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
         }
 
         if let Some(block) = active.as_mut() {
-            if let Event::Text(line) = ev {
+            if let Event::Text(line, span) = ev {
                 if line.trim() == end_marker {
+                    block.span = join_spans(block.span, *span);
                     if let Some(idx) = block.last_para_start.take() {
                         block.inner.truncate(idx);
                         block.skip_para_close = true;
@@ -51,14 +53,12 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
             }
 
             match ev {
-                Event::Text(line) => {
-                    block.inner.push(Event::Text(line.clone()));
-                }
-                Event::StartNode(NodeKind::Paragraph) => {
+                Event::Text(..) => block.inner.push(ev.clone()),
+                Event::StartNode(NodeKind::Paragraph, _) => {
                     block.last_para_start = Some(block.inner.len());
                     block.inner.push(ev.clone());
                 }
-                Event::EndNode(NodeKind::Paragraph) => {
+                Event::EndNode(NodeKind::Paragraph, _) => {
                     if block.skip_para_close {
                         block.skip_para_close = false;
                     } else {
@@ -79,15 +79,17 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
 }
 
 #[derive(Debug, Clone)]
-pub struct ActiveBlock {
+pub struct ActiveBlock<'a> {
     spec: PluginSpec,
     attrs: BTreeMap<String, String>,
-    inner: Vec<Event>,
+    inner: Vec<Event<'a>>,
     skip_para_close: bool,
     last_para_start: Option<usize>,
+    /// Covers the opening marker through the closing marker.
+    span: Option<Span>,
 }
 
-impl ActiveBlock {
+impl<'a> ActiveBlock<'a> {
     pub fn new(spec: PluginSpec, attrs: BTreeMap<String, String>) -> Self {
         ActiveBlock {
             spec,
@@ -95,12 +97,14 @@ impl ActiveBlock {
             inner: Vec::new(),
             skip_para_close: false,
             last_para_start: None,
+            span: None,
         }
     }
 
-    pub fn finish(self) -> Vec<Event> {
+    pub fn finish(self) -> Vec<Event<'a>> {
         let mut out = Vec::with_capacity(self.inner.len() + 4);
-        util::emit_component(&self.spec, &self.attrs, Some(&self.inner), &mut out);
+        let span = join_spans(self.span, events_span(&self.inner));
+        util::emit_component(&self.spec, &self.attrs, Some(&self.inner), span, &mut out);
         out
     }
 }
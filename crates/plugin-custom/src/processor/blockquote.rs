@@ -1,9 +1,9 @@
 use crate::processor::{attrs, util};
 use crate::specs::PluginSpec;
-use pendon_core::{Event, NodeKind};
+use pendon_core::{events_span, Event, NodeKind, Span};
 use regex::Match;
 
-pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>], spec: &PluginSpec) -> Vec<Event<'a>> {
     let Some(detector) = util::build_start_detector(spec) else {
         return events.to_vec();
     };
@@ -11,14 +11,14 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
     let mut out = Vec::with_capacity(events.len());
     let mut i = 0usize;
     while i < events.len() {
-        if matches!(events[i], Event::StartNode(NodeKind::Blockquote)) {
+        if matches!(events[i], Event::StartNode(NodeKind::Blockquote, _)) {
             let start = i;
             let mut depth = 1usize;
             let mut j = i + 1;
             while j < events.len() && depth > 0 {
                 match &events[j] {
-                    Event::StartNode(NodeKind::Blockquote) => depth += 1,
-                    Event::EndNode(NodeKind::Blockquote) => depth -= 1,
+                    Event::StartNode(NodeKind::Blockquote, _) => depth += 1,
+                    Event::EndNode(NodeKind::Blockquote, _) => depth -= 1,
                     _ => {}
                 }
                 j += 1;
@@ -31,13 +31,13 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
 
             let mut k = start + 1;
             while k + 1 < j {
-                if matches!(events[k], Event::StartNode(NodeKind::Paragraph)) {
+                if matches!(events[k], Event::StartNode(NodeKind::Paragraph, _)) {
                     let para_start = k;
                     let mut buf = String::new();
                     let mut body_events: Vec<Event> = Vec::new();
                     k += 1;
-                    while k < j && !matches!(events[k], Event::EndNode(NodeKind::Paragraph)) {
-                        if let Event::Text(t) = &events[k] {
+                    while k < j && !matches!(events[k], Event::EndNode(NodeKind::Paragraph, _)) {
+                        if let Event::Text(t, _) = &events[k] {
                             buf.push_str(t);
                         }
                         body_events.push(events[k].clone());
@@ -48,10 +48,11 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
                     }
 
                     if let Some(caps) = detector.captures(buf.trim()) {
-                        let (attrs, diags) = attrs::collect_attrs(spec, Some(&caps));
+                        let span = events_span(&events[para_start..k]);
+                        let (attrs, diags) = attrs::collect_attrs(spec, Some(&caps), span);
                         out.extend(diags);
                         let cleaned = strip_leading_sigil(&body_events, caps.name("type"));
-                        util::emit_component(spec, &attrs, Some(&cleaned), &mut out);
+                        util::emit_component(spec, &attrs, Some(&cleaned), span, &mut out);
                     } else {
                         out.extend_from_slice(&events[para_start..k]);
                     }
@@ -70,7 +71,7 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
     out
 }
 
-fn strip_leading_sigil(events: &[Event], sigil: Option<Match<'_>>) -> Vec<Event> {
+fn strip_leading_sigil<'a>(events: &[Event<'a>], sigil: Option<Match<'_>>) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut removed = false;
     let mut trim_next = false;
@@ -78,7 +79,11 @@ fn strip_leading_sigil(events: &[Event], sigil: Option<Match<'_>>) -> Vec<Event>
 
     for ev in events {
         match ev {
-            Event::Text(text) => {
+            Event::Text(text, span) => {
+                // Remaining text is always a suffix of `text`
+                let tail_span = |rest: &str| -> Option<Span> {
+                    span.map(|sp| sp.slice(text, text.len() - rest.len()..text.len()))
+                };
                 if !removed {
                     if let Some(sig) = &sig {
                         let trimmed = text.trim_start();
@@ -88,7 +93,7 @@ fn strip_leading_sigil(events: &[Event], sigil: Option<Match<'_>>) -> Vec<Event>
                             if rest.is_empty() {
                                 trim_next = true;
                             } else {
-                                out.push(Event::Text(rest.to_string()));
+                                out.push(Event::Text(rest.to_string().into(), tail_span(rest)));
                             }
                             removed = true;
                             continue;
@@ -97,10 +102,11 @@ fn strip_leading_sigil(events: &[Event], sigil: Option<Match<'_>>) -> Vec<Event>
                 }
 
                 if trim_next {
-                    out.push(Event::Text(text.trim_start().to_string()));
+                    let rest = text.trim_start();
+                    out.push(Event::Text(rest.to_string().into(), tail_span(rest)));
                     trim_next = false;
                 } else {
-                    out.push(Event::Text(text.clone()));
+                    out.push(Event::Text(text.clone(), *span));
                 }
             }
             other => {
@@ -1,25 +1,29 @@
 use crate::processor::{attrs, util};
 use crate::specs::PluginSpec;
-use pendon_core::{Event, NodeKind};
+use pendon_core::{events_span, join_spans, Event, NodeKind};
 
-pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>], spec: &PluginSpec) -> Vec<Event<'a>> {
     let Some(detector) = util::build_start_detector(spec) else {
         return events.to_vec();
     };
     let mut out = Vec::with_capacity(events.len());
     let mut i = 0usize;
     while i < events.len() {
-        if matches!(events[i], Event::StartNode(NodeKind::CodeFence)) {
+        if matches!(events[i], Event::StartNode(NodeKind::CodeFence, _)) {
             let mut j = i + 1;
             let mut lang: Option<String> = None;
             let mut body = String::new();
+            let mut body_span = None;
             while j < events.len() {
                 match &events[j] {
-                    Event::Attribute { name, value } if name == "lang" => {
-                        lang = Some(value.clone());
+                    Event::Attribute { name, value, .. } if name == "lang" => {
+                        lang = Some(value.to_string());
                     }
-                    Event::Text(t) => body.push_str(t),
-                    Event::EndNode(NodeKind::CodeFence) => {
+                    Event::Text(t, sp) => {
+                        body.push_str(t);
+                        body_span = join_spans(body_span, *sp);
+                    }
+                    Event::EndNode(NodeKind::CodeFence, _) => {
                         j += 1;
                         break;
                     }
@@ -30,13 +34,16 @@ pub fn process(events: &[Event], spec: &PluginSpec) -> Vec<Event> {
 
             if let Some(l) = lang.as_deref() {
                 if detector.is_match(l.trim()) {
-                    let (mut attrs, diags) = attrs::collect_attrs(spec, None);
+                    let span = events_span(&events[i..j]);
+                    let (mut attrs, diags) = attrs::collect_attrs(spec, None, span);
                     out.extend(diags);
                     if matches!(spec.matcher.parse_hint.as_deref(), Some("codefence-viewer")) {
                         attrs.insert("value".to_string(), minify_inline(&body));
-                        util::emit_component(spec, &attrs, None, &mut out);
+                        util::emit_component(spec, &attrs, None, span, &mut out);
                     } else {
-                        util::emit_component_with_body(spec, &attrs, &body, &mut out);
+                        util::emit_component_with_body(
+                            spec, &attrs, &body, body_span, span, &mut out,
+                        );
                     }
                     i = j;
                     continue;
@@ -1,5 +1,5 @@
 use crate::specs::PluginSpec;
-use pendon_core::{Event, NodeKind};
+use pendon_core::{Event, NodeKind, Span};
 use regex::Regex;
 use std::collections::BTreeMap;
 
@@ -11,12 +11,16 @@ pub fn resolve_node_kind(spec: &PluginSpec) -> NodeKind {
                 "Frontmatter" => NodeKind::Frontmatter,
                 "Paragraph" => NodeKind::Paragraph,
                 "Blockquote" => NodeKind::Blockquote,
+                "Callout" => NodeKind::Callout,
                 "CodeFence" => NodeKind::CodeFence,
                 "Heading" => NodeKind::Heading,
                 "ThematicBreak" => NodeKind::ThematicBreak,
                 "BulletList" => NodeKind::BulletList,
                 "OrderedList" => NodeKind::OrderedList,
                 "ListItem" => NodeKind::ListItem,
+                "DefinitionList" => NodeKind::DefinitionList,
+                "DefinitionTerm" => NodeKind::DefinitionTerm,
+                "DefinitionDescription" => NodeKind::DefinitionDescription,
                 "Table" => NodeKind::Table,
                 "TableHead" => NodeKind::TableHead,
                 "TableBody" => NodeKind::TableBody,
@@ -29,6 +33,9 @@ pub fn resolve_node_kind(spec: &PluginSpec) -> NodeKind {
                 "Link" => NodeKind::Link,
                 "Bold" => NodeKind::Bold,
                 "Italic" => NodeKind::Italic,
+                "Strikethrough" => NodeKind::Strikethrough,
+                "FootnoteReference" => NodeKind::FootnoteReference,
+                "FootnoteDefinition" => NodeKind::FootnoteDefinition,
                 other => NodeKind::Custom(other.to_string()),
             };
         }
@@ -47,27 +54,30 @@ pub fn build_start_detector(spec: &PluginSpec) -> Option<Regex> {
     }
 }
 
-pub fn emit_component(
+pub fn emit_component<'a>(
     spec: &PluginSpec,
     attrs: &BTreeMap<String, String>,
-    children: Option<&[Event]>,
-    out: &mut Vec<Event>,
+    children: Option<&[Event<'a>]>,
+    span: Option<Span>,
+    out: &mut Vec<Event<'a>>,
 ) {
     let nk = resolve_node_kind(spec);
-    out.push(Event::StartNode(nk.clone()));
+    out.push(Event::StartNode(nk.clone(), span));
     if let Some(ast) = &spec.ast {
         if let Some(name) = &ast.node_name {
             out.push(Event::Attribute {
-                name: "name".to_string(),
-                value: name.clone(),
+                name: "name".into(),
+                value: name.clone().into(),
+                span,
             });
         }
         if let Some(map) = &ast.attrs_map {
             for (from, to) in map.iter() {
                 if let Some(val) = attrs.get(from) {
                     out.push(Event::Attribute {
-                        name: to.clone(),
-                        value: val.clone(),
+                        name: to.clone().into(),
+                        value: val.clone().into(),
+                        span,
                     });
                 }
             }
@@ -76,15 +86,17 @@ pub fn emit_component(
     if let Some(children) = children {
         out.extend(children.iter().cloned());
     }
-    out.push(Event::EndNode(nk));
+    out.push(Event::EndNode(nk, span));
 }
 
 pub fn emit_component_with_body(
     spec: &PluginSpec,
     attrs: &BTreeMap<String, String>,
     body: &str,
+    body_span: Option<Span>,
+    span: Option<Span>,
     out: &mut Vec<Event>,
 ) {
-    let children = [Event::Text(body.to_string())];
-    emit_component(spec, attrs, Some(&children), out);
+    let children = [Event::Text(body.to_string().into(), body_span)];
+    emit_component(spec, attrs, Some(&children), span, out);
 }
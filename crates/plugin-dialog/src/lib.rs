@@ -5,3 +5,19 @@ mod render;
 mod tokenize;
 
 pub use pipeline::process;
+
+use pendon_core::{Event, Plugin};
+
+/// Registers [`process`] as the `dialog` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DialogPlugin;
+
+impl<H> Plugin<H> for DialogPlugin {
+    fn name(&self) -> &str {
+        "dialog"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+}
@@ -1,21 +1,23 @@
 use crate::charmap::extract_charmap;
-use crate::render::{render_dd, render_dt};
-use pendon_core::{Event, NodeKind};
+use crate::render::render_dd;
+use pendon_core::{events_span, Event, NodeKind, Span};
 use std::collections::HashMap;
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let charmap = extract_charmap(events);
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
 
     while i < events.len() {
-        if matches!(events.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(
+            events.get(i),
+            Some(Event::StartNode(NodeKind::Paragraph, _))
+        ) {
             if let Some(end) = find_matching_end(events, i, NodeKind::Paragraph) {
                 let block = &events[i + 1..end];
-                if let Some(html) = maybe_render_dialog_block(block, &charmap) {
-                    out.push(Event::StartNode(NodeKind::HtmlBlock));
-                    out.push(Event::Text(html));
-                    out.push(Event::EndNode(NodeKind::HtmlBlock));
+                if let Some(rows) = parse_dialog_block(block) {
+                    let span = events_span(&events[i..=end]);
+                    push_dialog_list(&mut out, rows, &charmap, span);
                     i = end + 1;
                     continue;
                 }
@@ -32,10 +34,7 @@ pub fn process(events: &[Event]) -> Vec<Event> {
     out
 }
 
-fn maybe_render_dialog_block(
-    block_events: &[Event],
-    charmap: &HashMap<String, String>,
-) -> Option<String> {
+fn parse_dialog_block(block_events: &[Event]) -> Option<Vec<(String, String)>> {
     let raw = collect_text(block_events);
     if raw.trim().is_empty() {
         return None;
@@ -55,21 +54,45 @@ fn maybe_render_dialog_block(
         rows.push((speaker.to_string(), content.trim().to_string()));
     }
 
-    if rows.is_empty() {
-        return None;
-    }
+    (!rows.is_empty()).then_some(rows)
+}
 
-    let mut html = String::new();
-    html.push_str("<dl>\n");
+// One `DefinitionList` per dialog block: the speaker is the term and the
+// rendered line its description, both classed from the charmap
+fn push_dialog_list<'a>(
+    out: &mut Vec<Event<'a>>,
+    rows: Vec<(String, String)>,
+    charmap: &HashMap<String, String>,
+    span: Option<Span>,
+) {
+    out.push(Event::StartNode(NodeKind::DefinitionList, span));
     for (speaker, content) in rows {
-        let class = charmap.get(&speaker).cloned();
-        html.push_str(&render_dt(&speaker, class.as_deref()));
-        html.push(' ');
-        html.push_str(&render_dd(&content, class.as_deref()));
-        html.push('\n');
+        let class = charmap.get(&speaker);
+        let html = render_dd(&content);
+
+        out.push(Event::StartNode(NodeKind::DefinitionTerm, span));
+        push_class(out, class, span);
+        out.push(Event::Text(speaker.into(), span));
+        out.push(Event::EndNode(NodeKind::DefinitionTerm, span));
+
+        out.push(Event::StartNode(NodeKind::DefinitionDescription, span));
+        push_class(out, class, span);
+        out.push(Event::StartNode(NodeKind::HtmlInline, span));
+        out.push(Event::Text(html.into(), span));
+        out.push(Event::EndNode(NodeKind::HtmlInline, span));
+        out.push(Event::EndNode(NodeKind::DefinitionDescription, span));
+    }
+    out.push(Event::EndNode(NodeKind::DefinitionList, span));
+}
+
+fn push_class(out: &mut Vec<Event>, class: Option<&String>, span: Option<Span>) {
+    if let Some(class) = class {
+        out.push(Event::Attribute {
+            name: "class".into(),
+            value: class.clone().into(),
+            span,
+        });
     }
-    html.push_str("</dl>\n");
-    Some(html)
 }
 
 fn is_valid_speaker(speaker: &str) -> bool {
@@ -90,7 +113,7 @@ fn is_valid_speaker(speaker: &str) -> bool {
 fn collect_text(events: &[Event]) -> String {
     let mut out = String::new();
     for ev in events {
-        if let Event::Text(t) = ev {
+        if let Event::Text(t, _) = ev {
             out.push_str(t);
         }
     }
@@ -101,8 +124,8 @@ fn find_matching_end(events: &[Event], start_idx: usize, kind: NodeKind) -> Opti
     let mut depth = 0isize;
     for (idx, ev) in events.iter().enumerate().skip(start_idx) {
         match ev {
-            Event::StartNode(k) if *k == kind => depth += 1,
-            Event::EndNode(k) if *k == kind => {
+            Event::StartNode(k, _) if *k == kind => depth += 1,
+            Event::EndNode(k, _) if *k == kind => {
                 depth -= 1;
                 if depth == 0 {
                     return Some(idx);
@@ -121,32 +144,58 @@ mod tests {
     #[test]
     fn does_not_parse_image_url_as_dialog() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::Text("!![Alt](https://example.com/a.webp)".to_string()),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("!![Alt](https://example.com/a.webp)".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process(&events);
         assert!(!out
             .iter()
-            .any(|ev| matches!(ev, Event::StartNode(NodeKind::HtmlBlock))));
+            .any(|ev| matches!(ev, Event::StartNode(NodeKind::DefinitionList, _))));
     }
 
     #[test]
     fn still_parses_normal_dialog_lines() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::Text("Revan: \"Hi\"\nStevano: \"Hello\"".to_string()),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("Revan: \"Hi\"\nStevano: \"Hello\"".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process(&events);
         assert!(out
             .iter()
-            .any(|ev| matches!(ev, Event::StartNode(NodeKind::HtmlBlock))));
+            .any(|ev| matches!(ev, Event::StartNode(NodeKind::DefinitionList, _))));
+    }
+
+    #[test]
+    fn speakers_become_terms_and_lines_descriptions() {
+        let events = vec![
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("Revan: \"Hi\"".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+        ];
+
+        let out = process(&events);
+        assert_eq!(
+            out,
+            [
+                Event::StartNode(NodeKind::DefinitionList, None),
+                Event::StartNode(NodeKind::DefinitionTerm, None),
+                Event::Text("Revan".into(), None),
+                Event::EndNode(NodeKind::DefinitionTerm, None),
+                Event::StartNode(NodeKind::DefinitionDescription, None),
+                Event::StartNode(NodeKind::HtmlInline, None),
+                Event::Text("<q>Hi</q>".into(), None),
+                Event::EndNode(NodeKind::HtmlInline, None),
+                Event::EndNode(NodeKind::DefinitionDescription, None),
+                Event::EndNode(NodeKind::DefinitionList, None),
+            ]
+        );
     }
 }
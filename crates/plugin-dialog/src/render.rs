@@ -1,28 +1,9 @@
 use crate::markdown::render_inline_markdown;
 use crate::tokenize::{tokenize_content, DialogToken};
 
-pub fn render_dt(speaker: &str, class: Option<&str>) -> String {
-    let mut out = String::from("<dt");
-    if let Some(cls) = class {
-        out.push_str(" class=\"");
-        out.push_str(&escape_html(cls));
-        out.push('"');
-    }
-    out.push('>');
-    out.push_str(&escape_html(speaker));
-    out.push_str("</dt>");
-    out
-}
-
-pub fn render_dd(content: &str, class: Option<&str>) -> String {
-    let mut out = String::from("<dd");
-    if let Some(cls) = class {
-        out.push_str(" class=\"");
-        out.push_str(&escape_html(cls));
-        out.push('"');
-    }
-    out.push('>');
-
+/// Inner HTML of a speaker's line: quotes, stage notes and plain runs.
+pub fn render_dd(content: &str) -> String {
+    let mut out = String::new();
     let tokens = tokenize_content(content);
     let mut wrote_non_break = false;
     let mut last_was_break = false;
@@ -69,7 +50,6 @@ pub fn render_dd(content: &str, class: Option<&str>) -> String {
         }
     }
 
-    out.push_str("</dd>");
     out
 }
 
@@ -96,18 +76,3 @@ fn render_plain(text: &str) -> String {
     }
     format!("<p>{}</p>", inner)
 }
-
-fn escape_html(s: &str) -> String {
-    let mut out = String::new();
-    for ch in s.chars() {
-        match ch {
-            '&' => out.push_str("&amp;"),
-            '<' => out.push_str("&lt;"),
-            '>' => out.push_str("&gt;"),
-            '"' => out.push_str("&quot;"),
-            '\'' => out.push_str("&#39;"),
-            _ => out.push(ch),
-        }
-    }
-    out
-}
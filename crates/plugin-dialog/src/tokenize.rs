@@ -20,10 +20,7 @@ pub fn tokenize_content(content: &str) -> Vec<DialogToken> {
             continue;
         }
 
-        if i + 1 < bytes.len()
-            && ((bytes[i] == b'_' && bytes[i + 1] == b'(')
-                || (bytes[i] == b'*' && bytes[i + 1] == b'('))
-        {
+        if i + 1 < bytes.len() && (bytes[i] == b'_' || bytes[i] == b'*') && bytes[i + 1] == b'(' {
             let closer = if bytes[i] == b'_' { ")_" } else { ")*" };
             if let Some(pos) = content[i + 2..].find(closer) {
                 flush_plain(&mut out, &mut buf);
@@ -1,5 +1,9 @@
-use pendon_core::{extract_id, slugify, Event, NodeKind};
+use pendon_core::{
+    ensure_unique_with, extract_id, join_spans, slugify_with, AttrValue, Event, NodeKind, Plugin,
+    SlugStrategy, Span,
+};
 use serde::Serialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct PendonHeading {
@@ -10,6 +14,23 @@ pub struct PendonHeading {
     pub subheadings: Vec<PendonHeading>,
 }
 
+impl From<&PendonHeading> for AttrValue<'static> {
+    fn from(heading: &PendonHeading) -> Self {
+        let mut entries = vec![
+            ("id".into(), heading.id.clone().into()),
+            ("text".into(), heading.text.clone().into()),
+            ("level".into(), AttrValue::Int(heading.level as i64)),
+        ];
+        if !heading.subheadings.is_empty() {
+            entries.push((
+                "subheadings".into(),
+                AttrValue::List(heading.subheadings.iter().map(AttrValue::from).collect()),
+            ));
+        }
+        AttrValue::Map(entries)
+    }
+}
+
 #[derive(Debug, Clone)]
 struct HeadingCapture {
     text: String,
@@ -17,48 +38,87 @@ struct HeadingCapture {
     id: Option<String>,
 }
 
-pub fn process(events: &[Event]) -> Vec<Event> {
-    let headings = collect_headings(events);
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
+    process_with_options(events, ExtractHeadingOptions::default())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtractHeadingOptions {
+    /// How ids are derived for headings outside an id-carrying section;
+    /// match the `sectionize` setting so both agree.
+    pub slug: SlugStrategy,
+}
+
+pub fn process_with_options<'a>(
+    events: &[Event<'a>],
+    opts: ExtractHeadingOptions,
+) -> Vec<Event<'a>> {
+    let headings = collect_headings(events, opts.slug);
     if headings.is_empty() {
         return events.to_vec();
     }
 
-    let data = match serde_json::to_string(&headings) {
-        Ok(s) => s,
-        Err(_) => return events.to_vec(),
-    };
+    let data = AttrValue::List(headings.iter().map(AttrValue::from).collect());
+
+    // The injected node summarizes every heading, so it spans all of them
+    let span = events
+        .iter()
+        .filter(|ev| matches!(ev, Event::StartNode(NodeKind::Heading, _)))
+        .fold(None, |acc, ev| join_spans(acc, ev.span()));
 
-    inject_headings_node(events, &data)
+    inject_headings_node(events, data, span)
 }
 
-fn collect_headings(events: &[Event]) -> Vec<PendonHeading> {
+/// Registers [`process_with_options`] as the `extract-heading` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractHeadingPlugin {
+    pub options: ExtractHeadingOptions,
+}
+
+impl<H> Plugin<H> for ExtractHeadingPlugin {
+    fn name(&self) -> &str {
+        "extract-heading"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process_with_options(events, self.options)
+    }
+}
+
+fn collect_headings(events: &[Event], slug: SlugStrategy) -> Vec<PendonHeading> {
     let mut roots: Vec<PendonHeading> = Vec::new();
+    let mut used_ids: HashMap<String, usize> = HashMap::new();
     let mut path: Vec<usize> = Vec::new();
     let mut section_stack: Vec<Option<String>> = Vec::new();
     let mut idx = 0usize;
 
     while idx < events.len() {
         match &events[idx] {
-            Event::StartNode(NodeKind::Section) => {
+            Event::StartNode(NodeKind::Section, _) => {
                 section_stack.push(None);
                 idx += 1;
             }
-            Event::Attribute { name, value } if name == "id" && section_stack.last().is_some() => {
+            Event::Attribute { name, value, .. }
+                if name == "id" && section_stack.last().is_some() =>
+            {
                 if let Some(last) = section_stack.last_mut() {
-                    *last = Some(value.clone());
+                    *last = Some(value.to_string());
                 }
                 idx += 1;
             }
-            Event::EndNode(NodeKind::Section) => {
+            Event::EndNode(NodeKind::Section, _) => {
                 section_stack.pop();
                 idx += 1;
             }
-            Event::StartNode(NodeKind::Heading) => {
+            Event::StartNode(NodeKind::Heading, _) => {
                 let (capture, consumed) = consume_heading(events, idx);
                 let section_id = section_stack.last().and_then(|id| id.clone());
-                let id = section_id
-                    .or(capture.id)
-                    .unwrap_or_else(|| slugify(&capture.text));
+                let id = match section_id.or(capture.id) {
+                    Some(id) => id,
+                    None => {
+                        ensure_unique_with(slugify_with(&capture.text, slug), &mut used_ids, slug)
+                    }
+                };
                 let level = capture.level.max(1);
                 let node = PendonHeading {
                     id,
@@ -86,21 +146,21 @@ fn consume_heading(events: &[Event], start_idx: usize) -> (HeadingCapture, usize
 
     while idx < events.len() {
         match &events[idx] {
-            Event::Attribute { name, value } => {
+            Event::Attribute { name, value, .. } => {
                 if name == "level" {
-                    if let Ok(parsed) = value.parse::<usize>() {
+                    if let Ok(parsed) = value.to_string().parse::<usize>() {
                         level = parsed;
                     }
                 } else if name == "id" {
-                    heading_id = Some(value.clone());
+                    heading_id = Some(value.to_string());
                 }
                 idx += 1;
             }
-            Event::Text(t) => {
+            Event::Text(t, _) => {
                 text.push_str(t);
                 idx += 1;
             }
-            Event::EndNode(NodeKind::Heading) => {
+            Event::EndNode(NodeKind::Heading, _) => {
                 idx += 1;
                 break;
             }
@@ -166,7 +226,11 @@ fn resolve_children_mut<'a>(
     cursor
 }
 
-fn inject_headings_node(events: &[Event], data: &str) -> Vec<Event> {
+fn inject_headings_node<'a>(
+    events: &[Event<'a>],
+    data: AttrValue<'static>,
+    span: Option<Span>,
+) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len() + 4);
     let mut in_document = false;
     let mut pending_insert = false;
@@ -175,27 +239,27 @@ fn inject_headings_node(events: &[Event], data: &str) -> Vec<Event> {
 
     for ev in events.iter() {
         match ev {
-            Event::StartNode(NodeKind::Document) => {
+            Event::StartNode(NodeKind::Document, _) => {
                 in_document = true;
                 pending_insert = true;
                 out.push(ev.clone());
             }
-            Event::StartNode(NodeKind::Frontmatter) if in_document => {
+            Event::StartNode(NodeKind::Frontmatter, _) if in_document => {
                 frontmatter_depth += 1;
                 out.push(ev.clone());
             }
-            Event::EndNode(NodeKind::Frontmatter) if in_document => {
+            Event::EndNode(NodeKind::Frontmatter, _) if in_document => {
                 out.push(ev.clone());
                 frontmatter_depth = frontmatter_depth.saturating_sub(1);
                 if frontmatter_depth == 0 && pending_insert && !inserted {
-                    push_headings_block(&mut out, data);
+                    push_headings_block(&mut out, data.clone(), span);
                     inserted = true;
                     pending_insert = false;
                 }
             }
-            Event::EndNode(NodeKind::Document) if in_document => {
+            Event::EndNode(NodeKind::Document, _) if in_document => {
                 if pending_insert && !inserted {
-                    push_headings_block(&mut out, data);
+                    push_headings_block(&mut out, data.clone(), span);
                     inserted = true;
                     pending_insert = false;
                 }
@@ -204,7 +268,7 @@ fn inject_headings_node(events: &[Event], data: &str) -> Vec<Event> {
             }
             _ => {
                 if in_document && pending_insert && frontmatter_depth == 0 && !inserted {
-                    push_headings_block(&mut out, data);
+                    push_headings_block(&mut out, data.clone(), span);
                     inserted = true;
                     pending_insert = false;
                 }
@@ -214,20 +278,21 @@ fn inject_headings_node(events: &[Event], data: &str) -> Vec<Event> {
     }
 
     if !inserted && in_document {
-        push_headings_block(&mut out, data);
+        push_headings_block(&mut out, data.clone(), span);
     }
 
     out
 }
 
-fn push_headings_block(out: &mut Vec<Event>, data: &str) {
+fn push_headings_block(out: &mut Vec<Event>, data: AttrValue<'static>, span: Option<Span>) {
     let kind = headings_node_kind();
-    out.push(Event::StartNode(kind.clone()));
+    out.push(Event::StartNode(kind.clone(), span));
     out.push(Event::Attribute {
-        name: "data".to_string(),
-        value: data.to_string(),
+        name: "data".into(),
+        value: data,
+        span,
     });
-    out.push(Event::EndNode(kind));
+    out.push(Event::EndNode(kind, span));
 }
 
 fn headings_node_kind() -> NodeKind {
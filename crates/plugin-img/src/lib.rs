@@ -1,18 +1,23 @@
-use pendon_core::{parse, Event, NodeKind, Options};
+use pendon_core::{events_span, leading_attrs, parse, Event, NodeKind, Options, Plugin};
 use pendon_plugin_markdown::process as process_markdown;
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
 
     while i < events.len() {
-        if matches!(events.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(
+            events.get(i),
+            Some(Event::StartNode(NodeKind::Paragraph, _))
+        ) {
             if let Some(end) = find_matching_end(events, i, NodeKind::Paragraph) {
                 let block = &events[i + 1..end];
                 if let Some(html) = maybe_render_advanced_image(block) {
-                    out.push(Event::StartNode(NodeKind::HtmlBlock));
-                    out.push(Event::Text(html));
-                    out.push(Event::EndNode(NodeKind::HtmlBlock));
+                    // The rendered block replaces the whole paragraph
+                    let span = events_span(&events[i..=end]);
+                    out.push(Event::StartNode(NodeKind::HtmlBlock, span));
+                    out.push(Event::Text(html.into(), span));
+                    out.push(Event::EndNode(NodeKind::HtmlBlock, span));
                     i = end + 1;
                     continue;
                 }
@@ -29,6 +34,20 @@ pub fn process(events: &[Event]) -> Vec<Event> {
     out
 }
 
+/// Registers [`process`] as the `img` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImgPlugin;
+
+impl<H> Plugin<H> for ImgPlugin {
+    fn name(&self) -> &str {
+        "img"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+}
+
 fn maybe_render_advanced_image(block_events: &[Event]) -> Option<String> {
     let raw = collect_text_only(block_events)?;
     let line = raw.trim();
@@ -130,7 +149,7 @@ fn render_decorated_image_syntax(line: &str) -> Option<String> {
     push_image_marker_attrs(&mut out, &core.marker);
     out.push_str(" alt=\"");
     escape_html(&core.alt, &mut out);
-    out.push_str("\"");
+    out.push('"');
     push_common_attrs(&mut out, &attrs);
     out.push_str(" src=\"");
     escape_html(&core.src, &mut out);
@@ -282,6 +301,24 @@ fn parse_optional_attrs(input: &str) -> (AttrSpec, &str, bool) {
     };
 
     let after_class = after_class.trim_start();
+    // A Pandoc-style `{#id .class key="value"}` block stands on its own
+    if class_block.is_none() {
+        if let Some((block, len)) = leading_attrs(after_class) {
+            let mut spec = AttrSpec {
+                id: block.id,
+                classes: block.classes,
+                ..AttrSpec::default()
+            };
+            for (key, value) in block.pairs {
+                if key.starts_with("--") {
+                    spec.styles.push((key, value));
+                } else {
+                    spec.data.push((key, value));
+                }
+            }
+            return (spec, &after_class[len..], true);
+        }
+    }
     if !after_class.starts_with('{') {
         return (AttrSpec::default(), s, false);
     }
@@ -381,13 +418,13 @@ fn push_common_attrs(out: &mut String, attrs: &AttrSpec) {
     if let Some(id) = attrs.id.as_deref() {
         out.push_str(" id=\"");
         escape_html(id, out);
-        out.push_str("\"");
+        out.push('"');
     }
 
     if !attrs.classes.is_empty() {
         out.push_str(" class=\"");
         escape_html(&attrs.classes.join(" "), out);
-        out.push_str("\"");
+        out.push('"');
     }
 
     for (k, v) in &attrs.data {
@@ -396,7 +433,7 @@ fn push_common_attrs(out: &mut String, attrs: &AttrSpec) {
         escape_html(k, out);
         out.push_str("=\"");
         escape_html(v, out);
-        out.push_str("\"");
+        out.push('"');
     }
 
     if !attrs.styles.is_empty() {
@@ -407,7 +444,7 @@ fn push_common_attrs(out: &mut String, attrs: &AttrSpec) {
             escape_html(v, out);
             out.push(';');
         }
-        out.push_str("\"");
+        out.push('"');
     }
 }
 
@@ -415,12 +452,12 @@ fn push_image_marker_attrs(out: &mut String, marker: &ImageMarker) {
     if let Some(width) = marker.width {
         out.push_str(" width=\"");
         out.push_str(&width.to_string());
-        out.push_str("\"");
+        out.push('"');
     }
     if let Some(height) = marker.height {
         out.push_str(" height=\"");
         out.push_str(&height.to_string());
-        out.push_str("\"");
+        out.push('"');
     }
     if marker.async_decoding {
         out.push_str(" decoding=\"async\"");
@@ -450,7 +487,7 @@ fn collect_text_only(events: &[Event]) -> Option<String> {
     let mut out = String::new();
     for ev in events {
         match ev {
-            Event::Text(t) => out.push_str(t),
+            Event::Text(t, _) => out.push_str(t),
             _ => return None,
         }
     }
@@ -461,8 +498,8 @@ fn find_matching_end(events: &[Event], start_idx: usize, kind: NodeKind) -> Opti
     let mut depth = 0isize;
     for (idx, ev) in events.iter().enumerate().skip(start_idx) {
         match ev {
-            Event::StartNode(k) if *k == kind => depth += 1,
-            Event::EndNode(k) if *k == kind => {
+            Event::StartNode(k, _) if *k == kind => depth += 1,
+            Event::EndNode(k, _) if *k == kind => {
                 depth -= 1;
                 if depth == 0 {
                     return Some(idx);
@@ -491,13 +528,13 @@ fn escape_html(s: &str, out: &mut String) {
 mod tests {
     use super::*;
 
-    fn paragraph_events(text: &str) -> Vec<Event> {
+    fn paragraph_events(text: &str) -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::Text(text.to_string()),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text(text.to_string().into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
@@ -508,7 +545,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<figure") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<figure") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -529,7 +566,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<img ") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<img ") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -547,7 +584,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<img ") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<img ") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -557,6 +594,25 @@ mod tests {
         assert!(!html.contains("figcaption"));
     }
 
+    #[test]
+    fn renders_pandoc_style_attribute_block() {
+        let events = paragraph_events(
+            "!![Alt](https://x.test/a.webp){#hero .x .y foo=\"bar\" --r=5deg} Caption",
+        );
+        let out = process(&events);
+        let html = out
+            .iter()
+            .find_map(|ev| match ev {
+                Event::Text(t, _) if t.contains("<figure") => Some(t.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        assert!(html
+            .contains("<figure id=\"hero\" class=\"x y\" data:foo=\"bar\" style=\"--r:5deg;\">"));
+        assert!(html.contains("<figcaption>Caption</figcaption>"));
+    }
+
     #[test]
     fn renders_figure_attributes_without_class_block() {
         let events =
@@ -565,7 +621,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<figure") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<figure") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -601,7 +657,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<img ") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<img ") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -619,7 +675,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<figure") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<figure") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -636,7 +692,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<p") && t.contains("<img") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<p") && t.contains("<img") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -653,7 +709,7 @@ mod tests {
         let html = out
             .iter()
             .find_map(|ev| match ev {
-                Event::Text(t) if t.contains("<div") && t.contains("<img") => Some(t.clone()),
+                Event::Text(t, _) if t.contains("<div") && t.contains("<img") => Some(t.clone()),
                 _ => None,
             })
             .unwrap();
@@ -1,29 +1,46 @@
-use pendon_core::{Event, NodeKind};
+use pendon_core::{aborted_document, codes, join_spans, Diagnostic, Event, NodeKind, Plugin, Span};
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+/// Pieces of a merged text run: char offset within the run, the original
+/// text and its span. Used to map char ranges back to source spans.
+type SpanMap = Vec<(usize, String, Option<Span>)>;
+
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
+    process_with_options(events, LatexOptions::default())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct LatexOptions {
+    /// Longest formula (in characters) handed to KaTeX; a longer one aborts
+    /// processing with an error diagnostic.
+    pub max_formula_len: Option<usize>,
+}
+
+pub fn process_with_options<'a>(events: &[Event<'a>], opts: LatexOptions) -> Vec<Event<'a>> {
     let merged = merge_adjacent_text(events.to_vec());
 
     let mut out = Vec::with_capacity(merged.len());
     let mut exclude_depth: usize = 0;
 
-    for ev in merged {
+    for (ev, map) in merged {
         match &ev {
-            Event::StartNode(NodeKind::CodeFence)
-            | Event::StartNode(NodeKind::InlineCode)
-            | Event::StartNode(NodeKind::HtmlBlock)
-            | Event::StartNode(NodeKind::HtmlInline) => {
+            Event::StartNode(NodeKind::CodeFence, _)
+            | Event::StartNode(NodeKind::InlineCode, _)
+            | Event::StartNode(NodeKind::HtmlBlock, _)
+            | Event::StartNode(NodeKind::HtmlInline, _) => {
                 exclude_depth += 1;
                 out.push(ev);
             }
-            Event::EndNode(NodeKind::CodeFence)
-            | Event::EndNode(NodeKind::InlineCode)
-            | Event::EndNode(NodeKind::HtmlBlock)
-            | Event::EndNode(NodeKind::HtmlInline) => {
+            Event::EndNode(NodeKind::CodeFence, _)
+            | Event::EndNode(NodeKind::InlineCode, _)
+            | Event::EndNode(NodeKind::HtmlBlock, _)
+            | Event::EndNode(NodeKind::HtmlInline, _) => {
                 exclude_depth = exclude_depth.saturating_sub(1);
                 out.push(ev);
             }
-            Event::Text(text) if exclude_depth == 0 => {
-                process_text(text, &mut out);
+            Event::Text(text, _) if exclude_depth == 0 => {
+                if let Err(diag) = process_text(text, &map, &mut out, opts.max_formula_len) {
+                    return aborted_document(events, diag.with_plugin("latex"));
+                }
             }
             _ => {
                 out.push(ev);
@@ -34,32 +51,87 @@ pub fn process(events: &[Event]) -> Vec<Event> {
     out
 }
 
-fn merge_adjacent_text(events: Vec<Event>) -> Vec<Event> {
-    let mut out: Vec<Event> = Vec::with_capacity(events.len());
+/// Registers [`process_with_options`] as the `latex` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatexPlugin {
+    pub options: LatexOptions,
+}
+
+impl<H> Plugin<H> for LatexPlugin {
+    fn name(&self) -> &str {
+        "latex"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process_with_options(events, self.options)
+    }
+}
+
+fn merge_adjacent_text<'a>(events: Vec<Event<'a>>) -> Vec<(Event<'a>, SpanMap)> {
+    let mut out: Vec<(Event, SpanMap)> = Vec::with_capacity(events.len());
     for ev in events {
         match ev {
-            Event::Text(t) => {
-                if let Some(Event::Text(prev)) = out.last_mut() {
-                    prev.push_str(&t);
+            Event::Text(t, span) => {
+                if let Some((Event::Text(prev, prev_span), map)) = out.last_mut() {
+                    map.push((prev.chars().count(), t.to_string(), span));
+                    prev.to_mut().push_str(&t);
+                    *prev_span = join_spans(*prev_span, span);
                 } else {
-                    out.push(Event::Text(t));
+                    let map = vec![(0, t.to_string(), span)];
+                    out.push((Event::Text(t, span), map));
                 }
             }
-            other => out.push(other),
+            other => out.push((other, Vec::new())),
         }
     }
     out
 }
 
-fn process_text(text: &str, out: &mut Vec<Event>) {
+/// Source span of the chars `from..to` of a merged text run.
+fn range_span(map: &SpanMap, from: usize, to: usize) -> Option<Span> {
+    let locate = |at: usize, is_end: bool| {
+        let (start, piece, span) = map
+            .iter()
+            .rev()
+            .find(|(start, _, _)| if is_end { *start < at } else { *start <= at })
+            .or_else(|| map.first())?;
+        let skip: String = piece.chars().take(at.saturating_sub(*start)).collect();
+        span.map(|sp| sp.start.advance(&skip))
+    };
+    let start = locate(from, false)?;
+    let end = locate(to, true)?;
+    Some(Span::new(start, end.max(start)))
+}
+
+fn process_text(
+    text: &str,
+    map: &SpanMap,
+    out: &mut Vec<Event>,
+    max_formula_len: Option<usize>,
+) -> Result<(), Box<Diagnostic>> {
+    let check_len = |len: usize, span: Option<Span>| match max_formula_len {
+        Some(max) if len > max => Err(Box::new(
+            Diagnostic::error(
+                codes::FORMULA_TOO_LONG,
+                format!("formula length {} exceeds limit {}", len, max),
+            )
+            .with_span(span)
+            .with_help("shorten the formula or raise `max_formula_len`"),
+        )),
+        _ => Ok(()),
+    };
     let mut cursor = 0;
     let chars: Vec<char> = text.chars().collect();
     let len = chars.len();
     let mut normal_text = String::new();
+    let mut normal_start = 0usize;
 
-    let flush_normal = |normal: &mut String, out: &mut Vec<Event>| {
+    let flush_normal = |normal: &mut String, from: usize, to: usize, out: &mut Vec<Event>| {
         if !normal.is_empty() {
-            out.push(Event::Text(normal.clone()));
+            out.push(Event::Text(
+                normal.clone().into(),
+                range_span(map, from, to),
+            ));
             normal.clear();
         }
     };
@@ -84,7 +156,10 @@ fn process_text(text: &str, out: &mut Vec<Event>) {
             }
 
             if found_close {
-                flush_normal(&mut normal_text, out);
+                flush_normal(&mut normal_text, normal_start, start, out);
+                normal_start = end + 2;
+                let span = range_span(map, start, end + 2);
+                check_len(end - start - 2, span)?;
                 let formula: String = chars[start + 2..end].iter().collect();
 
                 let opts = katex::Opts::builder()
@@ -97,17 +172,21 @@ fn process_text(text: &str, out: &mut Vec<Event>) {
                     Ok(html) => {
                         let escaped_html = html.replace('\\', "\\\\").replace('`', "\\`");
                         let wrapper = format!("<span class=\"latex latex-block\" style=\"display: block;\" innerHTML={{`{}`}}></span>", escaped_html);
-                        out.push(Event::StartNode(NodeKind::HtmlBlock));
-                        out.push(Event::Text(wrapper));
-                        out.push(Event::EndNode(NodeKind::HtmlBlock));
+                        out.push(Event::StartNode(NodeKind::HtmlBlock, span));
+                        out.push(Event::Text(wrapper.into(), span));
+                        out.push(Event::EndNode(NodeKind::HtmlBlock, span));
                     }
                     Err(err) => {
-                        out.push(Event::StartNode(NodeKind::HtmlBlock));
-                        out.push(Event::Text(format!(
-                            "<span class=\"latex-error\" style=\"display: block;\">{}</span>",
-                            err
-                        )));
-                        out.push(Event::EndNode(NodeKind::HtmlBlock));
+                        out.push(Event::StartNode(NodeKind::HtmlBlock, span));
+                        out.push(Event::Text(
+                            format!(
+                                "<span class=\"latex-error\" style=\"display: block;\">{}</span>",
+                                err
+                            )
+                            .into(),
+                            span,
+                        ));
+                        out.push(Event::EndNode(NodeKind::HtmlBlock, span));
                     }
                 }
                 cursor = end + 2;
@@ -122,18 +201,23 @@ fn process_text(text: &str, out: &mut Vec<Event>) {
 
             if end < len && chars[end] != ' ' && chars[end] != '\n' {
                 while end < len {
-                    if chars[end] == '$' {
-                        if end > start + 1 && chars[end - 1] != ' ' && chars[end - 1] != '\n' {
-                            found_close = true;
-                            break;
-                        }
+                    if chars[end] == '$'
+                        && end > start + 1
+                        && chars[end - 1] != ' '
+                        && chars[end - 1] != '\n'
+                    {
+                        found_close = true;
+                        break;
                     }
                     end += 1;
                 }
             }
 
             if found_close {
-                flush_normal(&mut normal_text, out);
+                flush_normal(&mut normal_text, normal_start, start, out);
+                normal_start = end + 1;
+                let span = range_span(map, start, end + 1);
+                check_len(end - start - 1, span)?;
                 let formula: String = chars[start + 1..end].iter().collect();
 
                 let opts = katex::Opts::builder()
@@ -149,17 +233,17 @@ fn process_text(text: &str, out: &mut Vec<Event>) {
                             "<span class=\"latex latex-inline\" innerHTML={{`{}`}}></span>",
                             escaped_html
                         );
-                        out.push(Event::StartNode(NodeKind::HtmlInline));
-                        out.push(Event::Text(wrapper));
-                        out.push(Event::EndNode(NodeKind::HtmlInline));
+                        out.push(Event::StartNode(NodeKind::HtmlInline, span));
+                        out.push(Event::Text(wrapper.into(), span));
+                        out.push(Event::EndNode(NodeKind::HtmlInline, span));
                     }
                     Err(err) => {
-                        out.push(Event::StartNode(NodeKind::HtmlInline));
-                        out.push(Event::Text(format!(
-                            "<span class=\"latex-error\">{}</span>",
-                            err
-                        )));
-                        out.push(Event::EndNode(NodeKind::HtmlInline));
+                        out.push(Event::StartNode(NodeKind::HtmlInline, span));
+                        out.push(Event::Text(
+                            format!("<span class=\"latex-error\">{}</span>", err).into(),
+                            span,
+                        ));
+                        out.push(Event::EndNode(NodeKind::HtmlInline, span));
                     }
                 }
                 cursor = end + 1;
@@ -171,7 +255,8 @@ fn process_text(text: &str, out: &mut Vec<Event>) {
         cursor += 1;
     }
 
-    flush_normal(&mut normal_text, out);
+    flush_normal(&mut normal_text, normal_start, len, out);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -180,37 +265,56 @@ mod tests {
 
     #[test]
     fn test_inline_math() {
-        let events = vec![Event::Text("Einstein: $E = mc^2$.".to_string())];
+        let events = vec![Event::Text("Einstein: $E = mc^2$.".into(), None)];
         let res = process(&events);
         assert_eq!(res.len(), 5);
-        assert_eq!(res[0], Event::Text("Einstein: ".to_string()));
-        assert_eq!(res[1], Event::StartNode(NodeKind::HtmlInline));
-        assert!(matches!(&res[2], Event::Text(h) if h.contains("innerHTML={`")));
-        assert_eq!(res[3], Event::EndNode(NodeKind::HtmlInline));
-        assert_eq!(res[4], Event::Text(".".to_string()));
+        assert_eq!(res[0], Event::Text("Einstein: ".into(), None));
+        assert_eq!(res[1], Event::StartNode(NodeKind::HtmlInline, None));
+        assert!(matches!(&res[2], Event::Text(h, _) if h.contains("innerHTML={`")));
+        assert_eq!(res[3], Event::EndNode(NodeKind::HtmlInline, None));
+        assert_eq!(res[4], Event::Text(".".into(), None));
     }
 
     #[test]
     fn test_block_math() {
         let events = vec![
-            Event::Text("$$".to_string()),
-            Event::Text("\n".to_string()),
-            Event::Text("x = y".to_string()),
-            Event::Text("\n".to_string()),
-            Event::Text("$$".to_string()),
+            Event::Text("$$".into(), None),
+            Event::Text("\n".into(), None),
+            Event::Text("x = y".into(), None),
+            Event::Text("\n".into(), None),
+            Event::Text("$$".into(), None),
         ];
         let res = process(&events);
         assert_eq!(res.len(), 3);
-        assert_eq!(res[0], Event::StartNode(NodeKind::HtmlBlock));
-        assert!(matches!(&res[1], Event::Text(h) if h.contains("innerHTML={`")));
-        assert_eq!(res[2], Event::EndNode(NodeKind::HtmlBlock));
+        assert_eq!(res[0], Event::StartNode(NodeKind::HtmlBlock, None));
+        assert!(matches!(&res[1], Event::Text(h, _) if h.contains("innerHTML={`")));
+        assert_eq!(res[2], Event::EndNode(NodeKind::HtmlBlock, None));
     }
 
     #[test]
     fn test_escaped_dollar() {
-        let events = vec![Event::Text("I have \\$5 and \\$10.".to_string())];
+        let events = vec![Event::Text("I have \\$5 and \\$10.".into(), None)];
         let res = process(&events);
         assert_eq!(res.len(), 1);
-        assert_eq!(res[0], Event::Text("I have $5 and $10.".to_string()));
+        assert_eq!(res[0], Event::Text("I have $5 and $10.".into(), None));
+    }
+
+    #[test]
+    fn formula_over_limit_aborts_before_katex() {
+        let events = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::Text("short $x$ then $a + b + c + d$".into(), None),
+            Event::EndNode(NodeKind::Document, None),
+        ];
+        let opts = LatexOptions {
+            max_formula_len: Some(5),
+        };
+        let res = process_with_options(&events, opts);
+        assert_eq!(res.len(), 3);
+        assert!(matches!(
+            &res[1],
+            Event::Diagnostic(d) if d.code == codes::FORMULA_TOO_LONG
+                && d.plugin.as_deref() == Some("latex")
+        ));
     }
 }
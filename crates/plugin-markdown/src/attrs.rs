@@ -0,0 +1,151 @@
+use pendon_core::{
+    is_safe_attr, join_spans, leading_attrs, trailing_attrs, AttrBlock, Event, NodeKind, Span,
+};
+
+/// Push the `id`, `class` and other attributes of `block` onto the node
+/// being emitted, leaving out any `is_safe_attr` rejects.
+pub fn push(out: &mut Vec<Event>, block: &AttrBlock, span: Option<Span>) {
+    let attributes = block.attributes().into_iter();
+    for (name, value) in attributes.filter(|(name, _)| is_safe_attr(name)) {
+        out.push(Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span,
+        });
+    }
+}
+
+/// `{...}` attribute block at `at`, right after an inline link or image:
+/// its attributes and the index past it.
+pub fn inline_at(chars: &[char], at: usize) -> Option<(AttrBlock, usize)> {
+    if chars.get(at) != Some(&'{') {
+        return None;
+    }
+    let rest: String = chars[at..].iter().collect();
+    let (block, len) = leading_attrs(&rest)?;
+    Some((block, at + rest[..len].chars().count()))
+}
+
+/// Move the attribute block closing a heading, a paragraph or a fenced code
+/// info string onto its node.
+pub fn resolve(events: &mut Vec<Event>) {
+    let mut idx = 0;
+    while idx < events.len() {
+        match &events[idx] {
+            Event::EndNode(kind @ (NodeKind::Heading | NodeKind::Paragraph), _) => {
+                let kind = kind.clone();
+                idx = trailing(events, idx, kind);
+            }
+            Event::Attribute { name, value, span }
+                if name == "lang"
+                    && idx > 0
+                    && matches!(events[idx - 1], Event::StartNode(NodeKind::CodeFence, _)) =>
+            {
+                let span = *span;
+                let info = value.to_string();
+                if let Some((lang, block)) = trailing_attrs(&info) {
+                    let mut attrs = Vec::new();
+                    if !lang.is_empty() {
+                        attrs.push(Event::Attribute {
+                            name: "lang".into(),
+                            value: lang.to_string().into(),
+                            span,
+                        });
+                    }
+                    push(&mut attrs, &block, span);
+                    let added = attrs.len();
+                    events.splice(idx..idx + 1, attrs);
+                    idx += added;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+}
+
+// Strip an attribute block from the text run ending the node closed at
+// `end` and attach it to the node; returns the new index of the end event
+fn trailing(events: &mut Vec<Event>, end: usize, kind: NodeKind) -> usize {
+    let from = end
+        - events[..end]
+            .iter()
+            .rev()
+            .take_while(|ev| matches!(ev, Event::Text(..)))
+            .count();
+    let text: String = events[from..end]
+        .iter()
+        .filter_map(|ev| match ev {
+            Event::Text(t, _) => Some(t.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let Some((before, block)) = trailing_attrs(&text) else {
+        return end;
+    };
+    // A paragraph holding nothing but braces keeps them as text
+    let bare = before.is_empty()
+        && matches!(
+            events[from - 1],
+            Event::StartNode(..) | Event::Attribute { .. }
+        );
+    if kind == NodeKind::Paragraph && bare {
+        return end;
+    }
+
+    let keep = before.len();
+    let mut offset = 0;
+    let mut tail = Vec::new();
+    let mut block_span = None;
+    for ev in events.drain(from..end) {
+        let Event::Text(t, span) = ev else { continue };
+        let len = t.len();
+        if offset + len <= keep {
+            tail.push(Event::Text(t, span));
+        } else if offset < keep {
+            let cut = keep - offset;
+            tail.push(Event::Text(
+                t[..cut].to_string().into(),
+                span.map(|sp| sp.slice(&t, 0..cut)),
+            ));
+            block_span = join_spans(block_span, span.map(|sp| sp.slice(&t, cut..len)));
+        } else {
+            block_span = join_spans(block_span, span);
+        }
+        offset += len;
+    }
+    let end = from + tail.len();
+    events.splice(from..from, tail);
+
+    let mut attrs = Vec::new();
+    push(&mut attrs, &block, block_span);
+    let at = attrs_end(events, start_of(events, end));
+    let added = attrs.len();
+    events.splice(at..at, attrs);
+    end + added
+}
+
+// Index of the start event matching the end event at `end`
+fn start_of(events: &[Event], end: usize) -> usize {
+    let mut depth = 0usize;
+    for idx in (0..end).rev() {
+        match events[idx] {
+            Event::EndNode(..) => depth += 1,
+            Event::StartNode(..) if depth == 0 => return idx,
+            Event::StartNode(..) => depth -= 1,
+            _ => {}
+        }
+    }
+    0
+}
+
+// Index past the attributes following the start event at `start`
+fn attrs_end(events: &[Event], start: usize) -> usize {
+    start
+        + 1
+        + events[start + 1..]
+            .iter()
+            .take_while(|ev| matches!(ev, Event::Attribute { .. }))
+            .count()
+}
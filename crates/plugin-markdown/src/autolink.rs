@@ -0,0 +1,210 @@
+use pendon_core::{join_spans, Event, NodeKind, Span};
+
+/// Pieces of a merged text run: the char range each covers within the run
+/// and its span.
+type SpanMap = Vec<(usize, usize, Option<Span>)>;
+
+/// `<scheme:target>` or `<user@host>` at `start`: the href, the link text
+/// and the index just past `>`.
+pub fn autolink_at(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    if chars.get(start) != Some(&'<') {
+        return None;
+    }
+    let close = start + 1 + chars[start + 1..].iter().position(|c| *c == '>')?;
+    let inner: String = chars[start + 1..close].iter().collect();
+    if is_uri(&inner) {
+        return Some((inner.clone(), inner, close + 1));
+    }
+    if is_email(&inner) {
+        return Some((format!("mailto:{inner}"), inner, close + 1));
+    }
+    None
+}
+
+// Scheme of 2-32 characters, then anything but spaces and angle brackets.
+// Schemes that run script when followed are not linked.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+        && (2..=32).contains(&scheme.len())
+        && !["javascript", "vbscript", "data"]
+            .iter()
+            .any(|unsafe_scheme| scheme.eq_ignore_ascii_case(unsafe_scheme))
+        && !rest.contains(|c: char| c.is_whitespace() || c.is_control() || c == '<')
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(c));
+    let label_ok = |label: &str| {
+        (1..=63).contains(&label.len())
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    local_ok && domain.split('.').all(label_ok)
+}
+
+/// Turn bare `http://`, `https://` and `www.` URLs in text into `Link`
+/// nodes (GFM extended autolinks). Text in code, HTML and existing links
+/// and images is left alone.
+pub fn linkify(events: Vec<Event<'_>>) -> Vec<Event<'_>> {
+    let skipped = |kind: &NodeKind| {
+        matches!(
+            kind,
+            NodeKind::CodeFence
+                | NodeKind::InlineCode
+                | NodeKind::HtmlBlock
+                | NodeKind::HtmlInline
+                | NodeKind::Link
+                | NodeKind::Image
+        )
+    };
+    let mut out = Vec::with_capacity(events.len());
+    let mut run = Vec::new();
+    let mut exclude_depth: usize = 0;
+    for ev in events {
+        match &ev {
+            Event::Text(..) if exclude_depth == 0 => {
+                run.push(ev);
+                continue;
+            }
+            Event::StartNode(kind, _) if skipped(kind) => exclude_depth += 1,
+            Event::EndNode(kind, _) if skipped(kind) => {
+                exclude_depth = exclude_depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+        flush_run(&mut run, &mut out);
+        out.push(ev);
+    }
+    flush_run(&mut run, &mut out);
+    out
+}
+
+// Emit a run of adjacent text events, split around the URLs it contains;
+// runs without one are kept as they are
+fn flush_run<'a>(run: &mut Vec<Event<'a>>, out: &mut Vec<Event<'a>>) {
+    let mut text = String::new();
+    let mut map: SpanMap = Vec::with_capacity(run.len());
+    let mut offset = 0;
+    for ev in run.iter() {
+        if let Event::Text(t, span) = ev {
+            let end = offset + t.chars().count();
+            map.push((offset, end, *span));
+            text.push_str(t);
+            offset = end;
+        }
+    }
+    if !text.contains("http") && !text.contains("www.") {
+        out.append(run);
+        return;
+    }
+    run.clear();
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut plain_from = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let at_boundary =
+            i == 0 || matches!(chars[i - 1], c if c.is_whitespace() || "*_~(".contains(c));
+        let found = if at_boundary {
+            literal_at(&chars, i)
+        } else {
+            None
+        };
+        let Some((href, end)) = found else {
+            i += 1;
+            continue;
+        };
+        if plain_from < i {
+            let plain: String = chars[plain_from..i].iter().collect();
+            out.push(Event::Text(plain.into(), range_span(&map, plain_from, i)));
+        }
+        let span = range_span(&map, i, end);
+        let label: String = chars[i..end].iter().collect();
+        out.push(Event::StartNode(NodeKind::Link, span));
+        out.push(Event::Attribute {
+            name: "href".into(),
+            value: href.into(),
+            span,
+        });
+        out.push(Event::Text(label.into(), span));
+        out.push(Event::EndNode(NodeKind::Link, span));
+        plain_from = end;
+        i = end;
+    }
+    if plain_from < chars.len() {
+        let plain: String = chars[plain_from..].iter().collect();
+        out.push(Event::Text(
+            plain.into(),
+            range_span(&map, plain_from, chars.len()),
+        ));
+    }
+}
+
+// A bare URL at `start`: the href and the index past it. Trailing
+// punctuation and unbalanced closing parentheses stay outside the link.
+fn literal_at(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let rest: String = chars[start..].iter().take(8).collect();
+    let prefix = ["https://", "http://", "www."]
+        .into_iter()
+        .find(|p| rest.starts_with(p))?;
+    let host_from = start + prefix.chars().count();
+    let host_len = chars[host_from..]
+        .iter()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .count();
+    let host: String = chars[host_from..host_from + host_len].iter().collect();
+    if host.trim_end_matches('.').is_empty() {
+        return None;
+    }
+    let mut end = host_from
+        + chars[host_from..]
+            .iter()
+            .take_while(|c| !c.is_whitespace() && **c != '<')
+            .count();
+    while end > host_from {
+        match chars[end - 1] {
+            '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '\'' | '"' => end -= 1,
+            ')' => {
+                let opens = chars[start..end].iter().filter(|c| **c == '(').count();
+                let closes = chars[start..end].iter().filter(|c| **c == ')').count();
+                if closes <= opens {
+                    break;
+                }
+                end -= 1;
+            }
+            _ => break,
+        }
+    }
+    if end <= host_from {
+        return None;
+    }
+    let url: String = chars[start..end].iter().collect();
+    let href = if prefix == "www." {
+        format!("http://{url}")
+    } else {
+        url
+    };
+    Some((href, end))
+}
+
+/// Span covering the pieces of a merged text run that hold chars `from..to`.
+fn range_span(map: &SpanMap, from: usize, to: usize) -> Option<Span> {
+    let first = map.partition_point(|(_, end, _)| *end <= from);
+    map[first..]
+        .iter()
+        .take_while(|(start, _, _)| *start < to)
+        .map(|(_, _, span)| *span)
+        .fold(None, join_spans)
+}
@@ -0,0 +1,60 @@
+use pendon_core::{AttrValue, Event, Span};
+
+/// `[!KIND]` line opening a callout, with an optional fold sign (`-`
+/// folded, `+` open) and title, as in `[!info]- Details`.
+pub struct Marker {
+    pub kind: String,
+    pub fold: Option<char>,
+    pub title: String,
+}
+
+pub fn marker(line: &str) -> Option<Marker> {
+    let rest = line.trim_start().strip_prefix("[!")?;
+    let close = rest.find(']')?;
+    let kind = &rest[..close];
+    if kind.is_empty()
+        || !kind
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    {
+        return None;
+    }
+    let mut rest = &rest[close + 1..];
+    let fold = match rest.chars().next() {
+        Some(sign @ ('-' | '+')) => {
+            rest = &rest[1..];
+            Some(sign)
+        }
+        _ => None,
+    };
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(Marker {
+        kind: kind.to_ascii_lowercase(),
+        fold,
+        title: rest.trim().to_string(),
+    })
+}
+
+/// Attributes of the `Callout` just opened at the end of `out`: the
+/// marker's `kind`, `title`, `foldable` and `open`.
+pub fn push_attrs(out: &mut Vec<Event>, marker: &Marker, span: Option<Span>) {
+    let mut attr = |name: &'static str, value: AttrValue<'static>| {
+        out.push(Event::Attribute {
+            name: name.into(),
+            value,
+            span,
+        });
+    };
+    attr("kind", marker.kind.clone().into());
+    if !marker.title.is_empty() {
+        attr("title", marker.title.clone().into());
+    }
+    if let Some(sign) = marker.fold {
+        attr("foldable", true.into());
+        if sign == '+' {
+            attr("open", true.into());
+        }
+    }
+}
@@ -1,19 +1,27 @@
-use pendon_core::{Event, NodeKind};
+use pendon_core::{propagate_node_spans, AttrValue, Event, NodeKind, Span};
 
+use crate::attrs;
+use crate::autolink;
+use crate::footnote;
+use crate::linkref::LinkRefs;
 use crate::MarkdownOptions;
 
-use crate::helpers::{adjust_blockquote, close_table, emit_html_event};
+use crate::helpers::{close_table, emit_html_event};
 
 #[derive(Clone, Debug)]
 pub(crate) struct ListFrame {
     pub kind: NodeKind,
     pub indent: usize,
+    /// Column where the current item's content starts (after the marker).
+    pub content_indent: usize,
     pub start_emitted: bool,
     pub item_open: bool,
+    /// Quotes open around the list.
+    pub quote_depth: usize,
 }
 
-pub struct ParseContext {
-    pub(crate) out: Vec<Event>,
+pub struct ParseContext<'a> {
+    pub(crate) out: Vec<Event<'a>>,
     pub(crate) stack: Vec<NodeKind>,
     pub(crate) in_heading: bool,
     pub(crate) heading_prefix_consumed: bool,
@@ -25,17 +33,40 @@ pub struct ParseContext {
     pub(crate) list_frames: Vec<ListFrame>,
     pub(crate) at_line_start: bool,
     pub(crate) pending_para_start: bool,
+    /// Quotes open; their `Blockquote` or `Callout` kinds are on `stack`.
     pub(crate) blockquote_depth: usize,
+    /// Quote depth and opening run of a code fence inside a quote, whose
+    /// lines the core parser reads as paragraph text.
+    pub(crate) quote_fence: Option<(usize, String)>,
     pub(crate) in_table: bool,
     pub(crate) first_table_row: bool,
+    /// Column alignments from the separator row of the open table.
+    pub(crate) table_align: Option<Vec<Option<&'static str>>>,
     pub(crate) options: MarkdownOptions,
     pub(crate) last_line_text: Option<String>,
-    pub(crate) previous_line_blank: bool,
     pub(crate) display_math_open: bool,
+    /// Columns stripped from each line of the open indented code block.
+    pub(crate) indented_code: Option<usize>,
+    /// Blank lines inside an indented code block, emitted only if the block
+    /// continues after them.
+    pub(crate) code_blank_lines: Vec<Option<Span>>,
+    /// The line before the current one was blank. Lists stay open over a
+    /// blank line until the next line shows whether it continues the item.
+    pub(crate) after_blank_line: bool,
+    /// Span of the input event currently being handled.
+    pub(crate) span: Option<Span>,
+    /// Link reference definitions collected before the main pass.
+    pub(crate) link_refs: LinkRefs,
+    /// The thematic break being read underlines a setext heading and is
+    /// dropped.
+    pub(crate) setext_break: bool,
+    /// Inside a block opened by a `---` break at the very start of the
+    /// document (frontmatter); its closing `---` is no setext underline.
+    pub(crate) in_frontmatter: bool,
 }
 
-impl ParseContext {
-    pub fn new(capacity: usize, options: MarkdownOptions) -> Self {
+impl<'a> ParseContext<'a> {
+    pub fn new(capacity: usize, options: MarkdownOptions, link_refs: LinkRefs) -> Self {
         Self {
             out: Vec::with_capacity(capacity),
             stack: Vec::new(),
@@ -50,63 +81,330 @@ impl ParseContext {
             at_line_start: false,
             pending_para_start: false,
             blockquote_depth: 0,
+            quote_fence: None,
             in_table: false,
             first_table_row: true,
+            table_align: None,
             options,
             last_line_text: None,
-            previous_line_blank: false,
             display_math_open: false,
+            indented_code: None,
+            code_blank_lines: Vec::new(),
+            after_blank_line: false,
+            span: None,
+            link_refs,
+            setext_break: false,
+            in_frontmatter: false,
         }
     }
 
+    /// Zero-width span at the start of the current input event. Synthesized
+    /// markers use it and are widened over their content by `finalize`.
+    pub fn point(&self) -> Option<Span> {
+        self.span.map(|sp| Span::new(sp.start, sp.start))
+    }
+
     pub fn emit_start(&mut self, kind: NodeKind) {
-        self.out.push(Event::StartNode(kind.clone()));
+        self.out.push(Event::StartNode(kind.clone(), self.point()));
         self.stack.push(kind);
     }
 
     pub fn emit_end(&mut self, kind: NodeKind) {
-        self.out.push(Event::EndNode(kind.clone()));
+        self.out.push(Event::EndNode(kind.clone(), self.point()));
         let _ = self.stack.pop();
     }
 
-    pub fn push_event(&mut self, event: &Event) {
+    /// Turn the open paragraph into a heading of `level` now that its
+    /// setext underline was read; false if there is no paragraph to take.
+    pub fn setext_heading(&mut self, level: usize) -> bool {
+        if !matches!(self.stack.last(), Some(NodeKind::Paragraph))
+            || !self.list_frames.is_empty()
+            || self.in_table
+            || self.in_frontmatter
+        {
+            return false;
+        }
+        let Some(start) = self.paragraph_start(self.out.len()) else {
+            return false;
+        };
+        let span = match &self.out[start] {
+            Event::StartNode(_, span) => *span,
+            _ => None,
+        };
+        self.out[start] = Event::StartNode(NodeKind::Heading, span);
+        self.out.insert(
+            start + 1,
+            Event::Attribute {
+                name: "level".into(),
+                value: AttrValue::Int(level as i64),
+                span: self.span,
+            },
+        );
+        self.stack.pop();
+        self.stack.push(NodeKind::Heading);
+        self.emit_end(NodeKind::Heading);
+        // Lines after the underline start a new paragraph
+        self.pending_para_start = true;
+        true
+    }
+
+    /// Index of the `Paragraph` start left open by the events before `end`.
+    fn paragraph_start(&self, end: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        self.out[..end].iter().rposition(|ev| match ev {
+            Event::EndNode(..) => {
+                depth += 1;
+                false
+            }
+            Event::StartNode(..) if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            Event::StartNode(kind, _) => *kind == NodeKind::Paragraph,
+            _ => false,
+        })
+    }
+
+    /// Prepare for a definition opened by a `: ` line at `indent`: keep the
+    /// definition list it continues, or turn the paragraph just read into
+    /// the `DefinitionTerm` of a list. False if the line defines nothing.
+    pub fn open_definition(&mut self, indent: usize) -> bool {
+        let open = self
+            .list_frames
+            .iter()
+            .any(|f| f.kind == NodeKind::DefinitionList && f.indent == indent);
+        if open {
+            self.close_lists_above(indent);
+            return true;
+        }
+        if !self.list_frames.is_empty() || self.in_table || self.in_frontmatter {
+            return false;
+        }
+        let term_open = matches!(self.stack.last(), Some(NodeKind::Paragraph));
+        match self.out.last() {
+            _ if term_open => {}
+            Some(Event::EndNode(NodeKind::Paragraph, _)) => {}
+            // A blank line closed the list before this definition
+            Some(Event::EndNode(NodeKind::DefinitionList, _)) => {
+                self.out.pop();
+                self.open_definition_list(indent);
+                return true;
+            }
+            _ => return false,
+        }
+        // A closed term ends with the `Paragraph` end matched above
+        let end = self.out.len() - usize::from(!term_open);
+        let Some(start) = self.paragraph_start(end) else {
+            return false;
+        };
+        let span = match &self.out[start] {
+            Event::StartNode(_, span) => *span,
+            _ => None,
+        };
+        self.out[start] = Event::StartNode(NodeKind::DefinitionTerm, span);
+        if term_open {
+            self.emit_end(NodeKind::DefinitionTerm);
+        } else if let Some(Event::EndNode(kind, _)) = self.out.last_mut() {
+            *kind = NodeKind::DefinitionTerm;
+        }
+        // A term after a blank line joins the list before it
+        if start > 0
+            && matches!(
+                self.out[start - 1],
+                Event::EndNode(NodeKind::DefinitionList, _)
+            )
+        {
+            self.out.remove(start - 1);
+        } else {
+            self.out
+                .insert(start, Event::StartNode(NodeKind::DefinitionList, span));
+        }
+        self.open_definition_list(indent);
+        true
+    }
+
+    // Track a definition list whose start is already in `out`
+    fn open_definition_list(&mut self, indent: usize) {
+        self.stack.push(NodeKind::DefinitionList);
+        self.list_frames.push(ListFrame {
+            kind: NodeKind::DefinitionList,
+            indent,
+            content_indent: indent + 2,
+            start_emitted: true,
+            item_open: false,
+            quote_depth: self.blockquote_depth,
+        });
+    }
+
+    pub fn push_event(&mut self, event: &Event<'a>) {
         self.out.push(event.clone());
     }
 
     pub fn close_all_lists(&mut self) {
         while let Some(frame) = self.list_frames.pop() {
-            if frame.item_open {
-                self.emit_end(NodeKind::ListItem);
-            }
-            self.emit_end(frame.kind);
+            self.end_list(frame);
+        }
+    }
+
+    // Close the list of a popped `frame`, with the quotes and item still
+    // open in it
+    fn end_list(&mut self, frame: ListFrame) {
+        self.close_quotes_to(frame.quote_depth);
+        if frame.item_open {
+            self.end_list_item(&frame.kind);
+        }
+        self.emit_end(frame.kind);
+    }
+
+    // Close an item of a `list` together with a paragraph or code block
+    // still open in it
+    fn end_list_item(&mut self, list: &NodeKind) {
+        self.close_indented_code();
+        if matches!(self.stack.last(), Some(NodeKind::Paragraph)) {
+            self.emit_end(NodeKind::Paragraph);
+        }
+        self.emit_end(item_kind(list));
+    }
+
+    pub fn open_indented_code(&mut self, strip: usize) {
+        self.emit_start(NodeKind::CodeFence);
+        self.indented_code = Some(strip);
+        self.code_blank_lines.clear();
+    }
+
+    /// Close an open indented code block; trailing blank lines are dropped.
+    pub fn close_indented_code(&mut self) {
+        if self.indented_code.take().is_some() {
+            self.emit_end(NodeKind::CodeFence);
+            self.after_blank_line |= !self.code_blank_lines.is_empty();
+            self.code_blank_lines.clear();
+        }
+    }
+
+    pub fn close_footnote(&mut self) {
+        if matches!(self.stack.last(), Some(NodeKind::FootnoteDefinition)) {
+            self.emit_end(NodeKind::FootnoteDefinition);
         }
     }
 
     pub fn close_table_if_open(&mut self) {
         if self.in_table {
-            close_table(&mut self.out, &mut self.in_table);
+            let at = self.point();
+            close_table(&mut self.out, &mut self.in_table, at);
             self.first_table_row = true;
         }
     }
 
     pub fn close_blockquotes(&mut self) {
-        if self.blockquote_depth > 0 {
-            adjust_blockquote(&mut self.out, &mut self.blockquote_depth, 0);
+        self.close_quotes_to(0);
+    }
+
+    /// Close or open quotes to reach nesting `depth`; the innermost quote
+    /// opened is a `kind` (`Blockquote` or `Callout`), any outer ones plain
+    /// blockquotes. Text after a change of depth starts a new paragraph.
+    pub fn set_quote_depth(&mut self, depth: usize, kind: NodeKind) {
+        if depth != self.blockquote_depth {
+            self.pending_para_start = true;
+        }
+        self.close_quotes_to(depth);
+        while self.blockquote_depth < depth {
+            self.close_quoted_leaf();
+            let innermost = self.blockquote_depth + 1 == depth;
+            self.emit_start(if innermost {
+                kind.clone()
+            } else {
+                NodeKind::Blockquote
+            });
+            self.blockquote_depth += 1;
         }
     }
 
-    pub fn finalize(mut self) -> Vec<Event> {
+    /// Close quotes down to nesting `depth` together with the blocks open
+    /// inside them; each ends with the kind it was opened as.
+    pub fn close_quotes_to(&mut self, depth: usize) {
+        while self.blockquote_depth > depth {
+            self.close_quoted_leaf();
+            while self
+                .list_frames
+                .last()
+                .is_some_and(|f| f.quote_depth >= self.blockquote_depth)
+            {
+                let frame = self.list_frames.pop().unwrap();
+                self.end_list(frame);
+            }
+            if let Some(kind @ (NodeKind::Blockquote | NodeKind::Callout)) = self.stack.last() {
+                self.emit_end(kind.clone());
+            }
+            self.blockquote_depth -= 1;
+        }
+    }
+
+    /// Close the lists of the innermost quote whose items a deeper quote
+    /// marker at column `indent` does not nest in.
+    pub fn close_lists_outside_quote(&mut self, indent: usize) {
+        while self
+            .list_frames
+            .last()
+            .is_some_and(|f| f.quote_depth == self.blockquote_depth && f.content_indent > indent)
+        {
+            let frame = self.list_frames.pop().unwrap();
+            self.end_list(frame);
+        }
+    }
+
+    /// Kind of the quote open at nesting `depth` (1-based).
+    pub fn quote_kind(&self, depth: usize) -> Option<&NodeKind> {
+        self.stack
+            .iter()
+            .filter(|k| matches!(k, NodeKind::Blockquote | NodeKind::Callout))
+            .nth(depth.checked_sub(1)?)
+    }
+
+    // End the leaf block a quote boundary interrupts; text after it starts
+    // a new paragraph
+    fn close_quoted_leaf(&mut self) {
+        self.close_quote_fence();
+        self.close_indented_code();
+        self.close_footnote();
+        self.close_table_if_open();
+        if matches!(self.stack.last(), Some(NodeKind::Paragraph)) {
+            self.emit_end(NodeKind::Paragraph);
+            self.pending_para_start = true;
+        }
+    }
+
+    pub fn open_quote_fence(&mut self, run: &str) {
+        self.close_quoted_leaf();
+        self.pending_para_start = false;
+        self.emit_start(NodeKind::CodeFence);
+        self.quote_fence = Some((self.blockquote_depth, run.to_string()));
+        self.skip_initial_code_newline = true;
+    }
+
+    pub fn close_quote_fence(&mut self) {
+        if self.quote_fence.take().is_some() {
+            self.emit_end(NodeKind::CodeFence);
+            self.skip_initial_code_newline = false;
+            self.pending_para_start = true;
+        }
+    }
+
+    pub fn finalize(mut self) -> Vec<Event<'a>> {
+        self.close_footnote();
+        self.close_indented_code();
         self.close_all_lists();
         if self.in_table {
-            close_table(&mut self.out, &mut self.in_table);
+            let at = self.point();
+            close_table(&mut self.out, &mut self.in_table, at);
             self.first_table_row = true;
         }
-        if self.blockquote_depth > 0 {
-            for _ in 0..self.blockquote_depth {
-                self.out.push(Event::EndNode(NodeKind::Blockquote));
-            }
-            self.blockquote_depth = 0;
+        self.close_blockquotes();
+        attrs::resolve(&mut self.out);
+        footnote::resolve(&mut self.out);
+        if self.options.extended_autolinks {
+            self.out = autolink::linkify(std::mem::take(&mut self.out));
         }
+        propagate_node_spans(&mut self.out);
         self.out
     }
 
@@ -118,10 +416,20 @@ impl ParseContext {
             .unwrap_or(false)
         {
             let frame = self.list_frames.pop().unwrap();
-            if frame.item_open {
-                self.emit_end(NodeKind::ListItem);
-            }
-            self.emit_end(frame.kind);
+            self.end_list(frame);
+        }
+    }
+
+    /// Close lists whose item content starts right of column `width`; used
+    /// after a blank line, when only lines indented into an item continue it.
+    pub fn close_lists_outside(&mut self, width: usize) {
+        while self
+            .list_frames
+            .last()
+            .is_some_and(|f| f.content_indent > width)
+        {
+            let frame = self.list_frames.pop().unwrap();
+            self.end_list(frame);
         }
     }
 
@@ -137,14 +445,14 @@ impl ParseContext {
         if let Some(line) = self.last_line_text.take() {
             if line.ends_with("  ") {
                 self.remove_trailing_chars(' ', 2);
-                emit_html_event(&mut self.out, "<br />", NodeKind::HtmlInline);
-                self.previous_line_blank = false;
+                let at = self.point();
+                emit_html_event(&mut self.out, "<br />", NodeKind::HtmlInline, at);
                 return true;
             }
             if line.ends_with("\\\\") {
                 self.remove_trailing_chars('\\', 2);
-                emit_html_event(&mut self.out, "<br />", NodeKind::HtmlInline);
-                self.previous_line_blank = false;
+                let at = self.point();
+                emit_html_event(&mut self.out, "<br />", NodeKind::HtmlInline, at);
                 return true;
             }
         }
@@ -157,8 +465,9 @@ impl ParseContext {
                 if let (NodeKind::OrderedList, Some(n)) = (&kind, start) {
                     if !frame.start_emitted {
                         self.out.push(Event::Attribute {
-                            name: "start".to_string(),
-                            value: n.to_string(),
+                            name: "start".into(),
+                            value: AttrValue::Int(n as i64),
+                            span: self.span,
                         });
                         frame.start_emitted = true;
                     }
@@ -167,10 +476,7 @@ impl ParseContext {
             }
             if frame.indent == indent && frame.kind != kind {
                 let popped = self.list_frames.pop().unwrap();
-                if popped.item_open {
-                    self.emit_end(NodeKind::ListItem);
-                }
-                self.emit_end(popped.kind);
+                self.end_list(popped);
             }
         }
 
@@ -196,15 +502,18 @@ impl ParseContext {
         self.emit_start(kind.clone());
         if let (NodeKind::OrderedList, Some(n)) = (kind.clone(), start) {
             self.out.push(Event::Attribute {
-                name: "start".to_string(),
-                value: n.to_string(),
+                name: "start".into(),
+                value: AttrValue::Int(n as i64),
+                span: self.span,
             });
         }
         self.list_frames.push(ListFrame {
             kind,
             indent,
+            content_indent: indent + 2,
             start_emitted: start.is_some(),
             item_open: false,
+            quote_depth: self.blockquote_depth,
         });
     }
 
@@ -212,8 +521,8 @@ impl ParseContext {
         let mut idx = self.out.len();
         while count > 0 && idx > 0 {
             idx -= 1;
-            if let Event::Text(text) = &mut self.out[idx] {
-                let removed = trim_line_end(text, ch, count);
+            if let Event::Text(text, _) = &mut self.out[idx] {
+                let removed = trim_line_end(text.to_mut(), ch, count);
                 if removed > 0 {
                     count -= removed;
                     if text.is_empty() {
@@ -224,20 +533,30 @@ impl ParseContext {
             }
         }
     }
-    pub fn start_list_item(&mut self) {
+    /// Open a new item whose content starts at column `content_indent`.
+    pub fn start_list_item(&mut self, content_indent: usize) {
+        let list = match self.list_frames.last() {
+            Some(frame) => frame.kind.clone(),
+            None => NodeKind::BulletList,
+        };
         let item_already_open = self.in_list_item();
         if item_already_open {
-            self.emit_end(NodeKind::ListItem);
+            self.end_list_item(&list);
             if let Some(frame) = self.list_frames.last_mut() {
                 frame.item_open = false;
             }
         }
-        self.emit_start(NodeKind::ListItem);
+        self.emit_start(item_kind(&list));
         if let Some(frame) = self.list_frames.last_mut() {
             frame.item_open = true;
+            frame.content_indent = content_indent;
         }
     }
 
+    pub fn current_item_content_indent(&self) -> Option<usize> {
+        self.list_frames.last().map(|f| f.content_indent)
+    }
+
     pub fn current_list_start_emitted(&self) -> bool {
         self.list_frames
             .last()
@@ -259,6 +578,14 @@ impl ParseContext {
     }
 }
 
+// Items of a definition list are its definitions
+fn item_kind(list: &NodeKind) -> NodeKind {
+    match list {
+        NodeKind::DefinitionList => NodeKind::DefinitionDescription,
+        _ => NodeKind::ListItem,
+    }
+}
+
 fn trim_line_end(text: &mut String, ch: char, max: usize) -> usize {
     let mut removed = 0;
     while removed < max {
@@ -1,4 +1,4 @@
-use pendon_core::{Event, NodeKind};
+use pendon_core::NodeKind;
 
 use crate::context::ParseContext;
 
@@ -15,22 +15,25 @@ pub fn handle(ctx: &mut ParseContext, kind: &NodeKind) {
             ctx.skip_backticks_once = true;
         }
         NodeKind::Document => {
+            ctx.close_footnote();
+            ctx.close_indented_code();
             ctx.close_all_lists();
             ctx.close_table_if_open();
-            if ctx.blockquote_depth > 0 {
-                for _ in 0..ctx.blockquote_depth {
-                    ctx.out.push(Event::EndNode(NodeKind::Blockquote));
-                }
-                ctx.blockquote_depth = 0;
-            }
+            ctx.close_blockquotes();
             ctx.emit_end(NodeKind::Document);
         }
+        NodeKind::ThematicBreak if std::mem::take(&mut ctx.setext_break) => {}
         NodeKind::Paragraph => {
+            // A footnote definition runs to the end of its paragraph, as
+            // does a fence inside a quote
+            ctx.close_footnote();
+            ctx.close_quote_fence();
             if ctx.pending_para_start {
                 ctx.pending_para_start = false;
             } else if ctx.skip_para_close > 0 {
                 ctx.skip_para_close = ctx.skip_para_close.saturating_sub(1);
-            } else {
+            } else if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
+                // Lists and tables may already have closed the paragraph
                 ctx.emit_end(NodeKind::Paragraph);
             }
             ctx.at_line_start = false;
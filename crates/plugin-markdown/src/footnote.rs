@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use pendon_core::{AttrValue, Event, NodeKind};
+
+/// `[^label]` starting at `i`: the label and the index just past `]`.
+pub fn reference_at(chars: &[char], i: usize) -> Option<(String, usize)> {
+    if chars.get(i) != Some(&'[') || chars.get(i + 1) != Some(&'^') {
+        return None;
+    }
+    let len = chars[i + 2..]
+        .iter()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '[' | ']'))
+        .count();
+    let end = i + 2 + len;
+    if len == 0 || chars.get(end) != Some(&']') {
+        return None;
+    }
+    Some((chars[i + 2..end].iter().collect(), end + 1))
+}
+
+/// A definition line `[^label]: text`: the label and the text.
+pub fn definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[^")?;
+    let close = rest.find("]:")?;
+    let label = &rest[..close];
+    if label.is_empty() || label.contains(|c: char| c.is_whitespace() || c == '[' || c == ']') {
+        return None;
+    }
+    Some((label, rest[close + 2..].trim_start()))
+}
+
+// Numbering state: labels in order of first reference, and how often each
+// was referenced
+#[derive(Default)]
+struct Notes {
+    order: Vec<String>,
+    refs: HashMap<String, usize>,
+}
+
+/// Number references in order of first use and move the referenced
+/// definitions, in that order, to the end of the document. References
+/// without a definition revert to text; unreferenced definitions are dropped.
+pub fn resolve(out: &mut Vec<Event<'_>>) {
+    let is_note = |ev: &Event| {
+        matches!(
+            ev,
+            Event::StartNode(
+                NodeKind::FootnoteReference | NodeKind::FootnoteDefinition,
+                _
+            )
+        )
+    };
+    if !out.iter().any(is_note) {
+        return;
+    }
+
+    let mut body = Vec::with_capacity(out.len());
+    let mut defs: HashMap<String, Vec<Event>> = HashMap::new();
+    let mut current: Option<Vec<Event>> = None;
+    for ev in out.drain(..) {
+        match (&ev, current.as_mut()) {
+            (Event::StartNode(NodeKind::FootnoteDefinition, _), _) => current = Some(vec![ev]),
+            (Event::EndNode(NodeKind::FootnoteDefinition, _), Some(def)) => {
+                def.push(ev);
+                let def = current.take().unwrap_or_default();
+                if let Some(label) = label_of(&def) {
+                    defs.entry(label).or_insert(def);
+                }
+            }
+            (_, Some(def)) => def.push(ev),
+            (_, None) => body.push(ev),
+        }
+    }
+
+    let mut notes = Notes::default();
+    let mut resolved = number_refs(body, &defs, &mut notes);
+    let doc_end = match resolved.last() {
+        Some(Event::EndNode(NodeKind::Document, _)) => resolved.pop(),
+        _ => None,
+    };
+    // Notes may reference further notes, which join the end of the list
+    let mut bodies = Vec::new();
+    let mut idx = 0;
+    while idx < notes.order.len() {
+        let label = notes.order[idx].clone();
+        idx += 1;
+        let Some(mut def) = defs.remove(&label) else {
+            continue;
+        };
+        let rest = def.split_off(1);
+        let rest = number_refs(rest, &defs, &mut notes);
+        bodies.push((idx, label, def, rest));
+    }
+    // Back-reference counts are only final once every note is numbered
+    for (index, label, mut def, rest) in bodies {
+        let span = def[0].span();
+        def.push(Event::Attribute {
+            name: "index".into(),
+            value: AttrValue::Int(index as i64),
+            span,
+        });
+        def.push(Event::Attribute {
+            name: "refs".into(),
+            value: AttrValue::Int(notes.refs[&label] as i64),
+            span,
+        });
+        def.extend(rest);
+        resolved.extend(def);
+    }
+    resolved.extend(doc_end);
+    *out = resolved;
+}
+
+// Normalized label of the definition or reference opened by `events[0]`
+fn label_of(events: &[Event]) -> Option<String> {
+    events.iter().skip(1).find_map(|ev| match ev {
+        Event::Attribute { name, value, .. } if name == "label" => {
+            Some(value.to_string().to_lowercase())
+        }
+        _ => None,
+    })
+}
+
+fn number_refs<'a>(
+    events: Vec<Event<'a>>,
+    defs: &HashMap<String, Vec<Event<'a>>>,
+    notes: &mut Notes,
+) -> Vec<Event<'a>> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut iter = events.into_iter();
+    while let Some(ev) = iter.next() {
+        let Event::StartNode(NodeKind::FootnoteReference, span) = ev else {
+            out.push(ev);
+            continue;
+        };
+        let mut node = vec![ev];
+        for inner in iter.by_ref() {
+            let end = matches!(inner, Event::EndNode(NodeKind::FootnoteReference, _));
+            node.push(inner);
+            if end {
+                break;
+            }
+        }
+        let Some(label) = label_of(&node) else {
+            continue;
+        };
+        let known = notes.refs.contains_key(&label) || defs.contains_key(&label);
+        if !known {
+            let raw = node.iter().find_map(|ev| match ev {
+                Event::Attribute { name, value, .. } if name == "label" => Some(value.to_string()),
+                _ => None,
+            });
+            out.push(Event::Text(
+                format!("[^{}]", raw.unwrap_or(label)).into(),
+                span,
+            ));
+            continue;
+        }
+        let count = notes.refs.entry(label.clone()).or_insert(0);
+        *count += 1;
+        let nth = *count;
+        if nth == 1 {
+            notes.order.push(label.clone());
+        }
+        let index = notes.order.iter().position(|l| *l == label).unwrap_or(0) + 1;
+        let end = node.pop();
+        out.extend(node);
+        out.push(Event::Attribute {
+            name: "index".into(),
+            value: AttrValue::Int(index as i64),
+            span,
+        });
+        out.push(Event::Attribute {
+            name: "ref".into(),
+            value: AttrValue::Int(nth as i64),
+            span,
+        });
+        out.extend(end);
+    }
+    out
+}
@@ -1,7 +1,14 @@
-use pendon_core::{Event, NodeKind};
+use std::borrow::Cow;
 
-use crate::math::math_region_end;
-use crate::MarkdownOptions;
+use pendon_core::{
+    entity_at, is_escapable, math_region_end, unescape, Diagnostic, Event, NodeKind, Position, Span,
+};
+
+use crate::attrs;
+use crate::autolink::autolink_at;
+use crate::footnote;
+use crate::linkref::{LinkRefs, LinkTarget};
+use crate::{MarkdownOptions, UNDEFINED_REFERENCE};
 
 pub fn parse_blockquote_prefix(line: &str) -> (usize, &str) {
     let bytes = line.as_bytes();
@@ -26,17 +33,102 @@ pub fn parse_blockquote_prefix(line: &str) -> (usize, &str) {
     (depth, line.get(i..).unwrap_or(""))
 }
 
-pub fn adjust_blockquote(out: &mut Vec<Event>, current: &mut usize, target: usize) {
-    while *current > target {
-        out.push(Event::EndNode(NodeKind::Blockquote));
-        *current -= 1;
+/// Span of `part`, which must be a suffix of `line`, given the span of `line`.
+pub fn suffix_span(span: Option<Span>, line: &str, part: &str) -> Option<Span> {
+    let from = line.len().saturating_sub(part.len());
+    span.map(|sp| sp.slice(line, from..line.len()))
+}
+
+/// Span of the first occurrence of `part` in `line`, given the span of `line`.
+pub fn find_span(span: Option<Span>, line: &str, part: &str) -> Option<Span> {
+    let from = line.find(part)?;
+    span.map(|sp| sp.slice(line, from..from + part.len()))
+}
+
+/// Indentation of `line` in columns; a tab advances to the next multiple of 4.
+pub fn indent_width(line: &str) -> usize {
+    let mut col = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => col += 1,
+            '\t' => col += 4 - col % 4,
+            _ => break,
+        }
+    }
+    col
+}
+
+/// `line` without its first `cols` columns of indentation. A tab straddling
+/// the cut leaves its remaining columns as spaces.
+pub fn strip_indent(line: &str, cols: usize) -> Cow<'_, str> {
+    let (mut col, mut idx) = (0, 0);
+    for ch in line.chars() {
+        if col >= cols {
+            break;
+        }
+        match ch {
+            ' ' => col += 1,
+            '\t' => col += 4 - col % 4,
+            _ => break,
+        }
+        idx += 1;
+    }
+    let rest = &line[idx..];
+    if col > cols {
+        Cow::Owned(format!("{}{}", " ".repeat(col - cols), rest))
+    } else {
+        Cow::Borrowed(rest)
+    }
+}
+
+/// `line` without the markers of `depth` enclosing quotes, or `None` if it
+/// has fewer.
+pub fn strip_blockquote_prefix(line: &str, depth: usize) -> Option<&str> {
+    let mut rest = line;
+    for _ in 0..depth {
+        rest = rest.trim_start_matches(' ').strip_prefix('>')?;
+        rest = rest.strip_prefix(' ').unwrap_or(rest);
+    }
+    Some(rest)
+}
+
+/// Opening run (three or more backticks or tildes) and info string of a
+/// code fence line.
+pub fn fence_open(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start_matches(' ');
+    let marker = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.len() - line.trim_start_matches(marker).len();
+    let (run, info) = line.split_at(len);
+    // Backtick fences may not carry backticks in their info string
+    (len >= 3 && !(marker == '`' && info.contains('`'))).then_some((run, info))
+}
+
+/// Heading level of a setext underline: a run of `=` (1) or `-` (2)
+/// indented at most three spaces.
+pub fn setext_level(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let run = line.trim();
+    if indent > 3 || run.is_empty() {
+        return None;
     }
-    while *current < target {
-        out.push(Event::StartNode(NodeKind::Blockquote));
-        *current += 1;
+    if run.chars().all(|c| c == '=') {
+        Some(1)
+    } else if run.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
     }
 }
 
+/// Width of the `:` and the blanks after it opening a definition line
+/// (`: text`); a line with nothing after the marker defines nothing.
+pub fn definition_marker(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix(':')?;
+    let content = rest.trim_start_matches([' ', '\t']);
+    let blanks = rest.len() - content.len();
+    (blanks > 0 && !content.trim_end().is_empty()).then_some(1 + blanks)
+}
+
 pub fn is_table_row(line: &str) -> bool {
     let trimmed = line.trim();
     if trimmed.is_empty() {
@@ -49,35 +141,61 @@ pub fn is_table_row(line: &str) -> bool {
     cells.len() >= 2
 }
 
-pub fn start_table(out: &mut Vec<Event>) {
-    out.push(Event::StartNode(NodeKind::Table));
-    out.push(Event::StartNode(NodeKind::TableHead));
+pub fn start_table(out: &mut Vec<Event>, at: Option<Span>) {
+    out.push(Event::StartNode(NodeKind::Table, at));
+    out.push(Event::StartNode(NodeKind::TableHead, at));
 }
 
-pub fn close_table(out: &mut Vec<Event>, in_table: &mut bool) {
+pub fn close_table(out: &mut Vec<Event>, in_table: &mut bool, at: Option<Span>) {
     if *in_table {
-        out.push(Event::EndNode(NodeKind::TableBody));
-        out.push(Event::EndNode(NodeKind::Table));
+        out.push(Event::EndNode(NodeKind::TableBody, at));
+        out.push(Event::EndNode(NodeKind::Table, at));
         *in_table = false;
     }
 }
 
-pub fn emit_table_row(line: &str, is_header: bool, out: &mut Vec<Event>, opts: MarkdownOptions) {
-    out.push(Event::StartNode(NodeKind::TableRow));
-    for cell in split_table_cells(line) {
-        out.push(Event::StartNode(NodeKind::TableCell));
+pub fn emit_table_row(
+    line: &str,
+    is_header: bool,
+    align: &[Option<&'static str>],
+    out: &mut Vec<Event>,
+    opts: MarkdownOptions,
+    refs: &LinkRefs,
+    span: Option<Span>,
+) {
+    out.push(Event::StartNode(NodeKind::TableRow, span));
+    // Cells are trimmed substrings of `line`; locate them left to right
+    let mut cursor = 0usize;
+    for (col, cell) in split_table_cells(line).into_iter().enumerate() {
+        let cell_span = match line[cursor..].find(cell.as_str()) {
+            Some(rel) => {
+                let from = cursor + rel;
+                cursor = from + cell.len();
+                span.map(|sp| sp.slice(line, from..cursor))
+            }
+            None => span,
+        };
+        out.push(Event::StartNode(NodeKind::TableCell, cell_span));
         if is_header {
             out.push(Event::Attribute {
-                name: "header".to_string(),
-                value: "1".to_string(),
+                name: "header".into(),
+                value: "1".into(),
+                span: cell_span,
+            });
+        }
+        if let Some(value) = align.get(col).copied().flatten() {
+            out.push(Event::Attribute {
+                name: "align".into(),
+                value: value.into(),
+                span: cell_span,
             });
         }
         if !cell.is_empty() {
-            emit_inline(&cell, out, opts);
+            emit_inline(&cell, out, opts, refs, cell_span);
         }
-        out.push(Event::EndNode(NodeKind::TableCell));
+        out.push(Event::EndNode(NodeKind::TableCell, cell_span));
     }
-    out.push(Event::EndNode(NodeKind::TableRow));
+    out.push(Event::EndNode(NodeKind::TableRow, span));
 }
 
 pub fn is_table_separator(cells: &[String]) -> bool {
@@ -87,152 +205,419 @@ pub fn is_table_separator(cells: &[String]) -> bool {
     })
 }
 
-pub fn emit_inline(s: &str, out: &mut Vec<Event>, opts: MarkdownOptions) {
+/// Column alignments of a separator row: `:---` left, `:---:` center,
+/// `---:` right and `---` unset.
+pub fn table_alignments(cells: &[String]) -> Vec<Option<&'static str>> {
+    cells
+        .iter()
+        .map(
+            |c| match (c.starts_with(':'), c.len() > 1 && c.ends_with(':')) {
+                (true, true) => Some("center"),
+                (true, false) => Some("left"),
+                (false, true) => Some("right"),
+                (false, false) => None,
+            },
+        )
+        .collect()
+}
+
+/// Add `align` attributes to the header cells already emitted, which are
+/// written before the separator row below them is seen.
+pub fn align_table_head(out: &mut Vec<Event>, align: &[Option<&'static str>]) {
+    let Some(head) = out
+        .iter()
+        .rposition(|ev| matches!(ev, Event::StartNode(NodeKind::TableHead, _)))
+    else {
+        return;
+    };
+    let cells: Vec<(usize, Option<Span>)> = out[head..]
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, ev)| match ev {
+            Event::StartNode(NodeKind::TableCell, span) => Some((head + idx, *span)),
+            _ => None,
+        })
+        .collect();
+    for (col, (idx, span)) in cells.into_iter().enumerate().rev() {
+        let Some(value) = align.get(col).copied().flatten() else {
+            continue;
+        };
+        // After the `header` attribute that follows the cell start
+        out.insert(
+            idx + 2,
+            Event::Attribute {
+                name: "align".into(),
+                value: value.into(),
+                span,
+            },
+        );
+    }
+}
+
+/// Emit inline events for `s`; `span` covers `s` and is split per construct.
+/// Reference links and images resolve against `refs`.
+pub fn emit_inline(
+    s: &str,
+    out: &mut Vec<Event>,
+    opts: MarkdownOptions,
+    refs: &LinkRefs,
+    span: Option<Span>,
+) {
     let bytes: Vec<char> = s.chars().collect();
+    let positions = char_positions(&bytes, span);
+    let sub = |from: usize, to: usize| -> Option<Span> {
+        positions
+            .as_ref()
+            .map(|p| Span::new(p[from.min(bytes.len())], p[to.min(bytes.len())]))
+    };
     let mut i = 0usize;
     while i < bytes.len() {
         if let Some(end) = math_region_end(&bytes, i) {
             let content: String = bytes[i..end].iter().collect();
-            out.push(Event::Text(content));
+            out.push(Event::Text(content.into(), sub(i, end)));
             i = end;
             continue;
         }
+        // `\$` is left to the math plugins, and a trailing `\\` is a line break
+        if bytes[i] == '\\'
+            && i + 1 < bytes.len()
+            && is_escapable(bytes[i + 1])
+            && bytes[i + 1] != '$'
+            && !(bytes[i + 1] == '\\' && i + 2 == bytes.len())
+        {
+            out.push(Event::Text(bytes[i + 1].to_string().into(), sub(i, i + 2)));
+            i += 2;
+            continue;
+        }
+        if bytes[i] == '&' {
+            // Longest HTML5 entity name is 31 characters
+            let ahead: String = bytes[i..].iter().take(40).collect();
+            if let Some((text, len)) = entity_at(&ahead) {
+                let end = i + ahead[..len].chars().count();
+                out.push(Event::Text(text.into_owned().into(), sub(i, end)));
+                i = end;
+                continue;
+            }
+        }
         if bytes[i] == '!' && i + 1 < bytes.len() && bytes[i + 1] == '[' {
             // Keep !![...](...) reserved for plugin-img advanced figure syntax.
             if i == 0 || bytes[i.saturating_sub(1)] != '!' {
-                if let Some(close_br) = find_next(&bytes, i + 2, ']') {
+                if let Some(close_br) = find_unescaped(&bytes, i + 2, ']') {
                     if close_br + 1 < bytes.len() && bytes[close_br + 1] == '(' {
-                        if let Some(close_par) = find_next(&bytes, close_br + 2, ')') {
-                            let alt: String = bytes[i + 2..close_br].iter().collect();
+                        if let Some(close_par) = find_unescaped(&bytes, close_br + 2, ')') {
+                            let raw_alt: String = bytes[i + 2..close_br].iter().collect();
+                            let alt = unescape(&raw_alt).into_owned();
                             let raw_target: String =
                                 bytes[close_br + 2..close_par].iter().collect();
                             let (src, title) = parse_target_and_title(&raw_target);
-                            out.push(Event::StartNode(NodeKind::Image));
+                            let extra = attrs::inline_at(&bytes, close_par + 1);
+                            let end = extra.as_ref().map_or(close_par + 1, |(_, end)| *end);
+                            let whole = sub(i, end);
+                            let target = sub(close_br + 2, close_par);
+                            out.push(Event::StartNode(NodeKind::Image, whole));
                             out.push(Event::Attribute {
-                                name: "alt".to_string(),
-                                value: alt,
+                                name: "alt".into(),
+                                value: alt.into(),
+                                span: sub(i + 2, close_br),
                             });
                             out.push(Event::Attribute {
-                                name: "src".to_string(),
-                                value: src,
+                                name: "src".into(),
+                                value: src.into(),
+                                span: target,
                             });
                             if let Some(title) = title {
                                 out.push(Event::Attribute {
-                                    name: "title".to_string(),
-                                    value: title,
+                                    name: "title".into(),
+                                    value: title.into(),
+                                    span: target,
                                 });
                             }
-                            out.push(Event::EndNode(NodeKind::Image));
-                            i = close_par + 1;
+                            if let Some((block, _)) = &extra {
+                                attrs::push(out, block, sub(close_par + 1, end));
+                            }
+                            out.push(Event::EndNode(NodeKind::Image, whole));
+                            i = end;
                             continue;
                         }
                     }
                 }
             }
         }
+        // An unresolved reference image is left to the link branch, which
+        // reports it once
+        if bytes[i] == '!' && bytes.get(i + 1) == Some(&'[') && (i == 0 || bytes[i - 1] != '!') {
+            if let Some((close_br, label, next, _)) = reference_at(&bytes, i + 1) {
+                if let Some(target) = refs.get(&label) {
+                    let raw_alt: String = bytes[i + 2..close_br].iter().collect();
+                    let alt = unescape(&raw_alt).into_owned();
+                    let extra = attrs::inline_at(&bytes, next);
+                    let end = extra.as_ref().map_or(next, |(_, end)| *end);
+                    let whole = sub(i, end);
+                    out.push(Event::StartNode(NodeKind::Image, whole));
+                    out.push(Event::Attribute {
+                        name: "alt".into(),
+                        value: alt.into(),
+                        span: sub(i + 2, close_br),
+                    });
+                    push_target_attrs(out, "src", target, whole);
+                    if let Some((block, _)) = &extra {
+                        attrs::push(out, block, sub(next, end));
+                    }
+                    out.push(Event::EndNode(NodeKind::Image, whole));
+                    i = end;
+                    continue;
+                }
+            }
+        }
         if bytes[i] == '`' {
             if let Some(end) = find_next(&bytes, i + 1, '`') {
-                out.push(Event::StartNode(NodeKind::InlineCode));
+                out.push(Event::StartNode(NodeKind::InlineCode, sub(i, end + 1)));
                 if end > i + 1 {
                     let content: String = bytes[i + 1..end].iter().collect();
-                    out.push(Event::Text(content));
+                    out.push(Event::Text(content.into(), sub(i + 1, end)));
                 }
-                out.push(Event::EndNode(NodeKind::InlineCode));
+                out.push(Event::EndNode(NodeKind::InlineCode, sub(i, end + 1)));
                 i = end + 1;
                 continue;
             }
         }
+        if let Some((href, text, next)) = autolink_at(&bytes, i) {
+            let whole = sub(i, next);
+            let target = sub(i + 1, next - 1);
+            out.push(Event::StartNode(NodeKind::Link, whole));
+            out.push(Event::Attribute {
+                name: "href".into(),
+                value: href.into(),
+                span: target,
+            });
+            out.push(Event::Text(text.into(), target));
+            out.push(Event::EndNode(NodeKind::Link, whole));
+            i = next;
+            continue;
+        }
         if opts.allow_html && bytes[i] == '<' {
             if let Some((content, next)) = extract_html_segment(&bytes, i) {
-                emit_html_event(out, &content, NodeKind::HtmlInline);
+                emit_html_event(out, &content, NodeKind::HtmlInline, sub(i, next));
                 i = next;
                 continue;
             }
         }
+        if let Some((label, next)) = footnote::reference_at(&bytes, i) {
+            let whole = sub(i, next);
+            out.push(Event::StartNode(NodeKind::FootnoteReference, whole));
+            out.push(Event::Attribute {
+                name: "label".into(),
+                value: label.into(),
+                span: sub(i + 2, next - 1),
+            });
+            out.push(Event::EndNode(NodeKind::FootnoteReference, whole));
+            i = next;
+            continue;
+        }
         if bytes[i] == '[' {
-            if let Some(close_br) = find_next(&bytes, i + 1, ']') {
+            if let Some(close_br) = find_unescaped(&bytes, i + 1, ']') {
                 if close_br + 1 < bytes.len() && bytes[close_br + 1] == '(' {
-                    if let Some(close_par) = find_next(&bytes, close_br + 2, ')') {
+                    if let Some(close_par) = find_unescaped(&bytes, close_br + 2, ')') {
                         let text: String = bytes[i + 1..close_br].iter().collect();
                         let raw_target: String = bytes[close_br + 2..close_par].iter().collect();
                         let (url, title) = parse_target_and_title(&raw_target);
-                        out.push(Event::StartNode(NodeKind::Link));
+                        let extra = attrs::inline_at(&bytes, close_par + 1);
+                        let end = extra.as_ref().map_or(close_par + 1, |(_, end)| *end);
+                        let whole = sub(i, end);
+                        let target = sub(close_br + 2, close_par);
+                        out.push(Event::StartNode(NodeKind::Link, whole));
                         out.push(Event::Attribute {
-                            name: "href".to_string(),
-                            value: url,
+                            name: "href".into(),
+                            value: url.into(),
+                            span: target,
                         });
                         if let Some(title) = title {
                             out.push(Event::Attribute {
-                                name: "title".to_string(),
-                                value: title,
+                                name: "title".into(),
+                                value: title.into(),
+                                span: target,
                             });
                         }
-                        emit_inline(&text, out, opts);
-                        out.push(Event::EndNode(NodeKind::Link));
-                        i = close_par + 1;
+                        if let Some((block, _)) = &extra {
+                            attrs::push(out, block, sub(close_par + 1, end));
+                        }
+                        emit_inline(&text, out, opts, refs, sub(i + 1, close_br));
+                        out.push(Event::EndNode(NodeKind::Link, whole));
+                        i = end;
                         continue;
                     }
                 }
             }
         }
+        if bytes[i] == '[' {
+            if let Some((close_br, label, next, explicit)) = reference_at(&bytes, i) {
+                let text: String = bytes[i + 1..close_br].iter().collect();
+                let whole = sub(i, next);
+                if let Some(target) = refs.get(&label) {
+                    let extra = attrs::inline_at(&bytes, next);
+                    let end = extra.as_ref().map_or(next, |(_, end)| *end);
+                    let whole = sub(i, end);
+                    out.push(Event::StartNode(NodeKind::Link, whole));
+                    push_target_attrs(out, "href", target, whole);
+                    if let Some((block, _)) = &extra {
+                        attrs::push(out, block, sub(next, end));
+                    }
+                    emit_inline(&text, out, opts, refs, sub(i + 1, close_br));
+                    out.push(Event::EndNode(NodeKind::Link, whole));
+                    i = end;
+                    continue;
+                }
+                // `[text][ref]` and `[ref][]` name a definition; a shortcut
+                // `[text]` is just as likely plain bracketed text
+                if explicit {
+                    let diag = Diagnostic::warning(
+                        UNDEFINED_REFERENCE,
+                        format!("undefined link reference `{}`", label.trim()),
+                    )
+                    .with_span(whole)
+                    .with_plugin("markdown")
+                    .with_help(format!(
+                        "define it on its own line: `[{}]: url`",
+                        label.trim()
+                    ));
+                    out.push(diag.into());
+                    out.push(Event::Text("[".into(), sub(i, i + 1)));
+                    emit_inline(&text, out, opts, refs, sub(i + 1, close_br));
+                    let rest: String = bytes[close_br..next].iter().collect();
+                    out.push(Event::Text(rest.into(), sub(close_br, next)));
+                    i = next;
+                    continue;
+                }
+            }
+        }
         if i + 2 < bytes.len() && bytes[i] == '*' && bytes[i + 1] == '*' && bytes[i + 2] == '*' {
             if let Some(end) = find_delim_run(&bytes, i + 3, '*', 3) {
-                out.push(Event::StartNode(NodeKind::Strong));
-                out.push(Event::StartNode(NodeKind::Emphasis));
+                let whole = sub(i, end + 3);
+                out.push(Event::StartNode(NodeKind::Strong, whole));
+                out.push(Event::StartNode(NodeKind::Emphasis, whole));
                 let content: String = bytes[i + 3..end].iter().collect();
-                emit_inline(&content, out, opts);
-                out.push(Event::EndNode(NodeKind::Emphasis));
-                out.push(Event::EndNode(NodeKind::Strong));
+                emit_inline(&content, out, opts, refs, sub(i + 3, end));
+                out.push(Event::EndNode(NodeKind::Emphasis, whole));
+                out.push(Event::EndNode(NodeKind::Strong, whole));
                 i = end + 3;
                 continue;
             }
         }
         if i + 1 < bytes.len() && bytes[i] == '*' && bytes[i + 1] == '*' {
             if let Some(end) = find_delim_run(&bytes, i + 2, '*', 2) {
-                out.push(Event::StartNode(NodeKind::Strong));
+                out.push(Event::StartNode(NodeKind::Strong, sub(i, end + 2)));
                 let content: String = bytes[i + 2..end].iter().collect();
-                emit_inline(&content, out, opts);
-                out.push(Event::EndNode(NodeKind::Strong));
+                emit_inline(&content, out, opts, refs, sub(i + 2, end));
+                out.push(Event::EndNode(NodeKind::Strong, sub(i, end + 2)));
                 i = end + 2;
                 continue;
             }
         }
         if i + 1 < bytes.len() && bytes[i] == '_' && bytes[i + 1] == '_' {
             if let Some(end) = find_delim_run(&bytes, i + 2, '_', 2) {
-                out.push(Event::StartNode(NodeKind::Bold));
+                out.push(Event::StartNode(NodeKind::Bold, sub(i, end + 2)));
+                let content: String = bytes[i + 2..end].iter().collect();
+                emit_inline(&content, out, opts, refs, sub(i + 2, end));
+                out.push(Event::EndNode(NodeKind::Bold, sub(i, end + 2)));
+                i = end + 2;
+                continue;
+            }
+        }
+        // Strikethrough opens and closes on runs of exactly two tildes
+        if bytes[i] == '~' && run_len(&bytes, i, '~') == 2 && (i == 0 || bytes[i - 1] != '~') {
+            if let Some(end) = find_tilde_pair(&bytes, i + 2) {
+                out.push(Event::StartNode(NodeKind::Strikethrough, sub(i, end + 2)));
                 let content: String = bytes[i + 2..end].iter().collect();
-                emit_inline(&content, out, opts);
-                out.push(Event::EndNode(NodeKind::Bold));
+                emit_inline(&content, out, opts, refs, sub(i + 2, end));
+                out.push(Event::EndNode(NodeKind::Strikethrough, sub(i, end + 2)));
                 i = end + 2;
                 continue;
             }
         }
         if bytes[i] == '*' {
             if let Some(end) = find_delim_run(&bytes, i + 1, '*', 1) {
-                out.push(Event::StartNode(NodeKind::Emphasis));
+                out.push(Event::StartNode(NodeKind::Emphasis, sub(i, end + 1)));
                 let content: String = bytes[i + 1..end].iter().collect();
-                emit_inline(&content, out, opts);
-                out.push(Event::EndNode(NodeKind::Emphasis));
+                emit_inline(&content, out, opts, refs, sub(i + 1, end));
+                out.push(Event::EndNode(NodeKind::Emphasis, sub(i, end + 1)));
                 i = end + 1;
                 continue;
             }
         }
         if bytes[i] == '_' {
             if let Some(end) = find_delim_run(&bytes, i + 1, '_', 1) {
-                out.push(Event::StartNode(NodeKind::Italic));
+                out.push(Event::StartNode(NodeKind::Italic, sub(i, end + 1)));
                 let content: String = bytes[i + 1..end].iter().collect();
-                emit_inline(&content, out, opts);
-                out.push(Event::EndNode(NodeKind::Italic));
+                emit_inline(&content, out, opts, refs, sub(i + 1, end));
+                out.push(Event::EndNode(NodeKind::Italic, sub(i, end + 1)));
                 i = end + 1;
                 continue;
             }
         }
-        out.push(Event::Text(bytes[i].to_string()));
+        out.push(Event::Text(bytes[i].to_string().into(), sub(i, i + 1)));
         i += 1;
     }
 }
 
-fn parse_target_and_title(raw: &str) -> (String, Option<String>) {
+/// Position before each char of `chars` plus the end position, when known.
+fn char_positions(chars: &[char], span: Option<Span>) -> Option<Vec<Position>> {
+    let mut pos = span?.start;
+    let mut out = Vec::with_capacity(chars.len() + 1);
+    let mut buf = [0u8; 4];
+    for ch in chars {
+        out.push(pos);
+        pos = pos.advance(ch.encode_utf8(&mut buf));
+    }
+    out.push(pos);
+    Some(out)
+}
+
+// `[text][label]`, `[label][]` or `[label]` opening at `open`: the index of
+// the bracket closing the text, the label, the index past the construct and
+// whether the label was marked as a reference with a second bracket pair
+fn reference_at(chars: &[char], open: usize) -> Option<(usize, String, usize, bool)> {
+    let close_br = find_unescaped(chars, open + 1, ']')?;
+    let text: String = chars[open + 1..close_br].iter().collect();
+    if text.contains('[') {
+        return None;
+    }
+    if chars.get(close_br + 1) == Some(&'[') {
+        let close_label = find_unescaped(chars, close_br + 2, ']')?;
+        let label: String = chars[close_br + 2..close_label].iter().collect();
+        if label.contains('[') {
+            return None;
+        }
+        let label = if label.trim().is_empty() { text } else { label };
+        return (!label.trim().is_empty()).then_some((close_br, label, close_label + 1, true));
+    }
+    (!text.trim().is_empty()).then_some((close_br, text, close_br + 1, false))
+}
+
+fn push_target_attrs(out: &mut Vec<Event>, key: &str, target: &LinkTarget, span: Option<Span>) {
+    out.push(Event::Attribute {
+        name: key.to_string().into(),
+        value: target.url.clone().into(),
+        span,
+    });
+    if let Some(title) = &target.title {
+        out.push(Event::Attribute {
+            name: "title".into(),
+            value: title.clone().into(),
+            span,
+        });
+    }
+}
+
+/// Destination and optional `"title"`, both with escapes and entities decoded.
+pub fn parse_target_and_title(raw: &str) -> (String, Option<String>) {
+    let (dest, title) = split_target_and_title(raw);
+    (
+        unescape(&dest).into_owned(),
+        title.map(|t| unescape(&t).into_owned()),
+    )
+}
+
+fn split_target_and_title(raw: &str) -> (String, Option<String>) {
     let s = raw.trim();
     if s.len() >= 2 && s.ends_with('"') {
         let bytes = s.as_bytes();
@@ -276,9 +661,28 @@ fn find_next(hay: &[char], mut i: usize, ch: char) -> Option<usize> {
     None
 }
 
+/// Like [`find_next`], but skips backslash-escaped characters.
+fn find_unescaped(hay: &[char], mut i: usize, ch: char) -> Option<usize> {
+    while i < hay.len() {
+        if hay[i] == '\\' && i + 1 < hay.len() && is_escapable(hay[i + 1]) {
+            i += 2;
+            continue;
+        }
+        if hay[i] == ch {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
 fn find_delim_run(hay: &[char], start: usize, delim: char, width: usize) -> Option<usize> {
     let mut i = start;
     while i + width <= hay.len() {
+        if hay[i] == '\\' && i + 1 < hay.len() && is_escapable(hay[i + 1]) {
+            i += 2;
+            continue;
+        }
         if hay[i..i + width].iter().all(|c| *c == delim) {
             return Some(i);
         }
@@ -287,24 +691,78 @@ fn find_delim_run(hay: &[char], start: usize, delim: char, width: usize) -> Opti
     None
 }
 
+fn run_len(hay: &[char], start: usize, ch: char) -> usize {
+    hay[start..].iter().take_while(|c| **c == ch).count()
+}
+
+fn find_tilde_pair(hay: &[char], start: usize) -> Option<usize> {
+    let mut from = start;
+    while let Some(end) = find_delim_run(hay, from, '~', 2) {
+        let run = run_len(hay, end, '~');
+        if run == 2 {
+            return Some(end);
+        }
+        from = end + run;
+    }
+    None
+}
+
+/// Split a table row on unescaped pipes outside code spans. `\|` stays
+/// escaped for the inline pass, except inside code where it becomes `|`.
 pub fn split_table_cells(line: &str) -> Vec<String> {
     let mut s = line.trim();
-    if s.starts_with('|') {
-        s = s.trim_start_matches('|');
+    s = s.strip_prefix('|').unwrap_or(s);
+    if s.ends_with('|') && !s.ends_with("\\|") {
+        s = &s[..s.len() - 1];
     }
-    if s.ends_with('|') {
-        s = s.trim_end_matches('|');
+    let chars: Vec<char> = s.chars().collect();
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    // Length of the backtick run that opened the current code span
+    let mut code: Option<usize> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1) == Some(&'|') => {
+                if code.is_none() {
+                    cell.push('\\');
+                }
+                cell.push('|');
+                i += 2;
+                continue;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|c| **c == '`').count();
+                let rest: String = chars[i + run..].iter().collect();
+                code = match code {
+                    // A run only opens a code span if a matching run closes it
+                    None if rest.contains(&"`".repeat(run)) => Some(run),
+                    Some(open) if open == run => None,
+                    other => other,
+                };
+                cell.extend(&chars[i..i + run]);
+                i += run;
+                continue;
+            }
+            '|' if code.is_none() => {
+                cells.push(cell.trim().to_string());
+                cell.clear();
+            }
+            ch => cell.push(ch),
+        }
+        i += 1;
     }
-    s.split('|').map(|c| c.trim().to_string()).collect()
+    cells.push(cell.trim().to_string());
+    cells
 }
 
-pub fn emit_html_event(out: &mut Vec<Event>, content: &str, kind: NodeKind) {
-    out.push(Event::StartNode(kind.clone()));
+pub fn emit_html_event(out: &mut Vec<Event>, content: &str, kind: NodeKind, span: Option<Span>) {
+    out.push(Event::StartNode(kind.clone(), span));
     let text = content.to_string();
     if !text.is_empty() {
-        out.push(Event::Text(text));
+        out.push(Event::Text(text.into(), span));
     }
-    out.push(Event::EndNode(kind));
+    out.push(Event::EndNode(kind, span));
 }
 
 pub fn capture_html_block(line: &str) -> Option<String> {
@@ -315,6 +773,11 @@ pub fn capture_html_block(line: &str) -> Option<String> {
     if !trimmed.starts_with('<') || !trimmed.ends_with('>') {
         return None;
     }
+    // `<https://...>` opens a paragraph with an autolink, not HTML
+    let chars: Vec<char> = trimmed.chars().collect();
+    if autolink_at(&chars, 0).is_some() {
+        return None;
+    }
     if html_like(trimmed) {
         Some(trimmed.to_string())
     } else {
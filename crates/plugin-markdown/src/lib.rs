@@ -1,48 +1,112 @@
-use pendon_core::Event;
+use pendon_core::{
+    aborted_document, depth_exceeded, too_many_events, DiagnosticCode, Event, Plugin, Span,
+};
 
+mod attrs;
+mod autolink;
+mod callout;
 mod context;
 mod end;
+mod footnote;
 mod helpers;
-mod math;
+mod linkref;
 mod start;
 mod text;
 
 use context::ParseContext;
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+/// `[text][ref]` or `[ref][]` names a label no definition provides.
+pub const UNDEFINED_REFERENCE: DiagnosticCode =
+    DiagnosticCode::new("PD0121", "undefined-link-reference");
+
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     process_with_options(events, MarkdownOptions::default())
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct MarkdownOptions {
     pub allow_html: bool,
+    /// Link bare `http://`, `https://` and `www.` URLs (GFM extended
+    /// autolinks). `<url>` autolinks are always recognized.
+    pub extended_autolinks: bool,
+    /// Turn blockquotes opening with `[!KIND]` into `Callout` nodes (GitHub
+    /// alerts, Obsidian callouts); a blank line ends a callout. Off by
+    /// default so `blockquote-sigil` specs matching the same markers work.
+    pub callouts: bool,
+    /// Node nesting limit (see `pendon_core::Limits::max_depth`); nested
+    /// lists and blockquotes past it abort processing.
+    pub max_depth: Option<usize>,
+    /// Output event limit (see `pendon_core::Limits::max_events`), checked
+    /// as events are produced.
+    pub max_events: Option<usize>,
 }
 
-impl Default for MarkdownOptions {
-    fn default() -> Self {
-        Self { allow_html: false }
-    }
-}
-
-pub fn process_with_options(events: &[Event], opts: MarkdownOptions) -> Vec<Event> {
-    let mut ctx = ParseContext::new(events.len(), opts);
-    for ev in events {
+pub fn process_with_options<'a>(events: &[Event<'a>], opts: MarkdownOptions) -> Vec<Event<'a>> {
+    // Definitions may follow their references, so they are read up front
+    let link_refs = linkref::collect(events);
+    let mut skip_newline = false;
+    let mut ctx = ParseContext::new(events.len(), opts, link_refs);
+    for (idx, ev) in events.iter().enumerate() {
+        // Definition lines and their line endings leave no trace
+        if ctx.link_refs.lines.contains(&idx) {
+            skip_newline = true;
+            continue;
+        }
+        if std::mem::take(&mut skip_newline) && matches!(ev, Event::Text(t, _) if t == "\n") {
+            continue;
+        }
         match ev {
-            Event::StartNode(kind) => start::handle(&mut ctx, kind),
-            Event::EndNode(kind) => end::handle(&mut ctx, kind),
-            Event::Text(s) => text::handle(&mut ctx, s),
-            Event::Diagnostic { .. } | Event::Attribute { .. } => ctx.push_event(ev),
+            Event::StartNode(kind, span) => {
+                ctx.span = span.map(|sp| Span::new(sp.start, sp.start));
+                start::handle(&mut ctx, kind)
+            }
+            Event::EndNode(kind, span) => {
+                ctx.span = span.map(|sp| Span::new(sp.end, sp.end));
+                end::handle(&mut ctx, kind)
+            }
+            Event::Text(s, span) => {
+                ctx.span = *span;
+                text::handle(&mut ctx, s)
+            }
+            Event::Diagnostic(_) | Event::Attribute { .. } => ctx.push_event(ev),
+        }
+        // Every open list or quote adds at least one node below `Document`
+        if let Some(max) = opts.max_depth {
+            if 1 + ctx.list_frames.len() + ctx.blockquote_depth > max {
+                let diag = depth_exceeded(max, ctx.span).with_plugin("markdown");
+                return aborted_document(events, diag);
+            }
+        }
+        if let Some(max) = opts.max_events.filter(|max| ctx.out.len() > *max) {
+            let diag = too_many_events(max, ctx.span).with_plugin("markdown");
+            return aborted_document(events, diag);
         }
     }
     ctx.finalize()
 }
 
+/// Registers [`process_with_options`] as the `markdown` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownPlugin {
+    pub options: MarkdownOptions,
+}
+
+impl<H> Plugin<H> for MarkdownPlugin {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process_with_options(events, self.options)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pendon_core::{parse, Event, NodeKind, Options};
+    use pendon_core::{parse, AttrValue, Event, NodeKind, Options};
 
-    fn run_markdown(src: &str, opts: MarkdownOptions) -> Vec<Event> {
+    fn run_markdown(src: &str, opts: MarkdownOptions) -> Vec<Event<'_>> {
         let events = parse(src, &Options::default());
         process_with_options(&events, opts)
     }
@@ -51,9 +115,9 @@ mod tests {
         let mut iter = events.iter();
         while let Some(ev) = iter.next() {
             match ev {
-                Event::StartNode(k) if *k == kind => {
-                    if let Some(Event::Text(text)) = iter.next() {
-                        return Some(text.clone());
+                Event::StartNode(k, _) if *k == kind => {
+                    if let Some(Event::Text(text, _)) = iter.next() {
+                        return Some(text.to_string());
                     }
                 }
                 _ => {}
@@ -65,16 +129,19 @@ mod tests {
     fn has_line_break(events: &[Event]) -> bool {
         events
             .iter()
-            .any(|ev| matches!(ev, Event::StartNode(NodeKind::HtmlInline)))
+            .any(|ev| matches!(ev, Event::StartNode(NodeKind::HtmlInline, _)))
     }
 
     #[test]
     fn html_block_is_emitted_when_allowed() {
-        let opts = MarkdownOptions { allow_html: true };
+        let opts = MarkdownOptions {
+            allow_html: true,
+            ..MarkdownOptions::default()
+        };
         let events = run_markdown("<div>ok</div>\n", opts);
         assert!(events
             .iter()
-            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlBlock))));
+            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlBlock, _))));
         assert_eq!(
             html_text(&events, NodeKind::HtmlBlock).unwrap(),
             "<div>ok</div>"
@@ -83,23 +150,26 @@ mod tests {
 
     #[test]
     fn html_inline_is_emitted_inside_text() {
-        let opts = MarkdownOptions { allow_html: true };
+        let opts = MarkdownOptions {
+            allow_html: true,
+            ..MarkdownOptions::default()
+        };
         let events = run_markdown("before <span>inline</span> after\n", opts);
         assert!(events
             .iter()
-            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlInline))));
+            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlInline, _))));
     }
 
     #[test]
     fn double_space_line_break_inserts_br() {
-        let opts = MarkdownOptions { allow_html: false };
+        let opts = MarkdownOptions::default();
         let events = run_markdown("line  \nnext\n", opts);
         assert!(has_line_break(&events));
     }
 
     #[test]
     fn double_backslash_line_break_inserts_br() {
-        let opts = MarkdownOptions { allow_html: false };
+        let opts = MarkdownOptions::default();
         let events = run_markdown("line\\\\\nnext\n", opts);
         assert!(has_line_break(&events));
     }
@@ -110,7 +180,7 @@ mod tests {
         let events = run_markdown("<div>ok</div>\n", opts);
         assert!(!events
             .iter()
-            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlBlock))));
+            .any(|e| matches!(e, Event::StartNode(NodeKind::HtmlBlock, _))));
     }
 
     #[test]
@@ -123,9 +193,13 @@ mod tests {
         let mut src = None::<String>;
         for ev in events {
             match ev {
-                Event::StartNode(NodeKind::Image) => found = true,
-                Event::Attribute { name, value } if name == "alt" => alt = Some(value),
-                Event::Attribute { name, value } if name == "src" => src = Some(value),
+                Event::StartNode(NodeKind::Image, _) => found = true,
+                Event::Attribute { name, value, .. } if name == "alt" => {
+                    alt = Some(value.to_string())
+                }
+                Event::Attribute { name, value, .. } if name == "src" => {
+                    src = Some(value.to_string())
+                }
                 _ => {}
             }
         }
@@ -142,7 +216,7 @@ mod tests {
 
         assert!(!events
             .iter()
-            .any(|e| matches!(e, Event::StartNode(NodeKind::Image))));
+            .any(|e| matches!(e, Event::StartNode(NodeKind::Image, _))));
     }
 
     #[test]
@@ -154,8 +228,12 @@ mod tests {
         let mut title = None::<String>;
         for ev in events {
             match ev {
-                Event::Attribute { name, value } if name == "href" => href = Some(value),
-                Event::Attribute { name, value } if name == "title" => title = Some(value),
+                Event::Attribute { name, value, .. } if name == "href" => {
+                    href = Some(value.to_string())
+                }
+                Event::Attribute { name, value, .. } if name == "title" => {
+                    title = Some(value.to_string())
+                }
                 _ => {}
             }
         }
@@ -173,8 +251,12 @@ mod tests {
         let mut title = None::<String>;
         for ev in events {
             match ev {
-                Event::Attribute { name, value } if name == "src" => src = Some(value),
-                Event::Attribute { name, value } if name == "title" => title = Some(value),
+                Event::Attribute { name, value, .. } if name == "src" => {
+                    src = Some(value.to_string())
+                }
+                Event::Attribute { name, value, .. } if name == "title" => {
+                    title = Some(value.to_string())
+                }
                 _ => {}
             }
         }
@@ -189,13 +271,13 @@ mod tests {
         let events = run_markdown("Foo ***bar*** baz.\n", opts);
 
         assert!(events.windows(7).any(|window| {
-            matches!(window[0], Event::StartNode(NodeKind::Strong))
-                && matches!(window[1], Event::StartNode(NodeKind::Emphasis))
-                && matches!(window[2], Event::Text(ref text) if text == "b")
-                && matches!(window[3], Event::Text(ref text) if text == "a")
-                && matches!(window[4], Event::Text(ref text) if text == "r")
-                && matches!(window[5], Event::EndNode(NodeKind::Emphasis))
-                && matches!(window[6], Event::EndNode(NodeKind::Strong))
+            matches!(window[0], Event::StartNode(NodeKind::Strong, _))
+                && matches!(window[1], Event::StartNode(NodeKind::Emphasis, _))
+                && matches!(window[2], Event::Text(ref text, _) if text == "b")
+                && matches!(window[3], Event::Text(ref text, _) if text == "a")
+                && matches!(window[4], Event::Text(ref text, _) if text == "r")
+                && matches!(window[5], Event::EndNode(NodeKind::Emphasis, _))
+                && matches!(window[6], Event::EndNode(NodeKind::Strong, _))
         }));
     }
 
@@ -203,31 +285,33 @@ mod tests {
     fn keeps_inline_link_inside_list_item_for_preprocessed_events() {
         let opts = MarkdownOptions::default();
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::Text("- ".to_string()),
-            Event::StartNode(NodeKind::Link),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("- ".into(), None),
+            Event::StartNode(NodeKind::Link, None),
             Event::Attribute {
-                name: "href".to_string(),
-                value: "/id/wiki/Foo".to_string(),
+                name: "href".into(),
+                value: "/id/wiki/Foo".into(),
+                span: None,
             },
             Event::Attribute {
-                name: "title".to_string(),
-                value: "Foo".to_string(),
+                name: "title".into(),
+                value: "Foo".into(),
+                span: None,
             },
-            Event::Text("Foo".to_string()),
-            Event::EndNode(NodeKind::Link),
-            Event::Text(": bar".to_string()),
-            Event::Text("\n".to_string()),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("Foo".into(), None),
+            Event::EndNode(NodeKind::Link, None),
+            Event::Text(": bar".into(), None),
+            Event::Text("\n".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process_with_options(&events, opts);
 
         assert!(out.windows(3).any(|w| {
-            matches!(w[0], Event::StartNode(NodeKind::ListItem))
-                && matches!(w[1], Event::StartNode(NodeKind::Link))
+            matches!(w[0], Event::StartNode(NodeKind::ListItem, _))
+                && matches!(w[1], Event::StartNode(NodeKind::Link, _))
                 && matches!(w[2], Event::Attribute { ref name, .. } if name == "href")
         }));
     }
@@ -239,19 +323,19 @@ mod tests {
 
         let bullet_starts = events
             .iter()
-            .filter(|ev| matches!(ev, Event::StartNode(NodeKind::BulletList)))
+            .filter(|ev| matches!(ev, Event::StartNode(NodeKind::BulletList, _)))
             .count();
         let bullet_ends = events
             .iter()
-            .filter(|ev| matches!(ev, Event::EndNode(NodeKind::BulletList)))
+            .filter(|ev| matches!(ev, Event::EndNode(NodeKind::BulletList, _)))
             .count();
 
         let mut saw_list_end = false;
         let mut saw_paragraph_after_list = false;
         for ev in &events {
             match ev {
-                Event::EndNode(NodeKind::BulletList) => saw_list_end = true,
-                Event::StartNode(NodeKind::Paragraph) if saw_list_end => {
+                Event::EndNode(NodeKind::BulletList, _) => saw_list_end = true,
+                Event::StartNode(NodeKind::Paragraph, _) if saw_list_end => {
                     saw_paragraph_after_list = true;
                     break;
                 }
@@ -267,13 +351,461 @@ mod tests {
     fn has_node(events: &[Event], kind: NodeKind) -> bool {
         events
             .iter()
-            .any(|ev| matches!(ev, Event::StartNode(k) if *k == kind))
+            .any(|ev| matches!(ev, Event::StartNode(k, _) if *k == kind))
     }
 
     fn text_contains(events: &[Event], needle: &str) -> bool {
         events
             .iter()
-            .any(|ev| matches!(ev, Event::Text(t) if t.contains(needle)))
+            .any(|ev| matches!(ev, Event::Text(t, _) if t.contains(needle)))
+    }
+
+    fn plain_text(events: &[Event]) -> String {
+        events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Text(t, _) => Some(t.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn backslash_escapes_suppress_inline_and_block_markup() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("\\# \\*a\\* \\[b\\](c) \\$5\n", opts);
+        assert!(!has_node(&events, NodeKind::Heading));
+        assert!(!has_node(&events, NodeKind::Italic));
+        assert!(!has_node(&events, NodeKind::Link));
+        // `\$` is left for the math plugins
+        assert_eq!(plain_text(&events), "# *a* [b](c) \\$5");
+    }
+
+    #[test]
+    fn entities_decode_in_text_and_link_targets() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("&copy; &nope; [a\\]](u\\)&amp;v \"t\\\"\")\n", opts);
+        assert!(plain_text(&events).starts_with("© &nope; a]"));
+        let href = events.iter().find_map(|ev| match ev {
+            Event::Attribute { name, value, .. } if name == "href" => Some(value.to_string()),
+            _ => None,
+        });
+        assert_eq!(href.as_deref(), Some("u)&v"));
+    }
+
+    fn code_blocks(events: &[Event]) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut current: Option<String> = None;
+        for ev in events {
+            match ev {
+                Event::StartNode(NodeKind::CodeFence, _) => current = Some(String::new()),
+                Event::EndNode(NodeKind::CodeFence, _) => blocks.extend(current.take()),
+                Event::Text(t, _) => {
+                    if let Some(code) = current.as_mut() {
+                        code.push_str(t);
+                    }
+                }
+                Event::Attribute { name, .. } if name == "lang" => panic!("unexpected lang"),
+                _ => {}
+            }
+        }
+        blocks
+    }
+
+    #[test]
+    fn setext_underlines_turn_paragraphs_into_headings() {
+        let opts = MarkdownOptions::default();
+        let src =
+            "---\nfront: x\n---\n\nTitle\n===\nbody\n\nSub\n---\n\n> q\n> --\n\n- item\n---\n";
+        let events = run_markdown(src, opts);
+        assert!(pendon_core::validate(&events).is_empty());
+        let levels = attr_values(&events, "level");
+        assert_eq!(levels, ["1", "2", "2"]);
+        let headings = attrs_of(&events, NodeKind::Heading).len();
+        assert_eq!(headings, 3);
+        // The frontmatter block and the break after a list keep their breaks
+        let breaks = events
+            .iter()
+            .filter(|ev| matches!(ev, Event::StartNode(NodeKind::ThematicBreak, _)))
+            .count();
+        assert_eq!(breaks, 3);
+        assert!(plain_text(&events).contains("TitlebodySubq"));
+    }
+
+    #[test]
+    fn indented_lines_become_code_without_lang() {
+        let opts = MarkdownOptions::default();
+        let src = "p\n\n    a *b*\n\n\n    \t# c\n    ---\n\n\nafter\n";
+        let events = run_markdown(src, opts);
+        assert_eq!(code_blocks(&events), vec!["a *b*\n\n\n\t# c\n---\n"]);
+        assert!(!has_node(&events, NodeKind::Heading));
+        assert!(!has_node(&events, NodeKind::ThematicBreak));
+        assert!(plain_text(&events).ends_with("after"));
+    }
+
+    #[test]
+    fn indented_line_does_not_interrupt_a_paragraph() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("p\n    lazy\n", opts);
+        assert!(code_blocks(&events).is_empty());
+    }
+
+    #[test]
+    fn indented_lines_in_lists_continue_the_item_or_hold_code() {
+        let opts = MarkdownOptions::default();
+        let lazy = run_markdown("- a\n    b\n", opts);
+        assert!(code_blocks(&lazy).is_empty());
+        let items = |events: &[Event]| {
+            events
+                .iter()
+                .filter(|e| matches!(e, Event::StartNode(NodeKind::ListItem, _)))
+                .count()
+        };
+        assert_eq!(items(&lazy), 1);
+
+        // After a blank line, the item's content column plus four starts code
+        let events = run_markdown("1. a\n\n   b\n\n       code\n2. c\n", opts);
+        assert_eq!(code_blocks(&events), vec!["code\n"]);
+        assert_eq!(items(&events), 2);
+        let kinds: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::StartNode(k, _) | Event::EndNode(k, _) if *k != NodeKind::Document => {
+                    Some(k.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            kinds[..7],
+            [
+                NodeKind::OrderedList,
+                NodeKind::ListItem,
+                NodeKind::Paragraph,
+                NodeKind::Paragraph,
+                NodeKind::CodeFence,
+                NodeKind::CodeFence,
+                NodeKind::ListItem,
+            ]
+        );
+    }
+
+    #[test]
+    fn task_markers_become_item_attributes() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("- [x] done\n- [ ] todo\n- [y] text\n", opts);
+        let attrs: Vec<_> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Attribute { name, value, .. } => Some((name.as_ref(), value.clone())),
+                _ => None,
+            })
+            .collect();
+        let set = AttrValue::Bool(true);
+        assert_eq!(
+            attrs,
+            [
+                ("task", set.clone()),
+                ("checked", set.clone()),
+                ("task", set)
+            ]
+        );
+        assert_eq!(plain_text(&events), "donetodo[y] text");
+    }
+
+    #[test]
+    fn double_tildes_mark_strikethrough() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("a ~~b **c**~~ ~~~~ \\~~d~~\n", opts);
+        let starts = events
+            .iter()
+            .filter(|e| matches!(e, Event::StartNode(NodeKind::Strikethrough, _)))
+            .count();
+        assert_eq!(starts, 1);
+        assert!(has_node(&events, NodeKind::Strong));
+        assert!(plain_text(&events).ends_with("~~~~ ~~d~~"));
+    }
+
+    fn attrs_of(events: &[Event], kind: NodeKind) -> Vec<Vec<(String, String)>> {
+        let mut nodes = Vec::new();
+        let mut iter = events.iter().peekable();
+        while let Some(ev) = iter.next() {
+            if !matches!(ev, Event::StartNode(k, _) if *k == kind) {
+                continue;
+            }
+            let mut attrs = Vec::new();
+            while let Some(Event::Attribute { name, value, .. }) = iter.peek() {
+                attrs.push((name.to_string(), value.to_string()));
+                iter.next();
+            }
+            nodes.push(attrs);
+        }
+        nodes
+    }
+
+    #[test]
+    fn footnotes_are_numbered_by_first_reference_and_moved_to_the_end() {
+        let opts = MarkdownOptions::default();
+        let src = "A[^b] B[^A] C[^b] [^none]\n\n[^a]: first\n    more\n[^b]: second\n[^unused]: x\n\ntail\n";
+        let events = run_markdown(src, opts);
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert_eq!(
+            attrs_of(&events, NodeKind::FootnoteReference),
+            [
+                vec![pair("label", "b"), pair("index", "1"), pair("ref", "1")],
+                vec![pair("label", "A"), pair("index", "2"), pair("ref", "1")],
+                vec![pair("label", "b"), pair("index", "1"), pair("ref", "2")],
+            ]
+        );
+        assert_eq!(
+            attrs_of(&events, NodeKind::FootnoteDefinition),
+            [
+                vec![pair("index", "1"), pair("refs", "2"), pair("label", "b")],
+                vec![pair("index", "2"), pair("refs", "1"), pair("label", "a")],
+            ]
+        );
+        assert!(events.iter().any(|ev| matches!(
+            ev,
+            Event::Attribute { name, value: AttrValue::Int(2), .. } if name == "refs"
+        )));
+        assert!(plain_text(&events).ends_with("[^none]tailsecondfirst\nmore"));
+        assert!(matches!(
+            &events[events.len() - 2],
+            Event::EndNode(NodeKind::FootnoteDefinition, _)
+        ));
+        assert!(pendon_core::validate(&events).is_empty());
+    }
+
+    fn attr_values(events: &[Event], key: &str) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Attribute { name, value, .. } if name == key => Some(value.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reference_links_resolve_against_later_definitions() {
+        let opts = MarkdownOptions::default();
+        let src = "[a][Docs], [docs][], [DOCS], ![i][pic] [x]\n\n[docs]: https://x.dev \"T\"\n  [pic]: <a b.png>\n[docs]: /ignored\n";
+        let events = run_markdown(src, opts);
+        assert_eq!(
+            attr_values(&events, "href"),
+            ["https://x.dev", "https://x.dev", "https://x.dev"]
+        );
+        assert_eq!(attr_values(&events, "title"), ["T", "T", "T"]);
+        assert_eq!(attr_values(&events, "src"), ["a b.png"]);
+        assert_eq!(plain_text(&events), "a, docs, DOCS,  [x]");
+        assert!(!events.iter().any(|e| matches!(e, Event::Diagnostic(_))));
+        assert!(pendon_core::validate(&events).is_empty());
+    }
+
+    #[test]
+    fn definitions_do_not_interrupt_a_paragraph() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("text\n[a]: /u\n\n[a]\n", opts);
+        assert!(!has_node(&events, NodeKind::Link));
+        assert!(plain_text(&events).contains("[a]: /u"));
+    }
+
+    #[test]
+    fn undefined_full_reference_is_reported_and_kept_as_text() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("[x][nope] [y][]\n", opts);
+        let codes: Vec<_> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Diagnostic(d) => Some(d.code.id.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(codes, ["PD0121", "PD0121"]);
+        assert_eq!(plain_text(&events), "[x][nope] [y][]");
+    }
+
+    #[test]
+    fn angle_autolinks_are_links_with_or_without_html() {
+        for allow_html in [false, true] {
+            let opts = MarkdownOptions {
+                allow_html,
+                ..MarkdownOptions::default()
+            };
+            let events = run_markdown("<https://a.dev/x> <me@b.org> <span>\n\n<ftp://c>\n", opts);
+            assert_eq!(
+                attr_values(&events, "href"),
+                ["https://a.dev/x", "mailto:me@b.org", "ftp://c"]
+            );
+            assert!(!has_node(&events, NodeKind::HtmlBlock));
+            assert_eq!(
+                events
+                    .iter()
+                    .filter(|e| matches!(e, Event::StartNode(NodeKind::HtmlInline, _)))
+                    .count(),
+                usize::from(allow_html)
+            );
+        }
+    }
+
+    #[test]
+    fn angle_autolinks_skip_script_schemes() {
+        let events = run_markdown(
+            "<javascript:alert(1)> <VBScript:x> <data:text/html,x> <mailto:a@b.c>\n",
+            MarkdownOptions::default(),
+        );
+        assert_eq!(attr_values(&events, "href"), ["mailto:a@b.c"]);
+        assert!(plain_text(&events).contains("<javascript:alert(1)>"));
+    }
+
+    #[test]
+    fn extended_autolinks_skip_code_and_existing_links() {
+        let src =
+            "Go https://x.dev/a(b)). or (www.y.org), `https://code` [https://t](/u) nohttps://z\n";
+        let off = run_markdown(src, MarkdownOptions::default());
+        assert_eq!(attr_values(&off, "href"), ["/u"]);
+
+        let opts = MarkdownOptions {
+            extended_autolinks: true,
+            ..MarkdownOptions::default()
+        };
+        let events = run_markdown(src, opts);
+        assert_eq!(
+            attr_values(&events, "href"),
+            ["https://x.dev/a(b)", "http://www.y.org", "/u"]
+        );
+        assert!(plain_text(&events).contains("a(b)). or (www.y.org), "));
+        assert_eq!(node_span(&events, NodeKind::Link), Some((1, 4, 22)));
+        assert!(pendon_core::validate(&events).is_empty());
+    }
+
+    #[test]
+    fn definition_lists_group_terms_and_definitions() {
+        let src = "Apple\n: A fruit.\n: A company.\n\nOrange\n:   Another fruit,\n    \
+                   lazy.\n\n    More.\n\n    - nested\n\nPlain.\n\nLoose\n\n: Def.\n";
+        let events = run_markdown(src, MarkdownOptions::default());
+        assert!(pendon_core::validate(&events).is_empty());
+        let outline: Vec<String> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::StartNode(kind, _) if *kind != NodeKind::Document => {
+                    Some(format!("<{}", kind.name()))
+                }
+                Event::Text(t, _) => Some(t.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .concat()
+            .split('<')
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            outline,
+            [
+                "",
+                "DefinitionList",
+                "DefinitionTermApple",
+                "DefinitionDescriptionA fruit.",
+                "DefinitionDescriptionA company.",
+                "DefinitionTermOrange",
+                "DefinitionDescriptionAnother fruit,\nlazy.",
+                "ParagraphMore.",
+                "BulletList",
+                "ListItemnested",
+                "ParagraphPlain.",
+                "DefinitionList",
+                "DefinitionTermLoose",
+                "DefinitionDescriptionDef.",
+            ]
+        );
+    }
+
+    #[test]
+    fn callouts_are_opt_in_and_end_at_blank_lines() {
+        let src = "> [!NOTE]\n> Body\n\n> [!info]- Fold *me*\n> a\n\n> plain\n> > [!Tip]+\n> > b\n";
+        let off = run_markdown(src, MarkdownOptions::default());
+        assert!(!has_node(&off, NodeKind::Callout));
+
+        let opts = MarkdownOptions {
+            callouts: true,
+            ..MarkdownOptions::default()
+        };
+        let events = run_markdown(src, opts);
+        assert!(pendon_core::validate(&events).is_empty());
+        let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert_eq!(
+            attrs_of(&events, NodeKind::Callout),
+            [
+                vec![pair("kind", "note")],
+                vec![
+                    pair("kind", "info"),
+                    pair("title", "Fold *me*"),
+                    pair("foldable", "true")
+                ],
+                vec![
+                    pair("kind", "tip"),
+                    pair("foldable", "true"),
+                    pair("open", "true")
+                ],
+            ]
+        );
+        let outline: Vec<String> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::StartNode(kind @ (NodeKind::Callout | NodeKind::Blockquote), _) => {
+                    Some(format!("<{}", kind.name()))
+                }
+                Event::EndNode(kind @ (NodeKind::Callout | NodeKind::Blockquote), _) => {
+                    Some(format!("{}>", kind.name()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            [
+                "<Callout",
+                "Callout>",
+                "<Callout",
+                "Callout>",
+                "<Blockquote",
+                "<Callout",
+                "Callout>",
+                "Blockquote>"
+            ]
+        );
+        assert_eq!(plain_text(&events), "Bodyaplainb");
+    }
+
+    #[test]
+    fn callouts_hold_fences_lists_and_nested_quotes() {
+        let src = "> [!WARNING] Careful\n> ```js\n> let a;\n> ```\n> - one\n> - two\n> > nested\n> after\n";
+        let opts = MarkdownOptions {
+            callouts: true,
+            ..MarkdownOptions::default()
+        };
+        let events = run_markdown(src, opts);
+        assert!(pendon_core::validate(&events).is_empty());
+        let outline: Vec<String> = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::StartNode(kind, _) if *kind != NodeKind::Document => {
+                    Some(format!("<{}", kind.name()))
+                }
+                Event::EndNode(kind, _) if *kind != NodeKind::Document => {
+                    Some(format!("{}>", kind.name()))
+                }
+                Event::Text(t, _) => Some(t.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            outline.concat(),
+            "<Callout<CodeFencelet a;\nCodeFence><BulletList<ListItemoneListItem>\
+             <ListItemtwoListItem>BulletList><Blockquote<ParagraphnestedParagraph>\
+             Blockquote><ParagraphafterParagraph>Callout>"
+        );
+        assert_eq!(attr_values(&events, "lang"), ["js"]);
     }
 
     #[test]
@@ -297,7 +829,7 @@ mod tests {
         let opts = MarkdownOptions::default();
         let src = "$$\\begin{aligned}\nA &= B \\\\\nC &= D\n\\end{aligned}$$\n";
         let events = run_markdown(src, opts);
-        assert!(!html_text(&events, NodeKind::HtmlInline).is_some_and(|h| h == "<br />"));
+        assert!(html_text(&events, NodeKind::HtmlInline).is_none_or(|h| h != "<br />"));
         assert!(text_contains(&events, "\\\\"));
     }
 
@@ -312,7 +844,7 @@ mod tests {
         events
             .iter()
             .filter_map(|ev| match ev {
-                Event::Text(t) => Some(t.as_str()),
+                Event::Text(t, _) => Some(t.as_ref()),
                 _ => None,
             })
             .collect()
@@ -326,4 +858,177 @@ mod tests {
         assert!(text.contains("$100"));
         assert!(text.contains("$500"));
     }
+
+    fn node_span(events: &[Event], kind: NodeKind) -> Option<(usize, usize, usize)> {
+        events.iter().find_map(|ev| match ev {
+            Event::StartNode(k, Some(sp)) if *k == kind => {
+                Some((sp.start.line, sp.start.column, sp.end.column))
+            }
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn inline_and_block_nodes_carry_source_spans() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("> quote\n\n- a **bold** [x](y)\n", opts);
+        assert!(events.iter().all(|ev| ev.span().is_some()));
+        assert_eq!(node_span(&events, NodeKind::Strong), Some((3, 5, 13)));
+        assert_eq!(node_span(&events, NodeKind::Link), Some((3, 14, 20)));
+        let quote_text = events.iter().find_map(|ev| match ev {
+            Event::Text(t, Some(sp)) if t == "q" => Some(sp.start.column),
+            _ => None,
+        });
+        assert_eq!(quote_text, Some(3));
+    }
+
+    #[test]
+    fn nested_fence_markers_are_kept_as_code() {
+        let opts = MarkdownOptions::default();
+        let events = run_markdown("````\n```\n````\n", opts);
+        let code: String = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Text(t, _) => Some(t.as_ref()),
+                _ => None,
+            })
+            .collect();
+        assert!(code.starts_with("```\n"));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, Event::StartNode(NodeKind::Paragraph, _))));
+    }
+
+    #[test]
+    fn code_fence_closes_open_table_and_quote() {
+        let src = "| A | B |\n|---|---|\n| 1 | 2 |\n\n```\nx\n```\n\n> q\n\n```\ny\n```\n";
+        let events = run_markdown(src, MarkdownOptions::default());
+        assert!(pendon_core::validate(&events).is_empty());
+        let mut open = Vec::new();
+        for ev in &events {
+            match ev {
+                Event::StartNode(NodeKind::CodeFence, _) => {
+                    assert_eq!(open, [NodeKind::Document]);
+                }
+                Event::StartNode(kind, _) => open.push(kind.clone()),
+                Event::EndNode(NodeKind::CodeFence, _) => {}
+                Event::EndNode(_, _) => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn table_cells_carry_column_alignment_and_keep_escaped_pipes() {
+        let src = "| L | C | R | N |\n|:--|:-:|--:|---|\n| a \\| b | `x|y` | 3 | 4 |\n";
+        let events = run_markdown(src, MarkdownOptions::default());
+        assert!(pendon_core::validate(&events).is_empty());
+        let align: Vec<Option<String>> = attrs_of(&events, NodeKind::TableCell)
+            .into_iter()
+            .map(|attrs| {
+                attrs
+                    .into_iter()
+                    .find(|(k, _)| k == "align")
+                    .map(|(_, v)| v)
+            })
+            .collect();
+        let expected = [Some("left"), Some("center"), Some("right"), None];
+        let expected: Vec<Option<String>> = expected
+            .iter()
+            .chain(&expected)
+            .map(|a| a.map(str::to_string))
+            .collect();
+        assert_eq!(align, expected);
+        assert_eq!(plain_text(&events), "LCRNa | bx|y34");
+    }
+
+    #[test]
+    fn deep_nesting_past_max_depth_aborts() {
+        let src: String = (0..200)
+            .map(|i| format!("{}- item\n", "  ".repeat(i)))
+            .collect();
+        let opts = MarkdownOptions {
+            max_depth: Some(8),
+            ..MarkdownOptions::default()
+        };
+        let events = run_markdown(&src, opts);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[1],
+            Event::Diagnostic(d) if d.code == pendon_core::codes::NESTING_TOO_DEEP
+                && d.plugin.as_deref() == Some("markdown")
+        ));
+
+        let shallow = run_markdown("- a\n  - b\n", opts);
+        assert!(!shallow.iter().any(|e| matches!(e, Event::Diagnostic(_))));
+    }
+
+    #[test]
+    fn output_past_max_events_aborts_early() {
+        let src = "*a* *b* *c*\n\n- x\n- y\n";
+        let all = run_markdown(src, MarkdownOptions::default());
+        let opts = MarkdownOptions {
+            max_events: Some(8),
+            ..MarkdownOptions::default()
+        };
+        let events = run_markdown(src, opts);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[1],
+            Event::Diagnostic(d) if d.code == pendon_core::codes::TOO_MANY_EVENTS
+                && d.plugin.as_deref() == Some("markdown")
+        ));
+
+        let roomy = MarkdownOptions {
+            max_events: Some(all.len()),
+            ..MarkdownOptions::default()
+        };
+        assert_eq!(run_markdown(src, roomy), all);
+    }
+
+    #[test]
+    fn attribute_blocks_attach_to_their_nodes() {
+        let src = "## Title {#top .lead}\n\nBody text {.note data-x=\"a b\"}\n\n\
+                   See [a](/x){.btn} and ![i](p.png){width=50}.\n\n\
+                   ```rust {#ex .numbered}\nfn main() {}\n```\n\n{.plain}\n";
+        let events = run_markdown(src, MarkdownOptions::default());
+        let pairs = |items: &[(&str, &str)]| -> Vec<(String, String)> {
+            items
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        assert!(attrs_of(&events, NodeKind::Heading)[0].ends_with(&pairs(&[
+            ("level", "2"),
+            ("id", "top"),
+            ("class", "lead")
+        ])));
+        assert_eq!(
+            attrs_of(&events, NodeKind::Paragraph)[0],
+            pairs(&[("class", "note"), ("data-x", "a b")])
+        );
+        assert_eq!(
+            attrs_of(&events, NodeKind::Link),
+            [pairs(&[("href", "/x"), ("class", "btn")])]
+        );
+        assert_eq!(
+            attrs_of(&events, NodeKind::Image),
+            [pairs(&[("alt", "i"), ("src", "p.png"), ("width", "50")])]
+        );
+        assert_eq!(
+            attrs_of(&events, NodeKind::CodeFence),
+            [pairs(&[
+                ("lang", "rust"),
+                ("id", "ex"),
+                ("class", "numbered")
+            ])]
+        );
+        // A paragraph of nothing but braces, and code, keep their text
+        assert_eq!(
+            plain_text(&events),
+            "TitleBody textSee a and .fn main() {}\n{.plain}"
+        );
+    }
 }
@@ -0,0 +1,111 @@
+use std::collections::{HashMap, HashSet};
+
+use pendon_core::{Event, NodeKind};
+
+use crate::helpers::parse_target_and_title;
+
+/// Destination and optional title of a `[label]: url "title"` definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkTarget {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Link reference definitions of a document, gathered before any inline
+/// content is parsed so a reference may precede its definition.
+#[derive(Debug, Default)]
+pub struct LinkRefs {
+    targets: HashMap<String, LinkTarget>,
+    /// Indices of the input text events holding definition lines.
+    pub lines: HashSet<usize>,
+}
+
+impl LinkRefs {
+    pub fn get(&self, label: &str) -> Option<&LinkTarget> {
+        self.targets.get(&normalize(label))
+    }
+}
+
+// Labels match case-insensitively with inner whitespace collapsed
+fn normalize(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A definition line `[label]: url "title"`, indented at most three
+/// spaces: the label and its target. Footnote labels (`[^x]`) are not links.
+pub fn definition(line: &str) -> Option<(&str, LinkTarget)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line[indent..].strip_prefix('[')?;
+    if rest.starts_with('^') {
+        return None;
+    }
+    let mut close = None;
+    let mut escaped = false;
+    for (idx, ch) in rest.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => return None,
+            ']' => {
+                close = Some(idx);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let label = &rest[..close];
+    let target = rest[close + 1..].strip_prefix(':')?.trim();
+    if label.trim().is_empty() || target.is_empty() {
+        return None;
+    }
+    let (url, title) = parse_target_and_title(target);
+    // A bare destination cannot contain spaces
+    if url.is_empty() || (url.contains(char::is_whitespace) && !target.starts_with('<')) {
+        return None;
+    }
+    Some((label, LinkTarget { url, title }))
+}
+
+/// Collect definitions from the raw parser events. A definition line must
+/// open a paragraph or follow another definition line, and the first
+/// definition of a label wins.
+pub fn collect(events: &[Event]) -> LinkRefs {
+    let mut refs = LinkRefs::default();
+    let mut open: Vec<&NodeKind> = Vec::new();
+    let mut eligible = false;
+    for (idx, ev) in events.iter().enumerate() {
+        match ev {
+            Event::StartNode(kind, _) => {
+                open.push(kind);
+                eligible = *kind == NodeKind::Paragraph;
+            }
+            Event::EndNode(_, _) => {
+                open.pop();
+                eligible = false;
+            }
+            Event::Text(text, _) if text == "\n" => {}
+            Event::Text(text, _) => {
+                let in_paragraph = open.last() == Some(&&NodeKind::Paragraph)
+                    && !open.contains(&&NodeKind::Heading);
+                let def = definition(text).filter(|_| eligible && in_paragraph);
+                match def {
+                    Some((label, target)) => {
+                        refs.lines.insert(idx);
+                        refs.targets.entry(normalize(label)).or_insert(target);
+                    }
+                    None => eligible = false,
+                }
+            }
+            Event::Attribute { .. } | Event::Diagnostic(_) => {}
+        }
+    }
+    refs
+}
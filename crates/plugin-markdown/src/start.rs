@@ -1,4 +1,4 @@
-use pendon_core::NodeKind;
+use pendon_core::{Event, NodeKind};
 
 use crate::context::ParseContext;
 
@@ -11,6 +11,8 @@ fn is_inline_node(kind: &NodeKind) -> bool {
             | NodeKind::Link
             | NodeKind::Bold
             | NodeKind::Italic
+            | NodeKind::Strikethrough
+            | NodeKind::FootnoteReference
             | NodeKind::HtmlInline
             | NodeKind::Image
     )
@@ -19,8 +21,10 @@ fn is_inline_node(kind: &NodeKind) -> bool {
 pub fn handle(ctx: &mut ParseContext, kind: &NodeKind) {
     match kind {
         NodeKind::Heading => {
+            ctx.close_indented_code();
             ctx.close_blockquotes();
             ctx.close_all_lists();
+            ctx.close_table_if_open();
             ctx.emit_start(NodeKind::Heading);
             ctx.in_heading = true;
             ctx.heading_prefix_consumed = false;
@@ -28,12 +32,29 @@ pub fn handle(ctx: &mut ParseContext, kind: &NodeKind) {
             ctx.skip_para_close = ctx.skip_para_close.saturating_add(1);
         }
         NodeKind::CodeFence => {
+            ctx.close_indented_code();
+            ctx.close_blockquotes();
             ctx.close_all_lists();
+            ctx.close_table_if_open();
             ctx.emit_start(NodeKind::CodeFence);
             ctx.in_code_fence = true;
             ctx.skip_initial_code_newline = true;
-            ctx.skip_para_open = ctx.skip_para_open.saturating_add(1);
-            ctx.skip_para_close = ctx.skip_para_close.saturating_add(1);
+        }
+        // A `---` line right after paragraph text underlines a heading
+        NodeKind::ThematicBreak if ctx.blockquote_depth == 0 && ctx.setext_heading(2) => {
+            ctx.setext_break = true;
+        }
+        NodeKind::ThematicBreak => {
+            let document_start = ctx
+                .out
+                .iter()
+                .all(|ev| matches!(ev, Event::StartNode(NodeKind::Document, _)));
+            ctx.in_frontmatter = !ctx.in_frontmatter && document_start;
+            ctx.close_indented_code();
+            ctx.close_blockquotes();
+            ctx.close_all_lists();
+            ctx.close_table_if_open();
+            ctx.emit_start(NodeKind::ThematicBreak);
         }
         NodeKind::Paragraph => {
             if ctx.skip_para_open > 0 {
@@ -45,6 +66,7 @@ pub fn handle(ctx: &mut ParseContext, kind: &NodeKind) {
         }
         _ => {
             if !is_inline_node(kind) {
+                ctx.close_indented_code();
                 ctx.close_blockquotes();
                 ctx.close_all_lists();
                 ctx.close_table_if_open();
@@ -1,39 +1,195 @@
-use pendon_core::{Event, NodeKind};
+use std::borrow::Cow;
 
+use pendon_core::{toggle_display_math_on_line, unescape, AttrValue, Event, NodeKind, Span};
+
+use crate::callout;
 use crate::context::ParseContext;
+use crate::footnote;
 use crate::helpers::{
-    adjust_blockquote, capture_html_block, close_table, emit_html_event, emit_inline,
-    emit_table_row, is_table_row, is_table_separator, parse_blockquote_prefix, split_table_cells,
-    start_table,
+    align_table_head, capture_html_block, close_table, definition_marker, emit_html_event,
+    emit_inline, emit_table_row, fence_open, find_span, indent_width, is_table_row,
+    is_table_separator, parse_blockquote_prefix, setext_level, split_table_cells, start_table,
+    strip_blockquote_prefix, strip_indent, suffix_span, table_alignments,
 };
-use crate::math::toggle_display_math_on_line;
 
-fn emit_line_content(ctx: &mut ParseContext, line: &str) {
+fn emit_line_content(ctx: &mut ParseContext, line: &str, span: Option<Span>) {
     if ctx.display_math_open {
-        ctx.out.push(Event::Text(line.to_string()));
+        ctx.out.push(Event::Text(line.to_string().into(), span));
     } else {
-        emit_inline(line, &mut ctx.out, ctx.options);
+        emit_inline(line, &mut ctx.out, ctx.options, &ctx.link_refs, span);
     }
     toggle_display_math_on_line(line, &mut ctx.display_math_open);
 }
 
+// A GFM task marker (`[ ] `, `[x] `) opening an item becomes `task` and
+// `checked` attributes; returns the item text after it
+fn take_task_marker<'l>(ctx: &mut ParseContext, tail: &'l str, span: Option<Span>) -> &'l str {
+    let checked = match tail.get(..4) {
+        Some("[ ] ") => false,
+        Some("[x] " | "[X] ") => true,
+        _ => return tail,
+    };
+    let marker = span.map(|sp| sp.slice(tail, 0..3));
+    ctx.out.push(Event::Attribute {
+        name: "task".into(),
+        value: true.into(),
+        span: marker,
+    });
+    if checked {
+        ctx.out.push(Event::Attribute {
+            name: "checked".into(),
+            value: true.into(),
+            span: marker,
+        });
+    }
+    &tail[4..]
+}
+
+// A `[^label]: text` line opens a footnote definition, closing the previous
+// one; other lines of the same paragraph continue it. Returns whether the
+// line was taken.
+fn footnote_line(ctx: &mut ParseContext, line: &str, span: Option<Span>) -> bool {
+    let open = matches!(ctx.stack.last(), Some(NodeKind::FootnoteDefinition));
+    if ctx.blockquote_depth > 0 || !ctx.list_frames.is_empty() {
+        if open {
+            ctx.close_footnote();
+        }
+        return false;
+    }
+    if let Some((label, text)) = footnote::definition(line) {
+        ctx.close_footnote();
+        ctx.close_table_if_open();
+        if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
+            ctx.emit_end(NodeKind::Paragraph);
+        }
+        ctx.pending_para_start = false;
+        ctx.emit_start(NodeKind::FootnoteDefinition);
+        ctx.out.push(Event::Attribute {
+            name: "label".into(),
+            value: label.to_string().into(),
+            span: span.map(|sp| sp.slice(line, 2..2 + label.len())),
+        });
+        if !text.is_empty() {
+            emit_line_content(ctx, text, suffix_span(span, line, text));
+        }
+    } else if open {
+        let at = ctx.point();
+        ctx.out.push(Event::Text("\n".into(), at));
+        let text = line.trim_start();
+        emit_line_content(ctx, text, suffix_span(span, line, text));
+    } else {
+        return false;
+    }
+    ctx.at_line_start = false;
+    true
+}
+
+// Columns to strip if `line` opens an indented code block: four past the
+// content of the enclosing list item, which needs a blank line first, as do
+// quotes and tables. An indented line never interrupts a paragraph.
+fn indented_code_start(ctx: &ParseContext, line: &str, after_blank: bool) -> Option<usize> {
+    if line.trim().is_empty()
+        || matches!(
+            ctx.stack.last(),
+            Some(NodeKind::Paragraph | NodeKind::FootnoteDefinition)
+        )
+    {
+        return None;
+    }
+    let base = match ctx.current_item_content_indent() {
+        Some(content) if after_blank => content,
+        Some(_) => return None,
+        None if (ctx.blockquote_depth > 0 || ctx.in_table) && !after_blank => return None,
+        None => 0,
+    };
+    (indent_width(line) >= base + 4).then_some(base + 4)
+}
+
+// Add a line to the open indented code block; false if the line ends it
+fn continue_indented_code(ctx: &mut ParseContext, s: &str) -> bool {
+    let Some(strip) = ctx.indented_code else {
+        return false;
+    };
+    if s.trim().is_empty() {
+        // Counted as blank by the newline that follows
+        return true;
+    }
+    if indent_width(s) < strip {
+        ctx.close_indented_code();
+        ctx.pending_para_start = true;
+        return false;
+    }
+    for span in std::mem::take(&mut ctx.code_blank_lines) {
+        ctx.out.push(Event::Text("\n".into(), span));
+    }
+    let content = strip_indent(s, strip);
+    let span = match &content {
+        Cow::Borrowed(part) => suffix_span(ctx.span, s, part),
+        Cow::Owned(_) => ctx.span,
+    };
+    ctx.out.push(Event::Text(content.into_owned().into(), span));
+    ctx.pending_para_start = false;
+    ctx.at_line_start = false;
+    true
+}
+
+// A line of the code fence open inside a quote, or the run closing it.
+// A line outside the fence's quote ends the fence and is not taken.
+fn quote_fence_line(ctx: &mut ParseContext, s: &str) -> bool {
+    let Some((depth, run)) = ctx.quote_fence.clone() else {
+        return false;
+    };
+    let Some(content) = strip_blockquote_prefix(s, depth) else {
+        ctx.close_quote_fence();
+        return false;
+    };
+    let close = content.trim();
+    if close.starts_with(&run) && close.chars().all(|c| run.starts_with(c)) {
+        ctx.close_quote_fence();
+    } else if !content.is_empty() {
+        let span = suffix_span(ctx.span, s, content);
+        ctx.out.push(Event::Text(content.to_string().into(), span));
+    }
+    ctx.at_line_start = false;
+    true
+}
+
 pub fn handle(ctx: &mut ParseContext, s: &str) {
     if matches!(
         ctx.stack.last(),
         Some(NodeKind::HtmlBlock | NodeKind::HtmlInline)
     ) {
-        ctx.out.push(Event::Text(s.to_string()));
+        ctx.out.push(Event::Text(s.to_string().into(), ctx.span));
         ctx.at_line_start = s == "\n";
         return;
     }
 
-    if matches!(ctx.stack.last(), Some(NodeKind::ThematicBreak)) {
+    if ctx.setext_break || matches!(ctx.stack.last(), Some(NodeKind::ThematicBreak)) {
         return;
     }
 
     if s == "\n" {
+        if ctx.quote_fence.is_some() {
+            if !std::mem::take(&mut ctx.skip_initial_code_newline) {
+                ctx.out.push(Event::Text("\n".into(), ctx.span));
+            }
+            ctx.at_line_start = true;
+            return;
+        }
         if ctx.display_math_open {
-            ctx.out.push(Event::Text("\n".to_string()));
+            ctx.out.push(Event::Text("\n".into(), ctx.span));
+            ctx.at_line_start = true;
+            return;
+        }
+        if ctx.indented_code.is_some() {
+            // A newline right after another one ends a blank line
+            let blank = !ctx.code_blank_lines.is_empty()
+                || matches!(ctx.out.last(), Some(Event::Text(t, _)) if t == "\n");
+            if blank {
+                ctx.code_blank_lines.push(ctx.span);
+            } else {
+                ctx.out.push(Event::Text("\n".into(), ctx.span));
+            }
             ctx.at_line_start = true;
             return;
         }
@@ -46,73 +202,157 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
             if ctx.skip_initial_code_newline {
                 ctx.skip_initial_code_newline = false;
             } else {
-                ctx.out.push(Event::Text("\n".to_string()));
+                ctx.out.push(Event::Text("\n".into(), ctx.span));
             }
-            ctx.previous_line_blank = false;
             ctx.at_line_start = true;
             return;
         }
         if ctx.in_heading {
-            ctx.previous_line_blank = false;
             ctx.at_line_start = true;
             return;
         }
         if blank_line {
-            ctx.close_all_lists();
+            ctx.after_blank_line = true;
         }
-        ctx.previous_line_blank = true;
         ctx.at_line_start = true;
         return;
     }
 
+    if ctx.at_line_start && quote_fence_line(ctx, s) {
+        return;
+    }
+
+    let mut after_blank = false;
+    if ctx.at_line_start && !ctx.in_heading && !ctx.in_code_fence && !ctx.display_math_open {
+        if continue_indented_code(ctx, s) {
+            return;
+        }
+        after_blank = std::mem::take(&mut ctx.after_blank_line);
+        if after_blank {
+            ctx.close_lists_outside(indent_width(s));
+        }
+        if let Some(strip) = indented_code_start(ctx, s, after_blank) {
+            ctx.close_blockquotes();
+            ctx.close_table_if_open();
+            ctx.open_indented_code(strip);
+            continue_indented_code(ctx, s);
+            return;
+        }
+    }
+
     ctx.capture_line_text(s);
+    // `line` is always a suffix of `s` (prefixes are stripped), so sub-spans
+    // are derived from the end of the input text span
+    let span = ctx.span;
+    let tail_span = |part: &str| suffix_span(span, s, part);
     let mut line = s.to_string();
     let original_line = line.clone();
 
     if ctx.at_line_start && !ctx.in_heading && !ctx.in_code_fence {
-        let previous_line_blank = ctx.previous_line_blank;
-        ctx.previous_line_blank = false;
         let (depth, tail) = parse_blockquote_prefix(&line);
-        if depth > 0 && previous_line_blank && !ctx.list_frames.is_empty() {
-            ctx.close_all_lists();
+        let underline = if depth > 0 { tail } else { line.as_str() };
+        if depth == ctx.blockquote_depth {
+            if let Some(level) = setext_level(underline) {
+                if ctx.setext_heading(level) {
+                    ctx.at_line_start = false;
+                    return;
+                }
+            }
+        }
+        if depth > ctx.blockquote_depth {
+            // A deeper quote left of an item's content ends the list
+            let indent = strip_blockquote_prefix(s, ctx.blockquote_depth).map_or(0, indent_width);
+            ctx.close_lists_outside_quote(indent);
+        }
+        // A quote whose first line is `[!KIND]` is a callout. After a blank
+        // line it closes the quote it would otherwise continue, and a
+        // callout is not continued at all.
+        let fresh = depth > ctx.blockquote_depth || after_blank;
+        let marker = (ctx.options.callouts && depth > 0 && fresh)
+            .then(|| callout::marker(tail))
+            .flatten();
+        let reopen = depth > 0
+            && depth <= ctx.blockquote_depth
+            && (marker.is_some()
+                || (after_blank
+                    && ctx.options.callouts
+                    && ctx.quote_kind(depth) == Some(&NodeKind::Callout)));
+        if reopen {
+            ctx.close_quotes_to(depth - 1);
         }
-        adjust_blockquote(&mut ctx.out, &mut ctx.blockquote_depth, depth);
+        let kind = match marker {
+            Some(_) => NodeKind::Callout,
+            None => NodeKind::Blockquote,
+        };
+        ctx.set_quote_depth(depth, kind);
         if depth > 0 {
             line = tail.to_string();
         } else {
             line = original_line;
         }
+        if let Some(marker) = marker {
+            callout::push_attrs(&mut ctx.out, &marker, tail_span(&line));
+            ctx.at_line_start = false;
+            return;
+        }
+        // The core parser only sees fences outside quotes
+        if let Some((run, info)) = fence_open(&line).filter(|_| depth > 0) {
+            ctx.open_quote_fence(run);
+            let lang = info.trim();
+            if !lang.is_empty() {
+                ctx.out.push(Event::Attribute {
+                    name: "lang".into(),
+                    value: unescape(lang).into_owned().into(),
+                    span: find_span(tail_span(&line), &line, lang),
+                });
+            }
+            ctx.at_line_start = false;
+            return;
+        }
         if ctx.options.allow_html {
             if let Some(html_line) = capture_html_block(&line) {
                 if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
                     ctx.emit_end(NodeKind::Paragraph);
                 }
-                emit_html_event(&mut ctx.out, &html_line, NodeKind::HtmlBlock);
+                let html_span = find_span(tail_span(&line), &line, &html_line);
+                emit_html_event(&mut ctx.out, &html_line, NodeKind::HtmlBlock, html_span);
                 ctx.pending_para_start = false;
                 ctx.at_line_start = false;
                 return;
             }
         }
+        if footnote_line(ctx, &line, tail_span(&line)) {
+            return;
+        }
         let trimmed_for_table = line.trim_start();
 
         if ctx.in_table {
             if is_table_row(trimmed_for_table) {
                 let cells = split_table_cells(trimmed_for_table);
                 if !ctx.first_table_row && is_table_separator(&cells) {
+                    if ctx.table_align.is_none() {
+                        let align = table_alignments(&cells);
+                        align_table_head(&mut ctx.out, &align);
+                        ctx.table_align = Some(align);
+                    }
                     ctx.at_line_start = false;
                     return;
                 }
                 emit_table_row(
                     trimmed_for_table,
                     ctx.first_table_row,
+                    ctx.table_align.as_deref().unwrap_or_default(),
                     &mut ctx.out,
                     ctx.options,
+                    &ctx.link_refs,
+                    tail_span(trimmed_for_table),
                 );
                 ctx.first_table_row = false;
                 ctx.at_line_start = false;
                 return;
             } else {
-                close_table(&mut ctx.out, &mut ctx.in_table);
+                let at = ctx.point();
+                close_table(&mut ctx.out, &mut ctx.in_table, at);
                 ctx.first_table_row = true;
             }
         }
@@ -126,10 +366,22 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
                 if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
                     ctx.emit_end(NodeKind::Paragraph);
                 }
-                start_table(&mut ctx.out);
-                emit_table_row(trimmed_for_table, true, &mut ctx.out, ctx.options);
-                ctx.out.push(Event::EndNode(NodeKind::TableHead));
-                ctx.out.push(Event::StartNode(NodeKind::TableBody));
+                let row_span = tail_span(trimmed_for_table);
+                let at = ctx.point();
+                start_table(&mut ctx.out, at);
+                ctx.table_align = None;
+                emit_table_row(
+                    trimmed_for_table,
+                    true,
+                    &[],
+                    &mut ctx.out,
+                    ctx.options,
+                    &ctx.link_refs,
+                    row_span,
+                );
+                let after = row_span.map(|sp| Span::new(sp.end, sp.end));
+                ctx.out.push(Event::EndNode(NodeKind::TableHead, after));
+                ctx.out.push(Event::StartNode(NodeKind::TableBody, after));
                 ctx.in_table = true;
                 ctx.first_table_row = false;
                 ctx.at_line_start = false;
@@ -137,7 +389,8 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
             }
         }
     } else if ctx.in_table {
-        close_table(&mut ctx.out, &mut ctx.in_table);
+        let at = ctx.point();
+        close_table(&mut ctx.out, &mut ctx.in_table, at);
         ctx.first_table_row = true;
     }
 
@@ -145,10 +398,22 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
         let indent = line.chars().take_while(|c| *c == ' ').count();
         let line = &line[indent..];
 
+        // `: text` defines the term read before it
+        if let Some(width) = definition_marker(line).filter(|_| indent < 4) {
+            if ctx.open_definition(indent) {
+                ctx.start_list_item(indent + width);
+                ctx.pending_para_start = false;
+                let tail = &line[width..];
+                emit_line_content(ctx, tail, tail_span(tail));
+                ctx.at_line_start = false;
+                return;
+            }
+        }
+
         // Ordered list detection
-        let mut chars = line.chars();
+        let chars = line.chars();
         let mut num_str = String::new();
-        while let Some(c) = chars.next() {
+        for c in chars {
             if c.is_ascii_digit() {
                 num_str.push(c);
             } else {
@@ -164,24 +429,17 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
                         ctx.emit_end(NodeKind::Paragraph);
                     }
                     let start_num = num_str.parse::<usize>().ok();
-                    let start_attr = if let Some(n) = start_num {
-                        if !ctx.current_list_start_emitted() {
-                            Some(n)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
+                    let start_attr = start_num.filter(|&_n| !ctx.current_list_start_emitted());
                     ctx.ensure_list(NodeKind::OrderedList, indent, start_attr);
                     if start_attr.is_some() {
                         ctx.mark_current_list_start_emitted();
                     }
-                    ctx.start_list_item();
+                    ctx.start_list_item(indent + consumed + 2);
                     ctx.pending_para_start = false;
                     let tail = &line[(consumed + 2)..];
+                    let tail = take_task_marker(ctx, tail, tail_span(tail));
                     if !tail.is_empty() {
-                        emit_line_content(ctx, tail);
+                        emit_line_content(ctx, tail, tail_span(tail));
                     }
                     ctx.at_line_start = false;
                     return;
@@ -196,27 +454,45 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
                 ctx.emit_end(NodeKind::Paragraph);
             }
             ctx.ensure_list(NodeKind::BulletList, indent, None);
-            ctx.start_list_item();
+            ctx.start_list_item(indent + 2);
             ctx.pending_para_start = false;
             let tail = &line[2..];
+            let tail = take_task_marker(ctx, tail, tail_span(tail));
             if !tail.is_empty() {
-                emit_line_content(ctx, tail);
+                emit_line_content(ctx, tail, tail_span(tail));
             }
             ctx.at_line_start = false;
             return;
         }
 
-        // Continuation line inside current list
-        if ctx.list_frames.last().is_some() {
+        // Continuation line inside current list, unless a quote opened in
+        // its item holds the line
+        let quote_depth = ctx.blockquote_depth;
+        if ctx
+            .list_frames
+            .last()
+            .is_some_and(|f| f.quote_depth == quote_depth)
+        {
             ctx.close_lists_above(indent);
             if ctx.list_frames.last().is_some() {
-                ctx.start_list_item();
-                if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
-                    ctx.emit_end(NodeKind::Paragraph);
+                // The line belongs to the open item: a new paragraph after a
+                // blank line, otherwise another line of the item's text
+                if !ctx.in_list_item() {
+                    ctx.start_list_item(indent);
+                }
+                if after_blank {
+                    if matches!(ctx.stack.last(), Some(NodeKind::Paragraph)) {
+                        ctx.emit_end(NodeKind::Paragraph);
+                    }
+                    ctx.emit_start(NodeKind::Paragraph);
+                } else {
+                    let at = ctx.point();
+                    ctx.out.push(Event::Text("\n".into(), at));
                 }
+                ctx.pending_para_start = false;
                 let tail = line;
                 if !tail.is_empty() {
-                    emit_line_content(ctx, tail);
+                    emit_line_content(ctx, tail, tail_span(tail));
                 }
                 ctx.at_line_start = false;
                 return;
@@ -234,8 +510,9 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
             if line.chars().all(|c| c == '#') {
                 let level = line.chars().count();
                 ctx.out.push(Event::Attribute {
-                    name: "level".to_string(),
-                    value: level.to_string(),
+                    name: "level".into(),
+                    value: AttrValue::Int(level as i64),
+                    span,
                 });
                 return;
             }
@@ -244,15 +521,12 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
             }
             ctx.heading_prefix_consumed = true;
         }
-        emit_line_content(ctx, &line);
+        emit_line_content(ctx, &line, tail_span(&line));
     } else if ctx.in_code_fence {
-        let trimmed = line.trim();
-        if !trimmed.is_empty() && trimmed.chars().all(|c| c == '`') {
-            return;
-        }
-        ctx.out.push(Event::Text(line.clone()));
+        ctx.out
+            .push(Event::Text(line.clone().into(), tail_span(&line)));
     } else if ctx.in_list_item() {
-        emit_line_content(ctx, &line);
+        emit_line_content(ctx, &line, tail_span(&line));
     } else {
         if ctx.skip_backticks_once {
             let trimmed = line.trim();
@@ -270,7 +544,7 @@ pub fn handle(ctx: &mut ParseContext, s: &str) {
             ctx.emit_start(NodeKind::Paragraph);
             ctx.pending_para_start = false;
         }
-        emit_line_content(ctx, &line);
+        emit_line_content(ctx, &line, tail_span(&line));
     }
     ctx.at_line_start = false;
 }
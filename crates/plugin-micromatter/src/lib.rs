@@ -1,35 +1,42 @@
-use pendon_core::{Event, NodeKind, Severity};
-use serde_json::{Number, Value};
+use pendon_core::{
+    events_span, AttrValue, Diagnostic, DiagnosticCode, Event, NodeKind, Plugin, Span,
+};
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub const UNCLOSED_FRONTMATTER: DiagnosticCode =
+    DiagnosticCode::new("PD0101", "unclosed-frontmatter");
+pub const INVALID_FRONTMATTER: DiagnosticCode =
+    DiagnosticCode::new("PD0102", "invalid-frontmatter");
+
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     if events.is_empty() {
         return Vec::new();
     }
-    if !matches!(events.get(0), Some(Event::StartNode(NodeKind::Document))) {
+    if !matches!(
+        events.first(),
+        Some(Event::StartNode(NodeKind::Document, _))
+    ) {
         return events.to_vec();
     }
 
     match extract_frontmatter(events) {
-        Ok(Some((json, resume_idx))) => {
+        Ok(Some((data, resume_idx, span))) => {
             let mut out = Vec::with_capacity(events.len() + 4);
-            out.push(Event::StartNode(NodeKind::Document));
-            out.push(Event::StartNode(NodeKind::Frontmatter));
+            out.push(events[0].clone());
+            out.push(Event::StartNode(NodeKind::Frontmatter, span));
             out.push(Event::Attribute {
-                name: "data".to_string(),
-                value: json,
+                name: "data".into(),
+                value: data,
+                span,
             });
-            out.push(Event::EndNode(NodeKind::Frontmatter));
+            out.push(Event::EndNode(NodeKind::Frontmatter, span));
             out.extend(events[resume_idx..].iter().cloned());
             out
         }
         Ok(None) => events.to_vec(),
-        Err(msg) => {
+        Err(mut diag) => {
             let mut out = events.to_vec();
-            let diag = Event::Diagnostic {
-                severity: Severity::Error,
-                message: format!("[micromatter] {}", msg),
-                span: None,
-            };
+            diag.plugin = Some("micromatter".to_string());
+            let diag = Event::Diagnostic(diag);
             let insert_at = if out.len() > 1 { 1 } else { 0 };
             out.insert(insert_at, diag);
             out
@@ -37,7 +44,25 @@ pub fn process(events: &[Event]) -> Vec<Event> {
     }
 }
 
-fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, String> {
+/// Registers [`process`] as the `micromatter` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MicromatterPlugin;
+
+impl<H> Plugin<H> for MicromatterPlugin {
+    fn name(&self) -> &str {
+        "micromatter"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+}
+
+type Extracted = (AttrValue<'static>, usize, Option<Span>);
+
+/// On success returns the frontmatter data, the index to resume copying from and the
+/// span of the frontmatter block; errors carry the span of the offending block.
+fn extract_frontmatter(events: &[Event]) -> Result<Option<Extracted>, Box<Diagnostic>> {
     if events.len() < 4 {
         return Ok(None);
     }
@@ -46,16 +71,16 @@ fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, Stri
     // Expect opening --- rendered as a thematic break at the very start
     if !matches!(
         events.get(idx),
-        Some(Event::StartNode(NodeKind::ThematicBreak))
+        Some(Event::StartNode(NodeKind::ThematicBreak, _))
     ) {
         return Ok(None);
     }
-    if !matches!(events.get(idx + 1), Some(Event::Text(t)) if t.trim() == "---") {
+    if !matches!(events.get(idx + 1), Some(Event::Text(t, _)) if t.trim() == "---") {
         return Ok(None);
     }
     if !matches!(
         events.get(idx + 2),
-        Some(Event::EndNode(NodeKind::ThematicBreak))
+        Some(Event::EndNode(NodeKind::ThematicBreak, _))
     ) {
         return Ok(None);
     }
@@ -64,7 +89,7 @@ fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, Stri
     // Optional newline(s) immediately after opening fence
     while idx < events.len() {
         match &events[idx] {
-            Event::Text(t) if t == "\n" => idx += 1,
+            Event::Text(t, _) if t == "\n" => idx += 1,
             _ => break,
         }
     }
@@ -74,17 +99,17 @@ fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, Stri
     while idx + 2 < events.len() {
         if matches!(
             events.get(idx),
-            Some(Event::StartNode(NodeKind::ThematicBreak))
-        ) && matches!(events.get(idx + 1), Some(Event::Text(t)) if t.trim() == "---")
+            Some(Event::StartNode(NodeKind::ThematicBreak, _))
+        ) && matches!(events.get(idx + 1), Some(Event::Text(t, _)) if t.trim() == "---")
             && matches!(
                 events.get(idx + 2),
-                Some(Event::EndNode(NodeKind::ThematicBreak))
+                Some(Event::EndNode(NodeKind::ThematicBreak, _))
             )
         {
             close_idx = Some(idx);
             break;
         }
-        if let Event::Text(t) = &events[idx] {
+        if let Event::Text(t, _) = &events[idx] {
             content.push_str(t);
         }
         idx += 1;
@@ -92,16 +117,24 @@ fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, Stri
 
     let close_start = match close_idx {
         Some(c) => c,
-        None => return Err("missing closing ---".to_string()),
+        None => {
+            return Err(Box::new(
+                Diagnostic::error(UNCLOSED_FRONTMATTER, "missing closing ---")
+                    .with_span(events_span(&events[1..]))
+                    .with_help("end the frontmatter block with a `---` line"),
+            ))
+        }
     };
 
-    let json = parse_frontmatter(&content)?;
+    let span = events_span(&events[1..close_start + 3]);
+    let data = parse_frontmatter(&content)
+        .map_err(|e| Box::new(Diagnostic::error(INVALID_FRONTMATTER, e).with_span(span)))?;
 
     let mut resume = close_start + 3;
     while resume < events.len() {
         match &events[resume] {
-            Event::Text(t) if t == "\n" => resume += 1,
-            Event::EndNode(NodeKind::Paragraph) | Event::StartNode(NodeKind::Paragraph) => {
+            Event::Text(t, _) if t == "\n" => resume += 1,
+            Event::EndNode(NodeKind::Paragraph, _) | Event::StartNode(NodeKind::Paragraph, _) => {
                 resume += 1;
                 break;
             }
@@ -109,49 +142,46 @@ fn extract_frontmatter(events: &[Event]) -> Result<Option<(String, usize)>, Stri
         }
     }
 
-    Ok(Some((json, resume)))
+    Ok(Some((data, resume, span)))
 }
 
-fn parse_frontmatter(body: &str) -> Result<String, String> {
+fn parse_frontmatter(body: &str) -> Result<AttrValue<'static>, String> {
     let yaml: serde_yaml::Value =
         serde_yaml::from_str(body).map_err(|e| format!("invalid YAML frontmatter: {}", e))?;
-    let json = yaml_to_json(&yaml)?;
-    serde_json::to_string(&json).map_err(|e| e.to_string())
+    yaml_to_value(&yaml)
 }
 
-fn yaml_to_json(value: &serde_yaml::Value) -> Result<Value, String> {
+fn yaml_to_value(value: &serde_yaml::Value) -> Result<AttrValue<'static>, String> {
     match value {
-        serde_yaml::Value::Null => Ok(Value::Null),
-        serde_yaml::Value::Bool(v) => Ok(Value::Bool(*v)),
+        serde_yaml::Value::Null => Ok(AttrValue::Null),
+        serde_yaml::Value::Bool(v) => Ok(AttrValue::Bool(*v)),
         serde_yaml::Value::Number(v) => {
             if let Some(i) = v.as_i64() {
-                Ok(Value::Number(Number::from(i)))
-            } else if let Some(u) = v.as_u64() {
-                Ok(Value::Number(Number::from(u)))
-            } else if let Some(f) = v.as_f64() {
-                Number::from_f64(f)
-                    .map(Value::Number)
-                    .ok_or_else(|| "invalid floating-point value".to_string())
+                Ok(AttrValue::Int(i))
+            } else if let Some(f) = v.as_f64().filter(|f| f.is_finite()) {
+                Ok(AttrValue::Float(f))
+            } else if v.as_f64().is_some() {
+                Err("invalid floating-point value".to_string())
             } else {
                 Err("unsupported numeric value".to_string())
             }
         }
-        serde_yaml::Value::String(v) => Ok(Value::String(v.clone())),
+        serde_yaml::Value::String(v) => Ok(AttrValue::from(v.clone())),
         serde_yaml::Value::Sequence(values) => {
             let mut out = Vec::with_capacity(values.len());
             for item in values {
-                out.push(yaml_to_json(item)?);
+                out.push(yaml_to_value(item)?);
             }
-            Ok(Value::Array(out))
+            Ok(AttrValue::List(out))
         }
         serde_yaml::Value::Mapping(entries) => {
-            let mut out = serde_json::Map::new();
+            let mut out = Vec::with_capacity(entries.len());
             for (key, value) in entries {
-                out.insert(yaml_key_to_string(key)?, yaml_to_json(value)?);
+                out.push((yaml_key_to_string(key)?.into(), yaml_to_value(value)?));
             }
-            Ok(Value::Object(out))
+            Ok(AttrValue::Map(out))
         }
-        serde_yaml::Value::Tagged(tagged) => yaml_to_json(&tagged.value),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_value(&tagged.value),
     }
 }
 
@@ -169,16 +199,16 @@ fn yaml_key_to_string(value: &serde_yaml::Value) -> Result<String, String> {
 mod tests {
     use super::*;
 
-    fn run_micromatter(input: &str) -> Vec<Event> {
+    fn run_micromatter(input: &str) -> Vec<Event<'_>> {
         let events = pendon_core::parse(input, &pendon_core::Options::default());
         process(&events)
     }
 
-    fn frontmatter_json(events: &[Event]) -> String {
+    fn frontmatter_data<'a>(events: &'a [Event]) -> &'a AttrValue<'a> {
         events
             .iter()
             .find_map(|ev| match ev {
-                Event::Attribute { name, value } if name == "data" => Some(value.clone()),
+                Event::Attribute { name, value, .. } if name == "data" => Some(value),
                 _ => None,
             })
             .expect("frontmatter data attr")
@@ -190,11 +220,16 @@ mod tests {
             "---\ntitle: Demo\nmeta:\n  tags:\n    - a\n    - b\n  flags:\n    enabled: true\n    count: 3\n---\n\n# Hello\n",
         );
 
-        let data: Value = serde_json::from_str(&frontmatter_json(&events)).expect("valid json");
+        let data = frontmatter_data(&events);
         assert_eq!(data.get("title").and_then(|v| v.as_str()), Some("Demo"));
-        assert_eq!(data["meta"]["tags"][0].as_str(), Some("a"));
-        assert_eq!(data["meta"]["flags"]["enabled"].as_bool(), Some(true));
-        assert_eq!(data["meta"]["flags"]["count"].as_i64(), Some(3));
+        let meta = data.get("meta").expect("meta");
+        assert_eq!(
+            meta.get("tags").map(|v| v.to_string()).as_deref(),
+            Some(r#"["a","b"]"#)
+        );
+        let flags = meta.get("flags").expect("flags");
+        assert_eq!(flags.get("enabled").and_then(|v| v.as_bool()), Some(true));
+        assert_eq!(flags.get("count").and_then(|v| v.as_int()), Some(3));
     }
 
     #[test]
@@ -203,14 +238,30 @@ mod tests {
             "---\nsummary: |\n  line one\n  line two\nitems: [one, two, three]\n---\n",
         );
 
-        let data: Value = serde_json::from_str(&frontmatter_json(&events)).expect("valid json");
-        assert_eq!(data["summary"].as_str(), Some("line one\nline two\n"));
-        assert_eq!(data["items"][1].as_str(), Some("two"));
+        let data = frontmatter_data(&events);
+        assert_eq!(
+            data.get("summary").and_then(|v| v.as_str()),
+            Some("line one\nline two\n")
+        );
+        assert_eq!(
+            data.get("items").map(|v| v.to_string()).as_deref(),
+            Some(r#"["one","two","three"]"#)
+        );
     }
 
     #[test]
     fn emits_error_for_invalid_yaml() {
         let events = run_micromatter("---\nfoo: [1, two\n---\n");
-        assert!(events.iter().any(|ev| matches!(ev, Event::Diagnostic { severity: Severity::Error, message, .. } if message.contains("invalid YAML frontmatter"))));
+        let diag = events
+            .iter()
+            .find_map(|ev| match ev {
+                Event::Diagnostic(d) => Some(d),
+                _ => None,
+            })
+            .expect("diagnostic");
+        assert!(diag.is_error());
+        assert_eq!(diag.code, INVALID_FRONTMATTER);
+        assert_eq!(diag.plugin.as_deref(), Some("micromatter"));
+        assert!(diag.message.starts_with("invalid YAML frontmatter"));
     }
 }
@@ -1,22 +1,39 @@
-use pendon_core::{Event, NodeKind};
-use pendon_renderer_html;
+use pendon_core::{events_span, join_spans, AttrValue, Event, NodeKind, Plugin, Span};
 use pendon_renderer_solid::{ComponentTemplate, ImportEntry, SolidRenderHints};
 use regex::Regex;
-use serde::Serialize;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
 struct Choice {
     content: String,
     correct: bool,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default)]
 struct Feedback {
     correct: Option<String>,
     wrong: Option<String>,
 }
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+impl From<Choice> for AttrValue<'static> {
+    fn from(choice: Choice) -> Self {
+        AttrValue::Map(vec![
+            ("content".into(), choice.content.into()),
+            ("correct".into(), choice.correct.into()),
+        ])
+    }
+}
+
+impl From<Feedback> for AttrValue<'static> {
+    fn from(feedback: Feedback) -> Self {
+        let text = |t: Option<String>| t.map_or(AttrValue::Null, AttrValue::from);
+        AttrValue::Map(vec![
+            ("correct".into(), text(feedback.correct)),
+            ("wrong".into(), text(feedback.wrong)),
+        ])
+    }
+}
+
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
     while i < events.len() {
@@ -24,7 +41,8 @@ pub fn process(events: &[Event]) -> Vec<Event> {
             let content_start = start_end + 1;
             if let Some((close_start, close_end)) = find_marker(events, content_start, ":::") {
                 let inner = &events[content_start..close_start];
-                if let Some(mut built) = build_quiz_component(inner) {
+                let span = events_span(&events[i..=close_end]);
+                if let Some(mut built) = build_quiz_component(inner, span) {
                     out.append(&mut built);
                 } else {
                     out.extend_from_slice(inner);
@@ -41,15 +59,18 @@ pub fn process(events: &[Event]) -> Vec<Event> {
 }
 
 fn marker_paragraph_at(events: &[Event], idx: usize, marker: &str) -> Option<usize> {
-    if !matches!(events.get(idx), Some(Event::StartNode(NodeKind::Paragraph))) {
+    if !matches!(
+        events.get(idx),
+        Some(Event::StartNode(NodeKind::Paragraph, _))
+    ) {
         return None;
     }
     let mut buf = String::new();
     let mut j = idx + 1;
     while j < events.len() {
         match &events[j] {
-            Event::Text(t) => buf.push_str(t),
-            Event::EndNode(NodeKind::Paragraph) => {
+            Event::Text(t, _) => buf.push_str(t),
+            Event::EndNode(NodeKind::Paragraph, _) => {
                 return if buf.trim() == marker { Some(j) } else { None };
             }
             _ => return None,
@@ -70,7 +91,7 @@ fn find_marker(events: &[Event], start: usize, marker: &str) -> Option<(usize, u
     None
 }
 
-fn build_quiz_component(inner: &[Event]) -> Option<Vec<Event>> {
+fn build_quiz_component<'a>(inner: &[Event<'a>], span: Option<Span>) -> Option<Vec<Event<'a>>> {
     // Prioritize list with checkbox items; fallback to first list
     let list_range = find_checkbox_list_range(inner).or_else(|| {
         find_first_range(inner, |k| {
@@ -90,29 +111,36 @@ fn build_quiz_component(inner: &[Event]) -> Option<Vec<Event>> {
         return None;
     }
 
-    let choices_json = serde_json::to_string(&choices).unwrap_or_else(|_| "[]".to_string());
-    let feedback_json = serde_json::to_string(&feedback).unwrap_or_else(|_| "{}".to_string());
+    let range_span = |range: Option<(usize, usize)>| {
+        range.and_then(|(s, e)| events_span(&inner[s..=e])).or(span)
+    };
     let mut out: Vec<Event> = Vec::with_capacity(question_events.len() + 6);
-    out.push(Event::StartNode(NodeKind::Custom("Quiz".to_string())));
+    out.push(Event::StartNode(NodeKind::Custom("Quiz".to_string()), span));
     out.push(Event::Attribute {
-        name: "name".to_string(),
-        value: "Quiz".to_string(),
+        name: "name".into(),
+        value: "Quiz".into(),
+        span,
     });
     out.push(Event::Attribute {
-        name: "choices".to_string(),
-        value: choices_json,
+        name: "choices".into(),
+        value: AttrValue::List(choices.into_iter().map(AttrValue::from).collect()),
+        span: range_span(list_range),
     });
     out.push(Event::Attribute {
-        name: "feedback".to_string(),
-        value: feedback_json,
+        name: "feedback".into(),
+        value: feedback.into(),
+        span: range_span(feedback_range),
     });
     out.extend(question_events);
-    out.push(Event::EndNode(NodeKind::Custom("Quiz".to_string())));
+    out.push(Event::EndNode(NodeKind::Custom("Quiz".to_string()), span));
 
     Some(out)
 }
 
-fn filter_out_ranges(events: &[Event], ranges: &[Option<(usize, usize)>]) -> Vec<Event> {
+fn filter_out_ranges<'a>(
+    events: &[Event<'a>],
+    ranges: &[Option<(usize, usize)>],
+) -> Vec<Event<'a>> {
     let mut out = Vec::new();
     for (idx, ev) in events.iter().enumerate() {
         if ranges
@@ -132,7 +160,7 @@ where
 {
     let mut i = 0usize;
     while i < events.len() {
-        if let Event::StartNode(kind) = &events[i] {
+        if let Event::StartNode(kind, _) = &events[i] {
             if matcher(kind) {
                 let end = matching_end(events, i)?;
                 return Some((i, end));
@@ -146,7 +174,7 @@ where
 fn find_checkbox_list_range(events: &[Event]) -> Option<(usize, usize)> {
     let mut i = 0usize;
     while i < events.len() {
-        if let Event::StartNode(kind) = &events[i] {
+        if let Event::StartNode(kind, _) = &events[i] {
             if matches!(kind, NodeKind::BulletList | NodeKind::OrderedList) {
                 if let Some(end) = matching_end(events, i) {
                     let list_events = &events[i..=end];
@@ -164,6 +192,10 @@ fn find_checkbox_list_range(events: &[Event]) -> Option<(usize, usize)> {
 }
 
 fn has_checkbox_items(list_events: &[Event]) -> bool {
+    let is_task = |ev: &Event| matches!(ev, Event::Attribute { name, .. } if name == "task");
+    if list_events.iter().any(is_task) {
+        return true;
+    }
     let text = collect_text(list_events);
     if let Ok(re) = Regex::new(r"\[[ xX]\]") {
         re.is_match(&text)
@@ -176,8 +208,8 @@ fn matching_end(events: &[Event], start_idx: usize) -> Option<usize> {
     let mut depth = 0isize;
     for (idx, ev) in events.iter().enumerate().skip(start_idx) {
         match ev {
-            Event::StartNode(_) => depth += 1,
-            Event::EndNode(_) => {
+            Event::StartNode(_, _) => depth += 1,
+            Event::EndNode(_, _) => {
                 depth -= 1;
                 if depth == 0 {
                     return Some(idx);
@@ -196,7 +228,7 @@ fn extract_choices(list_events: &[Event]) -> Option<Vec<Choice>> {
     let mut choices: Vec<Choice> = Vec::new();
     let mut i = 0usize;
     while i < list_events.len() {
-        if matches!(list_events[i], Event::StartNode(NodeKind::ListItem)) {
+        if matches!(list_events[i], Event::StartNode(NodeKind::ListItem, _)) {
             let end = matching_end(list_events, i)?;
             let item_events = list_events[i + 1..end].to_vec();
             let (correct, cleaned) = strip_checkbox_marker(item_events);
@@ -213,20 +245,36 @@ fn extract_choices(list_events: &[Event]) -> Option<Vec<Choice>> {
     Some(choices)
 }
 
-fn strip_checkbox_marker(mut events: Vec<Event>) -> (bool, Vec<Event>) {
+fn strip_checkbox_marker<'a>(mut events: Vec<Event<'a>>) -> (bool, Vec<Event<'a>>) {
+    // `markdown` already turned the marker into `task`/`checked` item attributes
+    let leading = events
+        .iter()
+        .take_while(|ev| matches!(ev, Event::Attribute { .. }))
+        .count();
+    let attr = |name: &str| {
+        events[..leading].iter().find_map(|ev| match ev {
+            Event::Attribute { name: n, value, .. } if n == name => Some(value.as_flag()),
+            _ => None,
+        })
+    };
+    if attr("task") == Some(true) {
+        let correct = attr("checked") == Some(true);
+        events.drain(..leading);
+        return (correct, merge_adjacent_text(events));
+    }
     events = merge_adjacent_text(events);
     let mut correct = false;
     let re = Regex::new(r"^\[(x|X| )\]\s*").ok();
     if let Some(re) = re {
         for ev in events.iter_mut() {
-            if let Event::Text(text) = ev {
+            if let Event::Text(text, _) = ev {
                 let trimmed = text.trim_start();
                 if let Some(m) = re.find(trimmed) {
                     correct = trimmed[m.start()..m.end()]
                         .to_ascii_lowercase()
                         .starts_with("[x]");
                     let stripped = trimmed[m.end()..].to_string();
-                    *text = stripped;
+                    *text = stripped.into();
                     break;
                 }
             }
@@ -238,7 +286,7 @@ fn strip_checkbox_marker(mut events: Vec<Event>) -> (bool, Vec<Event>) {
 fn find_feedback(events: &[Event]) -> (Option<(usize, usize)>, Feedback) {
     let mut i = 0usize;
     while i < events.len() {
-        if matches!(events[i], Event::StartNode(NodeKind::Blockquote)) {
+        if matches!(events[i], Event::StartNode(NodeKind::Blockquote, _)) {
             if let Some(end) = matching_end(events, i) {
                 let slice = &events[i..=end];
                 let fb = parse_feedback(slice);
@@ -293,15 +341,16 @@ fn parse_feedback(events: &[Event]) -> Feedback {
     fb
 }
 
-fn merge_adjacent_text(events: Vec<Event>) -> Vec<Event> {
+fn merge_adjacent_text<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     for ev in events {
         match ev {
-            Event::Text(t) => {
-                if let Some(Event::Text(prev)) = out.last_mut() {
-                    prev.push_str(&t);
+            Event::Text(t, span) => {
+                if let Some(Event::Text(prev, prev_span)) = out.last_mut() {
+                    prev.to_mut().push_str(&t);
+                    *prev_span = join_spans(*prev_span, span);
                 } else {
-                    out.push(Event::Text(t));
+                    out.push(Event::Text(t, span));
                 }
             }
             other => out.push(other),
@@ -314,8 +363,8 @@ fn collect_text(events: &[Event]) -> String {
     let mut out = String::new();
     for ev in events {
         match ev {
-            Event::Text(t) => out.push_str(t),
-            Event::EndNode(NodeKind::Paragraph) => out.push('\n'),
+            Event::Text(t, _) => out.push_str(t),
+            Event::EndNode(NodeKind::Paragraph, _) => out.push('\n'),
             _ => {}
         }
     }
@@ -327,9 +376,9 @@ fn render_fragment(events: &[Event]) -> String {
         return String::new();
     }
     let mut doc: Vec<Event> = Vec::with_capacity(events.len() + 2);
-    doc.push(Event::StartNode(NodeKind::Document));
+    doc.push(Event::StartNode(NodeKind::Document, None));
     doc.extend_from_slice(events);
-    doc.push(Event::EndNode(NodeKind::Document));
+    doc.push(Event::EndNode(NodeKind::Document, None));
     pendon_renderer_html::render_html(&doc)
 }
 
@@ -354,26 +403,69 @@ pub fn solid_hints() -> SolidRenderHints {
     hints
 }
 
+/// Registers [`process`] as the `quiz` plugin. Quiz blocks are recognised
+/// on markdown output, so it runs after `markdown` when both are requested.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuizPlugin;
+
+impl Plugin<SolidRenderHints> for QuizPlugin {
+    fn name(&self) -> &str {
+        "quiz"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["markdown"]
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+
+    fn hints(&self) -> Option<SolidRenderHints> {
+        Some(solid_hints())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn strips_checkbox_marker() {
-        let events = vec![Event::Text("[x] answer".to_string())];
+        let events = vec![Event::Text("[x] answer".into(), None)];
+        let (correct, cleaned) = strip_checkbox_marker(events);
+        assert!(correct);
+        assert!(matches!(cleaned.first(), Some(Event::Text(t, _)) if t == "answer"));
+    }
+
+    #[test]
+    fn reads_task_item_attributes() {
+        let events = vec![
+            Event::Attribute {
+                name: "task".into(),
+                value: true.into(),
+                span: None,
+            },
+            Event::Attribute {
+                name: "checked".into(),
+                value: true.into(),
+                span: None,
+            },
+            Event::Text("answer".into(), None),
+        ];
         let (correct, cleaned) = strip_checkbox_marker(events);
         assert!(correct);
-        assert!(matches!(cleaned.first(), Some(Event::Text(t)) if t == "answer"));
+        assert!(matches!(cleaned.as_slice(), [Event::Text(t, _)] if t == "answer"));
     }
 
     #[test]
     fn parses_feedback_lines() {
         let events = vec![
-            Event::StartNode(NodeKind::Blockquote),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::Text("x wrong v right".to_string()),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Blockquote),
+            Event::StartNode(NodeKind::Blockquote, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("x wrong v right".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Blockquote, None),
         ];
         let fb = parse_feedback(&events);
         assert_eq!(fb.wrong.as_deref(), Some("wrong"));
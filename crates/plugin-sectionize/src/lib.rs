@@ -1,4 +1,7 @@
-use pendon_core::{ensure_unique, extract_id, slugify, strip_trailing_id, Event, NodeKind};
+use pendon_core::{
+    ensure_unique_with, extract_id, propagate_node_spans, slugify_with, strip_trailing_id, Event,
+    NodeKind, Plugin, SlugStrategy, Span,
+};
 use std::collections::{HashMap, VecDeque};
 
 struct SectionFrame {
@@ -10,7 +13,17 @@ struct HeadingInfo {
     id: Option<String>,
 }
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
+    process_with_options(events, SectionizeOptions::default())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SectionizeOptions {
+    /// How section ids are derived from heading text.
+    pub slug: SlugStrategy,
+}
+
+pub fn process_with_options<'a>(events: &[Event<'a>], opts: SectionizeOptions) -> Vec<Event<'a>> {
     let events = strip_frontmatter_block(events);
 
     let mut out: Vec<Event> = Vec::with_capacity(events.len() + 8);
@@ -24,22 +37,38 @@ pub fn process(events: &[Event]) -> Vec<Event> {
 
     while idx < events.len() {
         match &events[idx] {
-            Event::StartNode(NodeKind::Heading) if !in_code_fence && !in_frontmatter => {
+            Event::StartNode(NodeKind::Heading, _) if !in_code_fence && !in_frontmatter => {
                 close_lists_for_heading(&mut container_stack, &mut forced_closed, &mut out);
 
                 let (mut heading_block, heading_info, consumed) =
-                    consume_heading(&events, idx, &mut used_ids);
+                    consume_heading(&events, idx, &mut used_ids, opts.slug);
 
                 close_sections_by_level(&mut stack, &mut out, heading_info.level);
 
-                open_section(&mut stack, &mut out, heading_info.level, heading_info.id);
+                open_section(
+                    &mut stack,
+                    &mut out,
+                    heading_info.level,
+                    heading_info.id,
+                    events[idx].span(),
+                );
 
                 out.append(&mut heading_block);
 
                 idx = consumed;
                 continue;
             }
-            Event::StartNode(kind) => {
+            Event::StartNode(NodeKind::FootnoteDefinition, _) if container_stack.len() <= 1 => {
+                // Footnotes gathered at the end of the document stay outside
+                // content sections
+                while stack.pop().is_some() {
+                    out.push(Event::EndNode(NodeKind::Section, None));
+                }
+                container_stack.push(NodeKind::FootnoteDefinition);
+                out.push(events[idx].clone());
+            }
+            Event::StartNode(kind, _) => {
+                let in_footnote = container_stack.contains(&NodeKind::FootnoteDefinition);
                 container_stack.push(kind.clone());
                 if *kind == NodeKind::CodeFence {
                     in_code_fence = true;
@@ -48,19 +77,25 @@ pub fn process(events: &[Event]) -> Vec<Event> {
                     in_frontmatter = true;
                 }
 
-                if !matches!(kind, NodeKind::Document) && !in_frontmatter {
+                if !matches!(kind, NodeKind::Document) && !in_frontmatter && !in_footnote {
                     ensure_preface_section(&mut stack, &mut out);
                 }
 
                 out.push(events[idx].clone());
             }
-            Event::EndNode(kind) => {
+            Event::EndNode(kind, _) => {
                 if forced_closed.front() == Some(kind) {
                     forced_closed.pop_front();
                     idx += 1;
                     continue;
                 }
                 pop_container(&mut container_stack, kind);
+                if *kind == NodeKind::Document {
+                    // Sections nest inside the document, so close them first
+                    while stack.pop().is_some() {
+                        out.push(Event::EndNode(NodeKind::Section, None));
+                    }
+                }
                 out.push(events[idx].clone());
 
                 if *kind == NodeKind::CodeFence {
@@ -71,7 +106,8 @@ pub fn process(events: &[Event]) -> Vec<Event> {
                 }
             }
             ev => {
-                if !in_frontmatter {
+                let in_footnote = container_stack.contains(&NodeKind::FootnoteDefinition);
+                if !in_frontmatter && !in_footnote {
                     ensure_preface_section(&mut stack, &mut out);
                 }
                 out.push(ev.clone());
@@ -82,17 +118,36 @@ pub fn process(events: &[Event]) -> Vec<Event> {
 
     // Close any remaining open sections
     while stack.pop().is_some() {
-        out.push(Event::EndNode(NodeKind::Section));
+        out.push(Event::EndNode(NodeKind::Section, None));
     }
 
+    // Sections and force-closed lists are sized from the content they wrap
+    propagate_node_spans(&mut out);
     out
 }
 
-fn consume_heading(
-    events: &[Event],
+/// Registers [`process_with_options`] as the `sectionize` plugin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SectionizePlugin {
+    pub options: SectionizeOptions,
+}
+
+impl<H> Plugin<H> for SectionizePlugin {
+    fn name(&self) -> &str {
+        "sectionize"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process_with_options(events, self.options)
+    }
+}
+
+fn consume_heading<'a>(
+    events: &[Event<'a>],
     start_idx: usize,
     used_ids: &mut HashMap<String, usize>,
-) -> (Vec<Event>, HeadingInfo, usize) {
+    slug: SlugStrategy,
+) -> (Vec<Event<'a>>, HeadingInfo, usize) {
     let mut out: Vec<Event> = Vec::new();
     let mut idx = start_idx;
     let mut heading_level: usize = 1;
@@ -102,31 +157,31 @@ fn consume_heading(
 
     while idx < events.len() {
         match &events[idx] {
-            Event::StartNode(NodeKind::Heading) => {
+            Event::StartNode(NodeKind::Heading, _) => {
                 out.push(events[idx].clone());
                 idx += 1;
             }
-            Event::EndNode(NodeKind::Heading) => {
+            Event::EndNode(NodeKind::Heading, _) => {
                 out.push(events[idx].clone());
                 idx += 1;
                 break;
             }
-            Event::Attribute { name, value } => {
+            Event::Attribute { name, value, .. } => {
                 if name == "level" {
-                    if let Ok(parsed) = value.parse::<usize>() {
+                    if let Ok(parsed) = value.to_string().parse::<usize>() {
                         heading_level = parsed;
                     }
                     // do not copy level attribute here; it will be reattached via original event
                     out.push(events[idx].clone());
                 } else if name == "id" {
-                    heading_id_attr = Some(value.clone());
+                    heading_id_attr = Some(value.to_string());
                     // skip copying heading id attribute; ids move to section
                 } else {
                     out.push(events[idx].clone());
                 }
                 idx += 1;
             }
-            Event::Text(text) => {
+            Event::Text(text, _) => {
                 heading_text.push_str(text);
                 last_text_idx = Some(out.len());
                 out.push(events[idx].clone());
@@ -140,11 +195,11 @@ fn consume_heading(
     }
 
     if let Some(tidx) = last_text_idx {
-        if let Some(Event::Text(text)) = out.get(tidx) {
+        if let Some(Event::Text(text, _)) = out.get(tidx) {
             let (stripped, removed) = strip_trailing_id(text);
             if removed {
-                if let Some(Event::Text(slot)) = out.get_mut(tidx) {
-                    *slot = stripped;
+                if let Some(Event::Text(slot, _)) = out.get_mut(tidx) {
+                    *slot = stripped.into();
                 }
             }
         }
@@ -155,8 +210,8 @@ fn consume_heading(
     let final_id = if need_id {
         let base = heading_id_attr
             .or(custom_id)
-            .unwrap_or_else(|| slugify(&clean_title));
-        Some(ensure_unique(base, used_ids))
+            .unwrap_or_else(|| slugify_with(&clean_title, slug));
+        Some(ensure_unique_with(base, used_ids, slug))
     } else {
         None
     };
@@ -176,12 +231,14 @@ fn open_section(
     out: &mut Vec<Event>,
     level: usize,
     id: Option<String>,
+    heading_span: Option<Span>,
 ) {
-    out.push(Event::StartNode(NodeKind::Section));
+    out.push(Event::StartNode(NodeKind::Section, None));
     if let Some(id_val) = id.clone() {
         out.push(Event::Attribute {
-            name: "id".to_string(),
-            value: id_val,
+            name: "id".into(),
+            value: id_val.into(),
+            span: heading_span,
         });
     }
     stack.push(SectionFrame { level });
@@ -190,20 +247,20 @@ fn open_section(
 fn ensure_preface_section(stack: &mut Vec<SectionFrame>, out: &mut Vec<Event>) {
     if stack.is_empty() {
         // Preface carries level 0 so it stays open until a heading explicitly closes it.
-        open_section(stack, out, 0, None);
+        open_section(stack, out, 0, None, None);
     }
 }
 
 fn close_sections_by_level(stack: &mut Vec<SectionFrame>, out: &mut Vec<Event>, new_level: usize) {
     while let Some(frame) = stack.last() {
         if frame.level == 0 {
-            out.push(Event::EndNode(NodeKind::Section));
+            out.push(Event::EndNode(NodeKind::Section, None));
             stack.pop();
             continue;
         }
 
         if (frame.level == 1 && new_level >= 2) || (frame.level >= new_level) {
-            out.push(Event::EndNode(NodeKind::Section));
+            out.push(Event::EndNode(NodeKind::Section, None));
             stack.pop();
         } else {
             break;
@@ -226,7 +283,7 @@ fn close_lists_for_heading(
     while let Some(top) = container_stack.last() {
         if is_list_kind(top) {
             let kind = container_stack.pop().unwrap();
-            out.push(Event::EndNode(kind.clone()));
+            out.push(Event::EndNode(kind.clone(), None));
             forced_closed.push_back(kind);
         } else {
             break;
@@ -241,12 +298,15 @@ fn pop_container(container_stack: &mut Vec<NodeKind>, kind: &NodeKind) {
     // if the kind was already force-closed, the container stack will already be clean
 }
 
-fn strip_frontmatter_block(events: &[Event]) -> Vec<Event> {
+fn strip_frontmatter_block<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut idx = 0usize;
 
     // Preserve leading Document start if present
-    if matches!(events.get(idx), Some(Event::StartNode(NodeKind::Document))) {
+    if matches!(
+        events.get(idx),
+        Some(Event::StartNode(NodeKind::Document, _))
+    ) {
         out.push(events[idx].clone());
         idx += 1;
     }
@@ -265,26 +325,29 @@ fn frontmatter_span(events: &[Event], start: usize) -> Option<usize> {
     // ---
     if !matches!(
         events.get(idx),
-        Some(Event::StartNode(NodeKind::ThematicBreak))
+        Some(Event::StartNode(NodeKind::ThematicBreak, _))
     ) {
         return None;
     }
     idx += 1;
     if matches!(
         events.get(idx),
-        Some(Event::EndNode(NodeKind::ThematicBreak))
+        Some(Event::EndNode(NodeKind::ThematicBreak, _))
     ) {
         idx += 1;
     }
 
     // paragraph payload
-    if !matches!(events.get(idx), Some(Event::StartNode(NodeKind::Paragraph))) {
+    if !matches!(
+        events.get(idx),
+        Some(Event::StartNode(NodeKind::Paragraph, _))
+    ) {
         return None;
     }
     idx += 1;
     while idx < events.len() {
         match events.get(idx) {
-            Some(Event::EndNode(NodeKind::Paragraph)) => {
+            Some(Event::EndNode(NodeKind::Paragraph, _)) => {
                 idx += 1;
                 break;
             }
@@ -296,14 +359,14 @@ fn frontmatter_span(events: &[Event], start: usize) -> Option<usize> {
     // closing ---
     if !matches!(
         events.get(idx),
-        Some(Event::StartNode(NodeKind::ThematicBreak))
+        Some(Event::StartNode(NodeKind::ThematicBreak, _))
     ) {
         return None;
     }
     idx += 1;
     if matches!(
         events.get(idx),
-        Some(Event::EndNode(NodeKind::ThematicBreak))
+        Some(Event::EndNode(NodeKind::ThematicBreak, _))
     ) {
         idx += 1;
     }
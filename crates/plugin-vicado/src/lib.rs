@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use pendon_core::{Event, NodeKind};
+use pendon_core::{events_span, Event, NodeKind, Plugin, Span};
 use pendon_renderer_solid::{ComponentTemplate, ImportEntry, SolidRenderHints};
 use serde_json::Value;
 
@@ -12,15 +12,19 @@ struct VicadoSpec {
     props: BTreeMap<String, Value>,
 }
 
-pub fn process(events: &[Event]) -> Vec<Event> {
+pub fn process<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
 
     while i < events.len() {
-        if matches!(events.get(i), Some(Event::StartNode(NodeKind::CodeFence))) {
+        if matches!(
+            events.get(i),
+            Some(Event::StartNode(NodeKind::CodeFence, _))
+        ) {
             if let Some(end) = find_matching_end(events, i, NodeKind::CodeFence) {
                 if let Some((spec, code)) = extract_vicado_spec_and_code(&events[i + 1..end]) {
-                    emit_vicado_component(&mut out, &spec, &code);
+                    let span = events_span(&events[i..=end]);
+                    emit_vicado_component(&mut out, &spec, &code, span);
                     i = end + 1;
                     continue;
                 }
@@ -57,18 +61,23 @@ pub fn solid_hints() -> SolidRenderHints {
     hints
 }
 
-fn emit_vicado_component(out: &mut Vec<Event>, spec: &VicadoSpec, code: &str) {
+fn emit_vicado_component(out: &mut Vec<Event>, spec: &VicadoSpec, code: &str, span: Option<Span>) {
     let jsx_props = build_jsx_props(spec, code);
-    out.push(Event::StartNode(NodeKind::Custom("Vicado".to_string())));
+    out.push(Event::StartNode(
+        NodeKind::Custom("Vicado".to_string()),
+        span,
+    ));
     out.push(Event::Attribute {
-        name: "name".to_string(),
-        value: "Vicado".to_string(),
+        name: "name".into(),
+        value: "Vicado".into(),
+        span,
     });
     out.push(Event::Attribute {
-        name: "jsx_props".to_string(),
-        value: jsx_props,
+        name: "jsx_props".into(),
+        value: jsx_props.into(),
+        span,
     });
-    out.push(Event::EndNode(NodeKind::Custom("Vicado".to_string())));
+    out.push(Event::EndNode(NodeKind::Custom("Vicado".to_string()), span));
 }
 
 fn build_jsx_props(spec: &VicadoSpec, code: &str) -> String {
@@ -109,10 +118,10 @@ fn extract_vicado_spec_and_code(slice: &[Event]) -> Option<(VicadoSpec, String)>
 
     for ev in slice {
         match ev {
-            Event::Attribute { name, value } if name == "lang" => {
-                info = Some(value.as_str());
+            Event::Attribute { name, value, .. } if name == "lang" => {
+                info = value.as_str();
             }
-            Event::Text(text) => code.push_str(text),
+            Event::Text(text, _) => code.push_str(text),
             _ => {}
         }
     }
@@ -302,8 +311,8 @@ fn find_matching_end(events: &[Event], start_idx: usize, kind: NodeKind) -> Opti
     let mut depth = 0isize;
     for (idx, ev) in events.iter().enumerate().skip(start_idx) {
         match ev {
-            Event::StartNode(k) if *k == kind => depth += 1,
-            Event::EndNode(k) if *k == kind => {
+            Event::StartNode(k, _) if *k == kind => depth += 1,
+            Event::EndNode(k, _) if *k == kind => {
                 depth -= 1;
                 if depth == 0 {
                     return Some(idx);
@@ -400,6 +409,35 @@ fn escape_jsx_attr(input: &str) -> String {
     out
 }
 
+/// Registers [`process`] as the `vicado` plugin, contributing `hints` to
+/// the Solid renderer (defaults to [`solid_hints`]).
+#[derive(Debug, Clone)]
+pub struct VicadoPlugin {
+    pub hints: SolidRenderHints,
+}
+
+impl Default for VicadoPlugin {
+    fn default() -> Self {
+        VicadoPlugin {
+            hints: solid_hints(),
+        }
+    }
+}
+
+impl Plugin<SolidRenderHints> for VicadoPlugin {
+    fn name(&self) -> &str {
+        "vicado"
+    }
+
+    fn process<'a>(&self, events: &[Event<'a>]) -> Vec<Event<'a>> {
+        process(events)
+    }
+
+    fn hints(&self) -> Option<SolidRenderHints> {
+        Some(self.hints.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -434,23 +472,24 @@ mod tests {
     #[test]
     fn transforms_vicado_codefence_into_custom_node() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::CodeFence),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::CodeFence, None),
             Event::Attribute {
-                name: "lang".to_string(),
-                value: "typescript vicado {mount: \"visible\"}".to_string(),
+                name: "lang".into(),
+                value: "typescript vicado {mount: \"visible\"}".into(),
+                span: None,
             },
-            Event::Text("function tsCodeHere()".to_string()),
-            Event::EndNode(NodeKind::CodeFence),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("function tsCodeHere()".into(), None),
+            Event::EndNode(NodeKind::CodeFence, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process(&events);
         assert!(out.iter().any(|e| {
-            matches!(e, Event::StartNode(NodeKind::Custom(name)) if name == "Vicado")
+            matches!(e, Event::StartNode(NodeKind::Custom(name), _) if name == "Vicado")
         }));
         assert!(out.iter().any(|e| {
-            matches!(e, Event::Attribute { name, value } if name == "jsx_props" && value.contains("code={\"function tsCodeHere()\"}"))
+            matches!(e, Event::Attribute { name, value, .. } if name == "jsx_props" && value.to_string().contains("code={\"function tsCodeHere()\"}"))
         }));
     }
 
@@ -466,42 +505,44 @@ mod tests {
     #[test]
     fn trims_single_boundary_newline_from_code_body() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::CodeFence),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::CodeFence, None),
             Event::Attribute {
-                name: "lang".to_string(),
-                value: "html vicado".to_string(),
+                name: "lang".into(),
+                value: "html vicado".into(),
+                span: None,
             },
-            Event::Text("\n<div>ok</div>\n".to_string()),
-            Event::EndNode(NodeKind::CodeFence),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("\n<div>ok</div>\n".into(), None),
+            Event::EndNode(NodeKind::CodeFence, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process(&events);
         assert!(out.iter().any(|e| {
-            matches!(e, Event::Attribute { name, value }
-                if name == "jsx_props" && value.contains("code={\"<div>ok</div>\"}"))
+            matches!(e, Event::Attribute { name, value, .. }
+                if name == "jsx_props" && value.to_string().contains("code={\"<div>ok</div>\"}"))
         }));
     }
 
     #[test]
     fn keeps_intentional_blank_line_inside_code_body() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::CodeFence),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::CodeFence, None),
             Event::Attribute {
-                name: "lang".to_string(),
-                value: "html vicado".to_string(),
+                name: "lang".into(),
+                value: "html vicado".into(),
+                span: None,
             },
-            Event::Text("\n\n<div>ok</div>\n\n".to_string()),
-            Event::EndNode(NodeKind::CodeFence),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("\n\n<div>ok</div>\n\n".into(), None),
+            Event::EndNode(NodeKind::CodeFence, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let out = process(&events);
         assert!(out.iter().any(|e| {
-            matches!(e, Event::Attribute { name, value }
-                if name == "jsx_props" && value.contains("code={\"\\n<div>ok</div>\\n\"}"))
+            matches!(e, Event::Attribute { name, value, .. }
+                if name == "jsx_props" && value.to_string().contains("code={\"\\n<div>ok</div>\\n\"}"))
         }));
     }
 }
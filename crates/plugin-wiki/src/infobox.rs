@@ -1,4 +1,4 @@
-use pendon_core::{parse, Event, NodeKind, Options};
+use pendon_core::{events_span, parse, Event, NodeKind, Options};
 use pendon_plugin_markdown::process as process_markdown;
 use regex::Regex;
 
@@ -6,12 +6,15 @@ use crate::options::WikiOptions;
 use crate::util::{collect_text, escape_html, find_matching_end, normalize_class_tokens};
 use crate::wikilink::rewrite_wikilink_markdown;
 
-pub(crate) fn process_infobox(events: &[Event], options: &WikiOptions) -> Vec<Event> {
+pub(crate) fn process_infobox<'a>(events: &[Event<'a>], options: &WikiOptions) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut i = 0usize;
 
     while i < events.len() {
-        if matches!(events.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(
+            events.get(i),
+            Some(Event::StartNode(NodeKind::Paragraph, _))
+        ) {
             if let Some(start_end) = find_matching_end(events, i, NodeKind::Paragraph) {
                 let marker = collect_text(&events[i + 1..start_end]);
                 if let Some(classes) = parse_infobox_start(marker.trim()) {
@@ -20,9 +23,10 @@ pub(crate) fn process_infobox(events: &[Event], options: &WikiOptions) -> Vec<Ev
                     {
                         let inner = &events[start_end + 1..close_start];
                         let html = render_infobox(inner, classes.as_deref(), options);
-                        out.push(Event::StartNode(NodeKind::HtmlBlock));
-                        out.push(Event::Text(html));
-                        out.push(Event::EndNode(NodeKind::HtmlBlock));
+                        let span = events_span(&events[i..=close_end]);
+                        out.push(Event::StartNode(NodeKind::HtmlBlock, span));
+                        out.push(Event::Text(html.into(), span));
+                        out.push(Event::EndNode(NodeKind::HtmlBlock, span));
                         i = close_end + 1;
                         continue;
                     }
@@ -53,7 +57,10 @@ fn parse_infobox_start(s: &str) -> Option<Option<String>> {
 fn find_infobox_close(events: &[Event], from: usize) -> Option<(usize, usize)> {
     let mut i = from;
     while i < events.len() {
-        if matches!(events.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(
+            events.get(i),
+            Some(Event::StartNode(NodeKind::Paragraph, _))
+        ) {
             let end = find_matching_end(events, i, NodeKind::Paragraph)?;
             let text = collect_text(&events[i + 1..end]);
             if text.trim() == ":::" {
@@ -80,7 +87,7 @@ fn render_infobox(inner: &[Event], class_name: Option<&str>, options: &WikiOptio
 
     let mut i = 0usize;
     while i < inner.len() {
-        if !matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if !matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph, _))) {
             i += 1;
             continue;
         }
@@ -209,7 +216,7 @@ fn parse_div_block(inner: &[Event], start_para: usize) -> Option<(String, (usize
 
     let mut i = start_end + 1;
     while i < inner.len() {
-        if matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph, _))) {
             let end = find_matching_end(inner, i, NodeKind::Paragraph)?;
             let t = collect_text(&inner[i + 1..end]);
             if t.trim() == "::" {
@@ -253,7 +260,7 @@ fn collect_div_block_text(inner: &[Event], from: usize, until_para_start: usize)
     let mut parts: Vec<String> = Vec::new();
     let mut i = from;
     while i < until_para_start {
-        if matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph))) {
+        if matches!(inner.get(i), Some(Event::StartNode(NodeKind::Paragraph, _))) {
             if let Some(end) = find_matching_end(inner, i, NodeKind::Paragraph) {
                 let t = collect_text(&inner[i + 1..end]);
                 if !t.trim().is_empty() {
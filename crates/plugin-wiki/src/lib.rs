@@ -5,14 +5,32 @@ mod wikilink;
 
 pub use options::WikiOptions;
 
-pub fn process(events: &[pendon_core::Event]) -> Vec<pendon_core::Event> {
+use pendon_core::Plugin;
+
+pub fn process<'a>(events: &[pendon_core::Event<'a>]) -> Vec<pendon_core::Event<'a>> {
     process_with_options(events, WikiOptions::default())
 }
 
-pub fn process_with_options(
-    events: &[pendon_core::Event],
+pub fn process_with_options<'a>(
+    events: &[pendon_core::Event<'a>],
     options: WikiOptions,
-) -> Vec<pendon_core::Event> {
+) -> Vec<pendon_core::Event<'a>> {
     let infobox_processed = infobox::process_infobox(events, &options);
     wikilink::process_wikilinks(&infobox_processed, &options)
 }
+
+/// Registers [`process_with_options`] as the `wiki` plugin.
+#[derive(Debug, Clone, Default)]
+pub struct WikiPlugin {
+    pub options: WikiOptions,
+}
+
+impl<H> Plugin<H> for WikiPlugin {
+    fn name(&self) -> &str {
+        "wiki"
+    }
+
+    fn process<'a>(&self, events: &[pendon_core::Event<'a>]) -> Vec<pendon_core::Event<'a>> {
+        process_with_options(events, self.options.clone())
+    }
+}
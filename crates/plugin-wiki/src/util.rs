@@ -3,7 +3,7 @@ use pendon_core::{Event, NodeKind};
 pub(crate) fn collect_text(events: &[Event]) -> String {
     let mut out = String::new();
     for ev in events {
-        if let Event::Text(t) = ev {
+        if let Event::Text(t, _) = ev {
             out.push_str(t);
         }
     }
@@ -18,8 +18,8 @@ pub(crate) fn find_matching_end(
     let mut depth = 0isize;
     for (idx, ev) in events.iter().enumerate().skip(start_idx) {
         match ev {
-            Event::StartNode(k) if *k == kind => depth += 1,
-            Event::EndNode(k) if *k == kind => {
+            Event::StartNode(k, _) if *k == kind => depth += 1,
+            Event::EndNode(k, _) if *k == kind => {
                 depth -= 1;
                 if depth == 0 {
                     return Some(idx);
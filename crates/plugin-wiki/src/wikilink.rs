@@ -1,4 +1,4 @@
-use pendon_core::{Event, NodeKind};
+use pendon_core::{Event, NodeKind, Span};
 
 use crate::options::WikiOptions;
 use crate::util::capitalize_first;
@@ -10,21 +10,24 @@ pub(crate) struct WikiLink {
     pub(crate) label: String,
 }
 
-pub(crate) fn process_wikilinks(events: &[Event], options: &WikiOptions) -> Vec<Event> {
+pub(crate) fn process_wikilinks<'a>(events: &[Event<'a>], options: &WikiOptions) -> Vec<Event<'a>> {
     let mut out: Vec<Event> = Vec::with_capacity(events.len());
     let mut html_depth = 0usize;
 
     for ev in events {
         match ev {
-            Event::StartNode(NodeKind::HtmlBlock) | Event::StartNode(NodeKind::HtmlInline) => {
+            Event::StartNode(NodeKind::HtmlBlock, _)
+            | Event::StartNode(NodeKind::HtmlInline, _) => {
                 html_depth += 1;
                 out.push(ev.clone());
             }
-            Event::EndNode(NodeKind::HtmlBlock) | Event::EndNode(NodeKind::HtmlInline) => {
+            Event::EndNode(NodeKind::HtmlBlock, _) | Event::EndNode(NodeKind::HtmlInline, _) => {
                 html_depth = html_depth.saturating_sub(1);
                 out.push(ev.clone());
             }
-            Event::Text(text) if html_depth == 0 => emit_wikilink_text(text, &mut out, options),
+            Event::Text(text, span) if html_depth == 0 => {
+                emit_wikilink_text(text, *span, &mut out, options)
+            }
             _ => out.push(ev.clone()),
         }
     }
@@ -32,42 +35,61 @@ pub(crate) fn process_wikilinks(events: &[Event], options: &WikiOptions) -> Vec<
     out
 }
 
-pub(crate) fn emit_wikilink_text(text: &str, out: &mut Vec<Event>, options: &WikiOptions) {
+pub(crate) fn emit_wikilink_text(
+    text: &str,
+    span: Option<Span>,
+    out: &mut Vec<Event>,
+    options: &WikiOptions,
+) {
+    let sub = |from: usize, to: usize| span.map(|sp| sp.slice(text, from..to));
     let mut cursor = 0usize;
     while let Some(start_rel) = text[cursor..].find("[[") {
         let start = cursor + start_rel;
         if start > cursor {
-            out.push(Event::Text(text[cursor..start].to_string()));
+            out.push(Event::Text(
+                text[cursor..start].to_string().into(),
+                sub(cursor, start),
+            ));
         }
         let after_open = start + 2;
         if let Some(end_rel) = text[after_open..].find("]]") {
             let end = after_open + end_rel;
             let raw = text[after_open..end].trim();
+            let whole = sub(start, end + 2);
             if let Some(link) = parse_wikilink(raw, options) {
-                out.push(Event::StartNode(NodeKind::Link));
+                let inner = sub(after_open, end);
+                out.push(Event::StartNode(NodeKind::Link, whole));
                 out.push(Event::Attribute {
-                    name: "href".to_string(),
-                    value: link.href,
+                    name: "href".into(),
+                    value: link.href.into(),
+                    span: inner,
                 });
                 out.push(Event::Attribute {
-                    name: "title".to_string(),
-                    value: link.title,
+                    name: "title".into(),
+                    value: link.title.into(),
+                    span: inner,
                 });
-                out.push(Event::Text(link.label));
-                out.push(Event::EndNode(NodeKind::Link));
+                out.push(Event::Text(link.label.into(), inner));
+                out.push(Event::EndNode(NodeKind::Link, whole));
             } else {
-                out.push(Event::Text(text[start..end + 2].to_string()));
+                out.push(Event::Text(text[start..end + 2].to_string().into(), whole));
             }
             cursor = end + 2;
         } else {
-            out.push(Event::Text(text[start..].to_string()));
+            out.push(Event::Text(
+                text[start..].to_string().into(),
+                sub(start, text.len()),
+            ));
             cursor = text.len();
             break;
         }
     }
 
     if cursor < text.len() {
-        out.push(Event::Text(text[cursor..].to_string()));
+        out.push(Event::Text(
+            text[cursor..].to_string().into(),
+            sub(cursor, text.len()),
+        ));
     }
 }
 
@@ -176,10 +198,15 @@ mod tests {
     #[test]
     fn rewrites_text_with_wikilink_to_link_events() {
         let mut out = Vec::new();
-        emit_wikilink_text("Nisi [[Anim Esta]] id", &mut out, &WikiOptions::default());
+        emit_wikilink_text(
+            "Nisi [[Anim Esta]] id",
+            None,
+            &mut out,
+            &WikiOptions::default(),
+        );
         assert!(out
             .iter()
-            .any(|ev| matches!(ev, Event::StartNode(NodeKind::Link))));
+            .any(|ev| matches!(ev, Event::StartNode(NodeKind::Link, _))));
     }
 
     #[test]
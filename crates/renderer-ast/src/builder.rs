@@ -1,4 +1,4 @@
-use pendon_core::{Event, NodeKind, Severity};
+use pendon_core::{AttrValue, Diagnostic, Event, NodeKind, Position, Span};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 use serde::Serialize as DeriveSerialize;
 use std::collections::BTreeMap;
@@ -7,7 +7,7 @@ struct AstNode {
     kind: String,
     text: Option<String>,
     children: Vec<AstNode>,
-    attrs: BTreeMap<String, String>,
+    attrs: BTreeMap<String, AttrValue<'static>>,
 }
 
 pub(super) struct AstDocument {
@@ -18,8 +18,56 @@ pub(super) struct AstDocument {
 
 #[derive(DeriveSerialize)]
 struct AstDiag {
-    severity: String,
+    severity: &'static str,
+    code: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plugin: Option<String>,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<AstSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related: Vec<AstRelated>,
+}
+
+#[derive(DeriveSerialize)]
+struct AstRelated {
+    message: String,
+    span: AstSpan,
+}
+
+#[derive(DeriveSerialize)]
+struct AstPos {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+#[derive(DeriveSerialize)]
+struct AstSpan {
+    start: AstPos,
+    end: AstPos,
+}
+
+impl From<Position> for AstPos {
+    fn from(p: Position) -> Self {
+        AstPos {
+            offset: p.offset,
+            line: p.line,
+            column: p.column,
+        }
+    }
+}
+
+impl From<Span> for AstSpan {
+    fn from(sp: Span) -> Self {
+        AstSpan {
+            start: sp.start.into(),
+            end: sp.end.into(),
+        }
+    }
 }
 
 impl Serialize for AstNode {
@@ -97,13 +145,11 @@ impl AstBuilder {
     fn consume(&mut self, events: &[Event]) {
         for ev in events {
             match ev {
-                Event::StartNode(kind) => self.handle_start(kind),
-                Event::EndNode(kind) => self.handle_end(kind),
-                Event::Text(text) => self.handle_text(text),
-                Event::Attribute { name, value } => self.handle_attribute(name, value),
-                Event::Diagnostic {
-                    severity, message, ..
-                } => self.handle_diagnostic(severity, message),
+                Event::StartNode(kind, _) => self.handle_start(kind),
+                Event::EndNode(kind, _) => self.handle_end(kind),
+                Event::Text(text, _) => self.handle_text(text),
+                Event::Attribute { name, value, .. } => self.handle_attribute(name, value),
+                Event::Diagnostic(diag) => self.handle_diagnostic(diag),
             }
         }
     }
@@ -125,6 +171,8 @@ impl AstBuilder {
             | NodeKind::Strong
             | NodeKind::Bold
             | NodeKind::Italic
+            | NodeKind::Strikethrough
+            | NodeKind::FootnoteReference
             | NodeKind::InlineCode
             | NodeKind::Link
             | NodeKind::HtmlInline => {
@@ -175,19 +223,30 @@ impl AstBuilder {
         }
     }
 
-    fn handle_attribute(&mut self, name: &str, value: &str) {
+    fn handle_attribute(&mut self, name: &str, value: &AttrValue) {
         if let Some(cur) = self.stack.last_mut() {
-            cur.attrs.insert(name.to_string(), value.to_string());
+            cur.attrs
+                .insert(name.to_string(), value.clone().into_owned());
         }
     }
 
-    fn handle_diagnostic(&mut self, severity: &Severity, message: &str) {
+    fn handle_diagnostic(&mut self, diag: &Diagnostic) {
         self.doc.diagnostics.push(AstDiag {
-            severity: match severity {
-                Severity::Warning => "Warning".to_string(),
-                Severity::Error => "Error".to_string(),
-            },
-            message: message.to_string(),
+            severity: diag.severity.name(),
+            code: diag.code.id.to_string(),
+            name: diag.code.name.to_string(),
+            plugin: diag.plugin.clone(),
+            message: diag.message.clone(),
+            span: diag.span.map(AstSpan::from),
+            help: diag.help.clone(),
+            related: diag
+                .related
+                .iter()
+                .map(|rel| AstRelated {
+                    message: rel.message.clone(),
+                    span: rel.span.into(),
+                })
+                .collect(),
         });
     }
 
@@ -276,10 +335,16 @@ impl AstBuilder {
         match kind {
             NodeKind::Paragraph => true,
             NodeKind::CodeFence => false,
-            NodeKind::Heading | NodeKind::ListItem => inline,
+            NodeKind::Heading
+            | NodeKind::ListItem
+            | NodeKind::DefinitionTerm
+            | NodeKind::DefinitionDescription
+            | NodeKind::FootnoteDefinition => inline,
             NodeKind::BulletList
             | NodeKind::OrderedList
+            | NodeKind::DefinitionList
             | NodeKind::Blockquote
+            | NodeKind::Callout
             | NodeKind::Table
             | NodeKind::TableHead
             | NodeKind::TableBody
@@ -292,6 +357,8 @@ impl AstBuilder {
             | NodeKind::Strong
             | NodeKind::Bold
             | NodeKind::Italic
+            | NodeKind::Strikethrough
+            | NodeKind::FootnoteReference
             | NodeKind::InlineCode
             | NodeKind::Link
             | NodeKind::Image
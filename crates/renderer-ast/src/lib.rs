@@ -1,5 +1,4 @@
 use pendon_core::Event;
-use serde_json;
 
 mod builder;
 use builder::build_ast_document;
@@ -15,16 +14,16 @@ pub fn render_ast_to_string_pretty(events: &[Event]) -> Result<String, serde_jso
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pendon_core::{Event, NodeKind};
+    use pendon_core::{AttrValue, Event, NodeKind};
     use serde_json::Value;
 
-    fn html_events(kind: NodeKind, text: &str) -> Vec<Event> {
+    fn html_events(kind: NodeKind, text: &str) -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(kind.clone()),
-            Event::Text(text.to_string()),
-            Event::EndNode(kind),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(kind.clone(), None),
+            Event::Text(text.to_string().into(), None),
+            Event::EndNode(kind, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
@@ -41,18 +40,18 @@ mod tests {
     #[test]
     fn html_inline_roundtrips() {
         let mut events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::StartNode(NodeKind::HtmlInline),
-            Event::Text("<span>ok</span>".to_string()),
-            Event::EndNode(NodeKind::HtmlInline),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::HtmlInline, None),
+            Event::Text("<span>ok</span>".into(), None),
+            Event::EndNode(NodeKind::HtmlInline, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
         let pretty = render_ast_to_string_pretty(&events).unwrap();
         assert!(pretty.contains("HtmlInline"));
 
-        events.push(Event::Text("ignored".to_string()));
+        events.push(Event::Text("ignored".into(), None));
         // ensure regular rendering still succeeds even with trailing text
         assert!(render_ast_to_string(&events).is_ok());
     }
@@ -60,21 +59,21 @@ mod tests {
     #[test]
     fn list_item_keeps_html_inline_in_order() {
         let events = vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::OrderedList),
-            Event::StartNode(NodeKind::ListItem),
-            Event::Text("Menekan tombol ".to_string()),
-            Event::StartNode(NodeKind::HtmlInline),
-            Event::Text("<kbd>".to_string()),
-            Event::EndNode(NodeKind::HtmlInline),
-            Event::Text("Tab".to_string()),
-            Event::StartNode(NodeKind::HtmlInline),
-            Event::Text("</kbd>".to_string()),
-            Event::EndNode(NodeKind::HtmlInline),
-            Event::Text(" pada keyboard.".to_string()),
-            Event::EndNode(NodeKind::ListItem),
-            Event::EndNode(NodeKind::OrderedList),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::OrderedList, None),
+            Event::StartNode(NodeKind::ListItem, None),
+            Event::Text("Menekan tombol ".into(), None),
+            Event::StartNode(NodeKind::HtmlInline, None),
+            Event::Text("<kbd>".into(), None),
+            Event::EndNode(NodeKind::HtmlInline, None),
+            Event::Text("Tab".into(), None),
+            Event::StartNode(NodeKind::HtmlInline, None),
+            Event::Text("</kbd>".into(), None),
+            Event::EndNode(NodeKind::HtmlInline, None),
+            Event::Text(" pada keyboard.".into(), None),
+            Event::EndNode(NodeKind::ListItem, None),
+            Event::EndNode(NodeKind::OrderedList, None),
+            Event::EndNode(NodeKind::Document, None),
         ];
 
         let output = render_ast_to_string(&events).unwrap();
@@ -93,4 +92,31 @@ mod tests {
         assert_eq!(item["children"][4]["type"], "Text");
         assert_eq!(item["children"][4]["text"], " pada keyboard.");
     }
+
+    #[test]
+    fn structured_attributes_are_json_values() {
+        let data = AttrValue::Map(vec![
+            ("title".into(), "Demo".into()),
+            ("count".into(), AttrValue::Int(3)),
+            ("tags".into(), AttrValue::List(vec!["a".into(), "b".into()])),
+        ]);
+        let events = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Frontmatter, None),
+            Event::Attribute {
+                name: "data".into(),
+                value: data,
+                span: None,
+            },
+            Event::EndNode(NodeKind::Frontmatter, None),
+            Event::EndNode(NodeKind::Document, None),
+        ];
+
+        let output = render_ast_to_string(&events).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let data = &parsed["children"][0]["attrs"]["data"];
+        assert_eq!(data["title"], "Demo");
+        assert_eq!(data["count"], 3);
+        assert_eq!(data["tags"][1], "b");
+    }
 }
@@ -1,38 +1,302 @@
-use pendon_core::{Event, Severity};
-use serde::Serialize;
+use pendon_core::{
+    AttrValue, Diagnostic, DiagnosticCode, Event, NodeKind, Position, Related, Severity, Span,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::{Read, Write};
 
 #[derive(Serialize)]
 #[serde(tag = "type")]
 enum Ev<'a> {
-    Start { node: String },
-    End { node: String },
-    Text { text: &'a str },
-    Attribute { name: &'a str, value: &'a str },
-    Diagnostic { severity: &'a str, message: &'a str },
+    Start {
+        node: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span: Option<SpanOut>,
+    },
+    End {
+        node: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span: Option<SpanOut>,
+    },
+    Text {
+        text: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span: Option<SpanOut>,
+    },
+    Attribute {
+        name: &'a str,
+        value: &'a AttrValue<'a>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span: Option<SpanOut>,
+    },
+    Diagnostic {
+        severity: &'a str,
+        code: &'a str,
+        name: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        plugin: Option<&'a str>,
+        message: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        span: Option<SpanOut>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        help: Option<&'a str>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        related: Vec<RelatedOut<'a>>,
+    },
+}
+
+#[derive(Serialize)]
+struct RelatedOut<'a> {
+    message: &'a str,
+    span: SpanOut,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PosOut {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpanOut {
+    start: PosOut,
+    end: PosOut,
+}
+
+fn pos_out(p: Position) -> PosOut {
+    PosOut {
+        offset: p.offset,
+        line: p.line,
+        column: p.column,
+    }
+}
+
+fn span_out(span: &Option<Span>) -> Option<SpanOut> {
+    span.map(pos_span)
+}
+
+fn pos_span(sp: Span) -> SpanOut {
+    SpanOut {
+        start: pos_out(sp.start),
+        end: pos_out(sp.end),
+    }
+}
+
+fn ev_out<'a>(ev: &'a Event<'a>) -> Ev<'a> {
+    match ev {
+        Event::StartNode(kind, span) => Ev::Start {
+            node: kind.name().into_owned(),
+            span: span_out(span),
+        },
+        Event::EndNode(kind, span) => Ev::End {
+            node: kind.name().into_owned(),
+            span: span_out(span),
+        },
+        Event::Text(s, span) => Ev::Text {
+            text: s,
+            span: span_out(span),
+        },
+        Event::Attribute { name, value, span } => Ev::Attribute {
+            name,
+            value,
+            span: span_out(span),
+        },
+        Event::Diagnostic(diag) => Ev::Diagnostic {
+            severity: diag.severity.name(),
+            code: &diag.code.id,
+            name: &diag.code.name,
+            plugin: diag.plugin.as_deref(),
+            message: &diag.message,
+            span: span_out(&diag.span),
+            help: diag.help.as_deref(),
+            related: diag
+                .related
+                .iter()
+                .map(|rel| RelatedOut {
+                    message: &rel.message,
+                    span: pos_span(rel.span),
+                })
+                .collect(),
+        },
+    }
 }
 
 pub fn render_events_to_string(events: &[Event]) -> Result<String, serde_json::Error> {
-    let mut out: Vec<Ev> = Vec::with_capacity(events.len());
-    for ev in events {
-        match ev {
-            Event::StartNode(kind) => out.push(Ev::Start {
-                node: kind.name().into_owned(),
-            }),
-            Event::EndNode(kind) => out.push(Ev::End {
-                node: kind.name().into_owned(),
-            }),
-            Event::Text(s) => out.push(Ev::Text { text: s }),
-            Event::Attribute { name, value } => out.push(Ev::Attribute { name, value }),
-            Event::Diagnostic {
-                severity, message, ..
-            } => out.push(Ev::Diagnostic {
-                severity: match severity {
-                    Severity::Warning => "Warning",
-                    Severity::Error => "Error",
-                },
+    let out: Vec<Ev> = events.iter().map(ev_out).collect();
+    serde_json::to_string(&out)
+}
+
+/// Streaming variant of [`render_events_to_string`]: serializes each event as
+/// it is pulled, producing the same compact JSON array.
+pub fn render_events_to_writer<'a, I, W>(events: I, mut writer: W) -> Result<(), serde_json::Error>
+where
+    I: IntoIterator<Item = Event<'a>>,
+    W: Write,
+{
+    writer.write_all(b"[").map_err(serde_json::Error::io)?;
+    for (idx, ev) in events.into_iter().enumerate() {
+        if idx > 0 {
+            writer.write_all(b",").map_err(serde_json::Error::io)?;
+        }
+        serde_json::to_writer(&mut writer, &ev_out(&ev))?;
+    }
+    writer.write_all(b"]").map_err(serde_json::Error::io)
+}
+
+// Owned mirror of `Ev` for reading the format back in
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum EvIn {
+    Start {
+        node: String,
+        span: Option<SpanOut>,
+    },
+    End {
+        node: String,
+        span: Option<SpanOut>,
+    },
+    Text {
+        text: String,
+        span: Option<SpanOut>,
+    },
+    Attribute {
+        name: String,
+        value: AttrValue<'static>,
+        span: Option<SpanOut>,
+    },
+    Diagnostic {
+        severity: SeverityIn,
+        code: String,
+        name: String,
+        plugin: Option<String>,
+        message: String,
+        span: Option<SpanOut>,
+        help: Option<String>,
+        #[serde(default)]
+        related: Vec<RelatedIn>,
+    },
+}
+
+#[derive(Deserialize)]
+enum SeverityIn {
+    Warning,
+    Error,
+}
+
+#[derive(Deserialize)]
+struct RelatedIn {
+    message: String,
+    span: SpanOut,
+}
+
+fn pos_in(p: PosOut) -> Position {
+    Position::new(p.offset, p.line, p.column)
+}
+
+fn span_in(sp: SpanOut) -> Span {
+    Span::new(pos_in(sp.start), pos_in(sp.end))
+}
+
+fn ev_in(ev: EvIn) -> Event<'static> {
+    match ev {
+        EvIn::Start { node, span } => {
+            Event::StartNode(NodeKind::from_name(&node), span.map(span_in))
+        }
+        EvIn::End { node, span } => Event::EndNode(NodeKind::from_name(&node), span.map(span_in)),
+        EvIn::Text { text, span } => Event::Text(Cow::Owned(text), span.map(span_in)),
+        EvIn::Attribute { name, value, span } => Event::Attribute {
+            name: Cow::Owned(name),
+            value,
+            span: span.map(span_in),
+        },
+        EvIn::Diagnostic {
+            severity,
+            code,
+            name,
+            plugin,
+            message,
+            span,
+            help,
+            related,
+        } => {
+            let severity = match severity {
+                SeverityIn::Warning => Severity::Warning,
+                SeverityIn::Error => Severity::Error,
+            };
+            let code = DiagnosticCode {
+                id: Cow::Owned(code),
+                name: Cow::Owned(name),
+            };
+            Event::Diagnostic(Box::new(Diagnostic {
+                severity,
+                code,
+                plugin,
                 message,
-            }),
+                span: span.map(span_in),
+                help,
+                related: related
+                    .into_iter()
+                    .map(|rel| Related {
+                        span: span_in(rel.span),
+                        message: rel.message,
+                    })
+                    .collect(),
+            }))
         }
     }
-    serde_json::to_string(&out)
+}
+
+/// Reads back the JSON written by [`render_events_to_string`] (compact or
+/// pretty), so a parsed stream can be cached or handed to another process.
+///
+/// Node names that are not builtin kinds come back as `NodeKind::Custom`.
+/// The stream is not validated; run `pendon_core::validate` on untrusted input.
+pub fn events_from_str(input: &str) -> Result<Vec<Event<'static>>, serde_json::Error> {
+    let evs: Vec<EvIn> = serde_json::from_str(input)?;
+    Ok(evs.into_iter().map(ev_in).collect())
+}
+
+/// [`events_from_str`] over any reader.
+pub fn events_from_reader<R: Read>(reader: R) -> Result<Vec<Event<'static>>, serde_json::Error> {
+    let evs: Vec<EvIn> = serde_json::from_reader(reader)?;
+    Ok(evs.into_iter().map(ev_in).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pendon_core::{codes, parse, Options};
+
+    #[test]
+    fn rendered_events_read_back_unchanged() {
+        let mut events = parse("# Title\n\nsome \"text\"\n", &Options::default());
+        let span = events[1].span().unwrap();
+        events.insert(
+            2,
+            Event::Attribute {
+                name: "data".into(),
+                value: AttrValue::Map(vec![
+                    ("n".into(), AttrValue::Int(3)),
+                    ("tags".into(), AttrValue::List(vec!["a".into()])),
+                ]),
+                span: Some(span),
+            },
+        );
+        events.insert(
+            3,
+            Diagnostic::warning(codes::LINE_TOO_LONG, "line 1 is long")
+                .with_span(Some(span))
+                .with_plugin("markdown")
+                .with_help("wrap it")
+                .with_related(span, "here")
+                .into(),
+        );
+        events.insert(4, Event::StartNode(NodeKind::Custom("Quiz".into()), None));
+        events.insert(5, Event::EndNode(NodeKind::Custom("Quiz".into()), None));
+
+        let json = render_events_to_string(&events).unwrap();
+        assert_eq!(events_from_str(&json).unwrap(), events);
+        assert_eq!(events_from_reader(json.as_bytes()).unwrap(), events);
+    }
 }
@@ -2,7 +2,11 @@ use pendon_core::Event;
 use serde_json::Value;
 
 use crate::events_to_ast_value;
-use crate::utils::{attr_bool, attr_str, children, escape_html};
+use crate::utils::{
+    attr_bool, attr_str, attr_usize, callout_heading, callout_open, children, escape_html,
+    extra_attrs, footnote_backrefs, footnote_index, footnote_ref, is_footnote, table_cell_open,
+    task_checkbox,
+};
 
 pub fn render_html(events: &[Event]) -> String {
     let ast = events_to_ast_value(events);
@@ -15,15 +19,28 @@ fn render_node(v: &Value, out: &mut String) {
     if let Some(kind) = v.get("type").and_then(|t| t.as_str()) {
         match kind {
             "Document" => {
-                if let Some(children) = children(v) {
-                    for child in children {
-                        render_node(child, out);
+                // Consecutive footnotes share one list at the end of the page
+                let mut in_notes = false;
+                for child in children(v).unwrap_or_default() {
+                    if is_footnote(child) != in_notes {
+                        in_notes = !in_notes;
+                        out.push_str(if in_notes {
+                            "<section class=\"footnotes\">\n<ol>\n"
+                        } else {
+                            "</ol>\n</section>\n"
+                        });
                     }
+                    render_node(child, out);
+                }
+                if in_notes {
+                    out.push_str("</ol>\n</section>\n");
                 }
             }
             "Frontmatter" => {}
             "Paragraph" => {
-                out.push_str("<p>");
+                out.push_str("<p");
+                extra_attrs(v, &[], out);
+                out.push('>');
                 render_children(v, out);
                 out.push_str("</p>\n");
             }
@@ -32,15 +49,27 @@ fn render_node(v: &Value, out: &mut String) {
                 render_children(v, out);
                 out.push_str("</blockquote>\n");
             }
+            "Callout" => {
+                callout_open(v, out);
+                out.push('\n');
+                callout_heading(v, out);
+                out.push('\n');
+                render_children(v, out);
+                if attr_bool(v, "foldable") {
+                    out.push_str("</details>\n");
+                }
+                out.push_str("</aside>\n");
+            }
             "Heading" => {
-                let level = attr_str(v, "level").unwrap_or("1");
+                let level = attr_usize(v, "level").unwrap_or(1).to_string();
                 out.push('<');
                 out.push('h');
-                out.push_str(level);
+                out.push_str(&level);
+                extra_attrs(v, &["level"], out);
                 out.push('>');
                 render_node_text_or_children(v, out);
                 out.push_str("</h");
-                out.push_str(level);
+                out.push_str(&level);
                 out.push_str(">\n");
             }
             "Section" => {
@@ -49,7 +78,7 @@ fn render_node(v: &Value, out: &mut String) {
                     out.push(' ');
                     out.push_str("id=\"");
                     escape_html(id, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
                 out.push_str(">\n");
                 render_children(v, out);
@@ -65,8 +94,9 @@ fn render_node(v: &Value, out: &mut String) {
                     out.push(' ');
                     out.push_str("class=\"");
                     escape_html(class, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["class", "lang", "raw_html", "syntect_debug"], out);
                 out.push_str("><code>");
                 let raw = attr_str(v, "raw_html");
                 if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
@@ -90,11 +120,11 @@ fn render_node(v: &Value, out: &mut String) {
             }
             "OrderedList" => {
                 out.push_str("<ol");
-                if let Some(start) = attr_str(v, "start") {
+                if let Some(start) = attr_usize(v, "start") {
                     out.push(' ');
                     out.push_str("start=\"");
-                    out.push_str(start);
-                    out.push_str("\"");
+                    out.push_str(&start.to_string());
+                    out.push('"');
                 }
                 out.push_str(">\n");
                 render_children(v, out);
@@ -102,12 +132,44 @@ fn render_node(v: &Value, out: &mut String) {
             }
             "ListItem" => {
                 out.push_str("<li>");
+                if attr_bool(v, "task") {
+                    task_checkbox(v, out);
+                    out.push(' ');
+                }
                 if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
                     escape_html(text, out);
                 }
                 render_children(v, out);
                 out.push_str("</li>\n");
             }
+            "DefinitionList" => {
+                out.push_str("<dl>\n");
+                render_children(v, out);
+                out.push_str("</dl>\n");
+            }
+            "DefinitionTerm" | "DefinitionDescription" => {
+                let tag = if kind == "DefinitionTerm" { "dt" } else { "dd" };
+                out.push('<');
+                out.push_str(tag);
+                extra_attrs(v, &[], out);
+                out.push('>');
+                if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
+                    escape_html(text, out);
+                }
+                render_children(v, out);
+                out.push_str("</");
+                out.push_str(tag);
+                out.push_str(">\n");
+            }
+            "FootnoteDefinition" => {
+                out.push_str("<li id=\"fn-");
+                escape_html(&footnote_index(v), out);
+                out.push_str("\">");
+                render_node_text_or_children(v, out);
+                out.push(' ');
+                footnote_backrefs(v, out);
+                out.push_str("</li>\n");
+            }
             "Table" => {
                 out.push_str("<table>\n");
                 render_children(v, out);
@@ -129,10 +191,7 @@ fn render_node(v: &Value, out: &mut String) {
                 out.push_str("</tr>\n");
             }
             "TableCell" => {
-                let tag = if attr_bool(v, "header") { "th" } else { "td" };
-                out.push('<');
-                out.push_str(tag);
-                out.push('>');
+                let tag = table_cell_open(v, out);
                 render_children(v, out);
                 out.push_str("</");
                 out.push_str(tag);
@@ -158,6 +217,12 @@ fn render_node(v: &Value, out: &mut String) {
                 render_children(v, out);
                 out.push_str("</i>");
             }
+            "Strikethrough" => {
+                out.push_str("<del>");
+                render_children(v, out);
+                out.push_str("</del>");
+            }
+            "FootnoteReference" => footnote_ref(v, out),
             "InlineCode" => {
                 out.push_str("<code>");
                 render_children(v, out);
@@ -169,14 +234,15 @@ fn render_node(v: &Value, out: &mut String) {
                     out.push(' ');
                     out.push_str("href=\"");
                     escape_html(href, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
                 if let Some(title) = attr_str(v, "title") {
                     out.push(' ');
                     out.push_str("title=\"");
                     escape_html(title, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["href", "title"], out);
                 out.push('>');
                 render_children(v, out);
                 out.push_str("</a>");
@@ -187,14 +253,15 @@ fn render_node(v: &Value, out: &mut String) {
                 out.push(' ');
                 out.push_str("alt=\"");
                 escape_html(alt, out);
-                out.push_str("\"");
+                out.push('"');
 
                 if let Some(src) = attr_str(v, "src") {
                     out.push(' ');
                     out.push_str("src=\"");
                     escape_html(src, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["alt", "src"], out);
 
                 out.push_str(" />");
             }
@@ -19,48 +19,231 @@ mod tests {
     use super::*;
     use pendon_core::{Event, NodeKind};
 
-    fn html_block_events() -> Vec<Event> {
+    fn html_block_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::HtmlBlock),
-            Event::Text("<div>raw</div>".to_string()),
-            Event::EndNode(NodeKind::HtmlBlock),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::HtmlBlock, None),
+            Event::Text("<div>raw</div>".into(), None),
+            Event::EndNode(NodeKind::HtmlBlock, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
-    fn html_inline_events() -> Vec<Event> {
+    fn html_inline_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::StartNode(NodeKind::HtmlInline),
-            Event::Text("<span>inline</span>".to_string()),
-            Event::EndNode(NodeKind::HtmlInline),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::HtmlInline, None),
+            Event::Text("<span>inline</span>".into(), None),
+            Event::EndNode(NodeKind::HtmlInline, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
-    fn link_with_title_events() -> Vec<Event> {
+    fn link_with_title_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::StartNode(NodeKind::Link),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::Link, None),
             Event::Attribute {
-                name: "href".to_string(),
-                value: "https://example.com".to_string(),
+                name: "href".into(),
+                value: "https://example.com".into(),
+                span: None,
             },
             Event::Attribute {
-                name: "title".to_string(),
-                value: "Baz Wax".to_string(),
+                name: "title".into(),
+                value: "Baz Wax".into(),
+                span: None,
             },
-            Event::Text("foo".to_string()),
-            Event::EndNode(NodeKind::Link),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("foo".into(), None),
+            Event::EndNode(NodeKind::Link, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
+    fn strikethrough_events() -> Vec<Event<'static>> {
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::Strikethrough, None),
+            Event::Text("gone".into(), None),
+            Event::EndNode(NodeKind::Strikethrough, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn task_list_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str| Event::Attribute {
+            name: name.into(),
+            value: true.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::BulletList, None),
+            Event::StartNode(NodeKind::ListItem, None),
+            attr("task"),
+            attr("checked"),
+            Event::Text("done".into(), None),
+            Event::EndNode(NodeKind::ListItem, None),
+            Event::StartNode(NodeKind::ListItem, None),
+            attr("task"),
+            Event::Text("todo".into(), None),
+            Event::EndNode(NodeKind::ListItem, None),
+            Event::EndNode(NodeKind::BulletList, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn footnote_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: i64| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("see".into(), None),
+            Event::StartNode(NodeKind::FootnoteReference, None),
+            attr("index", 1),
+            attr("ref", 1),
+            Event::EndNode(NodeKind::FootnoteReference, None),
+            Event::StartNode(NodeKind::FootnoteReference, None),
+            attr("index", 1),
+            attr("ref", 2),
+            Event::EndNode(NodeKind::FootnoteReference, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::FootnoteDefinition, None),
+            attr("index", 1),
+            attr("refs", 2),
+            Event::Text("note".into(), None),
+            Event::EndNode(NodeKind::FootnoteDefinition, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn aligned_table_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Table, None),
+            Event::StartNode(NodeKind::TableHead, None),
+            Event::StartNode(NodeKind::TableRow, None),
+            Event::StartNode(NodeKind::TableCell, None),
+            attr("header", "1"),
+            attr("align", "center"),
+            Event::Text("A".into(), None),
+            Event::EndNode(NodeKind::TableCell, None),
+            Event::EndNode(NodeKind::TableRow, None),
+            Event::EndNode(NodeKind::TableHead, None),
+            Event::StartNode(NodeKind::TableBody, None),
+            Event::StartNode(NodeKind::TableRow, None),
+            Event::StartNode(NodeKind::TableCell, None),
+            attr("align", "center"),
+            Event::Text("1".into(), None),
+            Event::EndNode(NodeKind::TableCell, None),
+            Event::EndNode(NodeKind::TableRow, None),
+            Event::EndNode(NodeKind::TableBody, None),
+            Event::EndNode(NodeKind::Table, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn attributed_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Heading, None),
+            attr("level", "2"),
+            attr("id", "top"),
+            attr("class", "lead"),
+            Event::Text("Title".into(), None),
+            Event::EndNode(NodeKind::Heading, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            attr("class", "note"),
+            Event::StartNode(NodeKind::Link, None),
+            attr("href", "/x"),
+            attr("target", "_blank"),
+            Event::Text("a".into(), None),
+            Event::EndNode(NodeKind::Link, None),
+            Event::StartNode(NodeKind::Image, None),
+            attr("alt", "i"),
+            attr("src", "p.png"),
+            attr("width", "50"),
+            Event::EndNode(NodeKind::Image, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn definition_list_events() -> Vec<Event<'static>> {
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::DefinitionList, None),
+            Event::StartNode(NodeKind::DefinitionTerm, None),
+            Event::Attribute {
+                name: "class".into(),
+                value: "a".into(),
+                span: None,
+            },
+            Event::Text("Term".into(), None),
+            Event::EndNode(NodeKind::DefinitionTerm, None),
+            Event::StartNode(NodeKind::DefinitionDescription, None),
+            Event::Text("one".into(), None),
+            Event::EndNode(NodeKind::DefinitionDescription, None),
+            Event::StartNode(NodeKind::DefinitionDescription, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("two".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::DefinitionDescription, None),
+            Event::EndNode(NodeKind::DefinitionList, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn callout_events(foldable: bool) -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        let mut events = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Callout, None),
+            attr("kind", "warning"),
+        ];
+        if foldable {
+            events.extend([
+                attr("title", "Mind <this>"),
+                Event::Attribute {
+                    name: "foldable".into(),
+                    value: true.into(),
+                    span: None,
+                },
+            ]);
+        }
+        events.extend([
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("careful".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Callout, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]);
+        events
+    }
+
     #[test]
     fn html_block_passes_through_in_compact_mode() {
         let output = render_html(&html_block_events());
@@ -87,4 +270,137 @@ mod tests {
             "pretty output = {pretty}"
         );
     }
+
+    #[test]
+    fn strikethrough_renders_as_del_in_html_modes() {
+        let compact = render_html(&strikethrough_events());
+        assert!(
+            compact.contains("<del>gone</del>"),
+            "compact output = {compact}"
+        );
+
+        let pretty = render_html_pretty(&strikethrough_events());
+        assert!(pretty.contains("<del>"), "pretty output = {pretty}");
+        assert!(pretty.contains("gone"), "pretty output = {pretty}");
+    }
+
+    #[test]
+    fn task_items_render_disabled_checkboxes_in_html_modes() {
+        let compact = render_html(&task_list_events());
+        assert!(
+            compact.contains("<li><input type=\"checkbox\" disabled checked /> done</li>"),
+            "compact output = {compact}"
+        );
+        assert!(
+            compact.contains("<li><input type=\"checkbox\" disabled /> todo</li>"),
+            "compact output = {compact}"
+        );
+
+        let pretty = render_html_pretty(&task_list_events());
+        assert!(
+            pretty.contains("<input type=\"checkbox\" disabled checked />\n"),
+            "pretty output = {pretty}"
+        );
+    }
+
+    #[test]
+    fn footnotes_render_with_back_references_in_html_modes() {
+        let compact = render_html(&footnote_events());
+        assert!(
+            compact.contains(
+                "<sup><a href=\"#fn-1\" id=\"fnref-1-2\" class=\"footnote-ref\">1</a></sup>"
+            ),
+            "compact output = {compact}"
+        );
+        assert!(
+            compact.contains("<section class=\"footnotes\">\n<ol>\n<li id=\"fn-1\">note <a href=\"#fnref-1\" class=\"footnote-backref\">↩</a> <a href=\"#fnref-1-2\" class=\"footnote-backref\">↩</a></li>\n</ol>\n</section>\n"),
+            "compact output = {compact}"
+        );
+
+        let pretty = render_html_pretty(&footnote_events());
+        assert!(
+            pretty.contains("<section class=\"footnotes\">\n  <ol>\n    <li id=\"fn-1\">\n"),
+            "pretty output = {pretty}"
+        );
+        assert!(
+            pretty.ends_with("  </ol>\n</section>\n"),
+            "pretty output = {pretty}"
+        );
+    }
+
+    #[test]
+    fn table_cells_render_column_alignment_in_html_modes() {
+        let compact = render_html(&aligned_table_events());
+        assert!(
+            compact.contains("<th align=\"center\">A</th>"),
+            "compact output = {compact}"
+        );
+        assert!(
+            compact.contains("<td align=\"center\">1</td>"),
+            "compact output = {compact}"
+        );
+
+        let pretty = render_html_pretty(&aligned_table_events());
+        assert!(
+            pretty.contains("<td align=\"center\">1</td>\n"),
+            "pretty output = {pretty}"
+        );
+    }
+
+    #[test]
+    fn callouts_render_as_asides_in_html_modes() {
+        let compact = render_html(&callout_events(false));
+        assert_eq!(
+            compact,
+            "<aside class=\"callout callout-warning\">\n<p class=\"callout-title\">Warning</p>\n<p>careful</p>\n</aside>\n"
+        );
+        let folded = render_html(&callout_events(true));
+        assert!(
+            folded.contains("<details><summary>Mind &lt;this&gt;</summary>\n<p>careful</p>\n</details>\n</aside>"),
+            "compact output = {folded}"
+        );
+
+        let pretty = render_html_pretty(&callout_events(true));
+        assert!(
+            pretty.starts_with("<aside class=\"callout callout-warning\">\n  <details><summary>"),
+            "pretty output = {pretty}"
+        );
+        assert!(
+            pretty.ends_with("  </details>\n</aside>\n"),
+            "pretty output = {pretty}"
+        );
+    }
+
+    #[test]
+    fn attribute_blocks_render_on_their_tags_in_html_modes() {
+        let compact = render_html(&attributed_events());
+        assert_eq!(
+            compact,
+            "<h2 class=\"lead\" id=\"top\">Title</h2>\n<p class=\"note\"><a href=\"/x\" target=\"_blank\">a</a><img alt=\"i\" src=\"p.png\" width=\"50\" /></p>\n"
+        );
+        let pretty = render_html_pretty(&attributed_events());
+        assert!(
+            pretty.starts_with("<h2 class=\"lead\" id=\"top\">\n"),
+            "pretty output = {pretty}"
+        );
+        assert!(
+            pretty.contains("<p class=\"note\">\n"),
+            "pretty output = {pretty}"
+        );
+    }
+
+    #[test]
+    fn definition_lists_render_as_dl_in_html_modes() {
+        let compact = render_html(&definition_list_events());
+        assert_eq!(
+            compact,
+            "<dl>\n<dt class=\"a\">Term</dt>\n<dd>one</dd>\n<dd><p>two</p>\n</dd>\n</dl>\n"
+        );
+        let pretty = render_html_pretty(&definition_list_events());
+        assert!(
+            pretty.starts_with("<dl>\n  <dt class=\"a\">"),
+            "pretty output = {pretty}"
+        );
+        assert!(pretty.ends_with("</dl>\n"), "pretty output = {pretty}");
+    }
 }
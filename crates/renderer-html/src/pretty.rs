@@ -2,7 +2,11 @@ use pendon_core::Event;
 use serde_json::Value;
 
 use crate::events_to_ast_value;
-use crate::utils::{attr_bool, attr_str, children, escape_html};
+use crate::utils::{
+    attr_bool, attr_str, attr_usize, callout_heading, callout_open, children, escape_html,
+    extra_attrs, footnote_backrefs, footnote_index, footnote_ref, is_footnote, table_cell_open,
+    task_checkbox,
+};
 
 pub fn render_html_pretty(events: &[Event]) -> String {
     let ast = events_to_ast_value(events);
@@ -22,16 +26,41 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
     if let Some(kind) = v.get("type").and_then(|t| t.as_str()) {
         match kind {
             "Document" => {
-                if let Some(children) = children(v) {
-                    for child in children {
-                        render_node(child, out, indent);
+                // Consecutive footnotes share one list at the end of the page
+                let mut in_notes = false;
+                for child in children(v).unwrap_or_default() {
+                    if is_footnote(child) != in_notes {
+                        in_notes = !in_notes;
+                        if in_notes {
+                            pad(out, *indent);
+                            out.push_str("<section class=\"footnotes\">\n");
+                            pad(out, *indent + 1);
+                            out.push_str("<ol>\n");
+                            *indent += 2;
+                        } else {
+                            *indent -= 2;
+                            pad(out, *indent + 1);
+                            out.push_str("</ol>\n");
+                            pad(out, *indent);
+                            out.push_str("</section>\n");
+                        }
                     }
+                    render_node(child, out, indent);
+                }
+                if in_notes {
+                    *indent -= 2;
+                    pad(out, *indent + 1);
+                    out.push_str("</ol>\n");
+                    pad(out, *indent);
+                    out.push_str("</section>\n");
                 }
             }
             "Frontmatter" => {}
             "Paragraph" => {
                 pad(out, *indent);
-                out.push_str("<p>\n");
+                out.push_str("<p");
+                extra_attrs(v, &[], out);
+                out.push_str(">\n");
                 *indent += 1;
                 render_children(v, out, indent);
                 *indent -= 1;
@@ -47,12 +76,33 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 pad(out, *indent);
                 out.push_str("</blockquote>\n");
             }
+            "Callout" => {
+                let foldable = attr_bool(v, "foldable");
+                pad(out, *indent);
+                callout_open(v, out);
+                out.push('\n');
+                pad(out, *indent + 1);
+                callout_heading(v, out);
+                out.push('\n');
+                // Content of a foldable callout sits inside its `<details>`
+                let depth = if foldable { 2 } else { 1 };
+                *indent += depth;
+                render_children(v, out, indent);
+                *indent -= depth;
+                if foldable {
+                    pad(out, *indent + 1);
+                    out.push_str("</details>\n");
+                }
+                pad(out, *indent);
+                out.push_str("</aside>\n");
+            }
             "Heading" => {
-                let level = attr_str(v, "level").unwrap_or("1");
+                let level = attr_usize(v, "level").unwrap_or(1).to_string();
                 pad(out, *indent);
                 out.push('<');
                 out.push('h');
-                out.push_str(level);
+                out.push_str(&level);
+                extra_attrs(v, &["level"], out);
                 out.push('>');
                 out.push('\n');
                 *indent += 1;
@@ -60,7 +110,7 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 *indent -= 1;
                 pad(out, *indent);
                 out.push_str("</h");
-                out.push_str(level);
+                out.push_str(&level);
                 out.push_str(">\n");
             }
             "Section" => {
@@ -70,7 +120,7 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                     out.push(' ');
                     out.push_str("id=\"");
                     escape_html(id, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
                 out.push_str(">\n");
                 *indent += 1;
@@ -91,8 +141,9 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                     out.push(' ');
                     out.push_str("class=\"");
                     escape_html(class, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["class", "lang", "raw_html", "syntect_debug"], out);
                 out.push_str("><code>");
                 let raw = attr_str(v, "raw_html");
                 if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
@@ -121,11 +172,11 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
             "OrderedList" => {
                 pad(out, *indent);
                 out.push_str("<ol");
-                if let Some(start) = attr_str(v, "start") {
+                if let Some(start) = attr_usize(v, "start") {
                     out.push(' ');
                     out.push_str("start=\"");
-                    out.push_str(start);
-                    out.push_str("\"");
+                    out.push_str(&start.to_string());
+                    out.push('"');
                 }
                 out.push_str(">\n");
                 *indent += 1;
@@ -138,6 +189,11 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 pad(out, *indent);
                 out.push_str("<li>\n");
                 *indent += 1;
+                if attr_bool(v, "task") {
+                    pad(out, *indent);
+                    task_checkbox(v, out);
+                    out.push('\n');
+                }
                 if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
                     pad(out, *indent);
                     escape_html(text, out);
@@ -148,6 +204,49 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 pad(out, *indent);
                 out.push_str("</li>\n");
             }
+            "DefinitionList" => {
+                pad(out, *indent);
+                out.push_str("<dl>\n");
+                *indent += 1;
+                render_children(v, out, indent);
+                *indent -= 1;
+                pad(out, *indent);
+                out.push_str("</dl>\n");
+            }
+            "DefinitionTerm" | "DefinitionDescription" => {
+                let tag = if kind == "DefinitionTerm" { "dt" } else { "dd" };
+                pad(out, *indent);
+                out.push('<');
+                out.push_str(tag);
+                extra_attrs(v, &[], out);
+                out.push_str(">\n");
+                *indent += 1;
+                if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
+                    pad(out, *indent);
+                    escape_html(text, out);
+                    out.push('\n');
+                }
+                render_children(v, out, indent);
+                *indent -= 1;
+                pad(out, *indent);
+                out.push_str("</");
+                out.push_str(tag);
+                out.push_str(">\n");
+            }
+            "FootnoteDefinition" => {
+                pad(out, *indent);
+                out.push_str("<li id=\"fn-");
+                escape_html(&footnote_index(v), out);
+                out.push_str("\">\n");
+                *indent += 1;
+                render_node_text_or_children(v, out, indent, pad);
+                pad(out, *indent);
+                footnote_backrefs(v, out);
+                out.push('\n');
+                *indent -= 1;
+                pad(out, *indent);
+                out.push_str("</li>\n");
+            }
             "Table" => {
                 pad(out, *indent);
                 out.push_str("<table>\n");
@@ -185,11 +284,8 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 out.push_str("</tr>\n");
             }
             "TableCell" => {
-                let tag = if attr_bool(v, "header") { "th" } else { "td" };
                 pad(out, *indent);
-                out.push('<');
-                out.push_str(tag);
-                out.push('>');
+                let tag = table_cell_open(v, out);
                 render_children_inline(v, out);
                 out.push_str("</");
                 out.push_str(tag);
@@ -219,6 +315,17 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 render_children(v, out, indent);
                 out.push_str("</i>\n");
             }
+            "Strikethrough" => {
+                pad(out, *indent);
+                out.push_str("<del>");
+                render_children(v, out, indent);
+                out.push_str("</del>\n");
+            }
+            "FootnoteReference" => {
+                pad(out, *indent);
+                footnote_ref(v, out);
+                out.push('\n');
+            }
             "InlineCode" => {
                 pad(out, *indent);
                 out.push_str("<code>");
@@ -232,14 +339,15 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                     out.push(' ');
                     out.push_str("href=\"");
                     escape_html(href, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
                 if let Some(title) = attr_str(v, "title") {
                     out.push(' ');
                     out.push_str("title=\"");
                     escape_html(title, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["href", "title"], out);
                 out.push('>');
                 out.push('\n');
                 *indent += 1;
@@ -255,13 +363,14 @@ fn render_node(v: &Value, out: &mut String, indent: &mut usize) {
                 out.push(' ');
                 out.push_str("alt=\"");
                 escape_html(alt, out);
-                out.push_str("\"");
+                out.push('"');
                 if let Some(src) = attr_str(v, "src") {
                     out.push(' ');
                     out.push_str("src=\"");
                     escape_html(src, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["alt", "src"], out);
                 out.push_str(" />\n");
             }
             "Text" => {
@@ -12,8 +12,153 @@ pub(crate) fn attr_str<'a>(v: &'a Value, key: &str) -> Option<&'a str> {
         .and_then(|val| val.as_str())
 }
 
+/// Numeric attribute, whether stored as an integer or a decimal string.
+pub(crate) fn attr_usize(v: &Value, key: &str) -> Option<usize> {
+    match v.get("attrs").and_then(|a| a.get(key))? {
+        Value::Number(n) => n.as_u64().and_then(|n| usize::try_from(n).ok()),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Flag attribute set to `true` or, as older plugins emit it, `"1"`.
 pub(crate) fn attr_bool(v: &Value, key: &str) -> bool {
-    attr_str(v, key).map(|raw| raw == "1").unwrap_or(false)
+    match v.get("attrs").and_then(|a| a.get(key)) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s == "1",
+        _ => false,
+    }
+}
+
+/// Attributes beyond the ones a node's tag already renders, such as those
+/// set by a `{#id .class key=value}` block, as ` name="value"` pairs.
+pub(crate) fn extra_attrs(v: &Value, known: &[&str], out: &mut String) {
+    let Some(attrs) = v.get("attrs").and_then(|a| a.as_object()) else {
+        return;
+    };
+    for (name, value) in attrs {
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        let valid = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':'));
+        if known.contains(&name.as_str()) || !valid {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        escape_html(value, out);
+        out.push('"');
+    }
+}
+
+/// Disabled checkbox shown at the start of a GFM task list item.
+pub(crate) fn task_checkbox(v: &Value, out: &mut String) {
+    if attr_bool(v, "checked") {
+        out.push_str("<input type=\"checkbox\" disabled checked />");
+    } else {
+        out.push_str("<input type=\"checkbox\" disabled />");
+    }
+}
+
+/// Opening `<th>` or `<td>` tag of a table cell with its column alignment;
+/// returns the tag name for the closing tag.
+pub(crate) fn table_cell_open(v: &Value, out: &mut String) -> &'static str {
+    let tag = if attr_bool(v, "header") { "th" } else { "td" };
+    out.push('<');
+    out.push_str(tag);
+    if let Some(align) = attr_str(v, "align") {
+        out.push_str(" align=\"");
+        escape_html(align, out);
+        out.push('"');
+    }
+    out.push('>');
+    tag
+}
+
+/// Title of a callout: its `title`, else its kind capitalized.
+pub(crate) fn callout_title(v: &Value) -> String {
+    if let Some(title) = attr_str(v, "title") {
+        return title.to_string();
+    }
+    let mut kind = attr_str(v, "kind").unwrap_or("note").chars();
+    kind.next()
+        .map(|first| first.to_uppercase().chain(kind).collect())
+        .unwrap_or_default()
+}
+
+/// `<aside>` start tag of a callout, classed by its kind.
+pub(crate) fn callout_open(v: &Value, out: &mut String) {
+    out.push_str("<aside class=\"callout callout-");
+    escape_html(attr_str(v, "kind").unwrap_or("note"), out);
+    out.push_str("\">");
+}
+
+/// Title line of a callout; a foldable callout opens a `<details>` whose
+/// summary is the title.
+pub(crate) fn callout_heading(v: &Value, out: &mut String) {
+    if attr_bool(v, "foldable") {
+        out.push_str(if attr_bool(v, "open") {
+            "<details open><summary>"
+        } else {
+            "<details><summary>"
+        });
+        escape_html(&callout_title(v), out);
+        out.push_str("</summary>");
+    } else {
+        out.push_str("<p class=\"callout-title\">");
+        escape_html(&callout_title(v), out);
+        out.push_str("</p>");
+    }
+}
+
+pub(crate) fn is_footnote(v: &Value) -> bool {
+    v.get("type").and_then(|t| t.as_str()) == Some("FootnoteDefinition")
+}
+
+/// Number of a footnote or of the note a reference points to.
+pub(crate) fn footnote_index(v: &Value) -> String {
+    attr_usize(v, "index")
+        .map(|n| n.to_string())
+        .unwrap_or_default()
+}
+
+// `id` of the `nth` reference to note `index`; only repeats get a suffix
+fn footnote_ref_id(index: &str, nth: usize) -> String {
+    if nth == 1 {
+        format!("fnref-{index}")
+    } else {
+        format!("fnref-{index}-{nth}")
+    }
+}
+
+/// Superscript link from a footnote reference to its note.
+pub(crate) fn footnote_ref(v: &Value, out: &mut String) {
+    let index = footnote_index(v);
+    let id = footnote_ref_id(&index, attr_usize(v, "ref").unwrap_or(1));
+    out.push_str("<sup><a href=\"#fn-");
+    escape_html(&index, out);
+    out.push_str("\" id=\"");
+    escape_html(&id, out);
+    out.push_str("\" class=\"footnote-ref\">");
+    escape_html(&index, out);
+    out.push_str("</a></sup>");
+}
+
+/// Links from a footnote back to each of its references.
+pub(crate) fn footnote_backrefs(v: &Value, out: &mut String) {
+    let index = footnote_index(v);
+    let refs = attr_usize(v, "refs").unwrap_or(1);
+    for nth in 1..=refs {
+        if nth > 1 {
+            out.push(' ');
+        }
+        out.push_str("<a href=\"#");
+        escape_html(&footnote_ref_id(&index, nth), out);
+        out.push_str("\" class=\"footnote-backref\">↩</a>");
+    }
 }
 
 pub(crate) fn escape_html(input: &str, out: &mut String) {
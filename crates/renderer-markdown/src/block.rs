@@ -0,0 +1,297 @@
+use pendon_core::NodeKind;
+
+use crate::inline::{escape_line_starts, render_inline_children, render_inline_run};
+use crate::tree::{longest_run, prefix_lines, Child, Node};
+
+/// Blocks under `node`, each rendered without a trailing newline. Runs of
+/// inline children (list item text, text placed directly in a custom node)
+/// form one paragraph.
+pub(crate) fn render_blocks(node: &Node) -> Vec<String> {
+    collect_blocks(node, render_block)
+}
+
+fn collect_blocks(node: &Node, mut block: impl FnMut(&Node) -> Option<String>) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut run: Vec<&Child> = Vec::new();
+    for child in &node.children {
+        match child {
+            Child::Node(n) if !n.is_inline() => {
+                flush_paragraph(&mut run, &mut blocks);
+                blocks.extend(block(n));
+            }
+            inline => run.push(inline),
+        }
+    }
+    flush_paragraph(&mut run, &mut blocks);
+    blocks
+}
+
+fn flush_paragraph(run: &mut Vec<&Child>, blocks: &mut Vec<String>) {
+    let mut para = String::new();
+    render_inline_run(run.drain(..), &mut para);
+    let trimmed = para.trim_matches('\n');
+    if !trimmed.is_empty() {
+        blocks.push(escape_line_starts(trimmed));
+    }
+}
+
+fn render_block(node: &Node) -> Option<String> {
+    let mut out = String::new();
+    match node.kind {
+        NodeKind::Frontmatter => render_frontmatter(node, &mut out),
+        NodeKind::Paragraph => {
+            render_inline_children(node, &mut out);
+            out = escape_line_starts(out.trim_matches('\n'));
+            let attrs = node.attr_block(&[]);
+            if !attrs.is_empty() {
+                out.push(' ');
+                out.push_str(&attrs);
+            }
+        }
+        NodeKind::Heading => {
+            let level = node.attr_usize("level").unwrap_or(1).clamp(1, 6);
+            let mut content = String::new();
+            render_inline_children(node, &mut content);
+            out.push_str(&"#".repeat(level));
+            out.push(' ');
+            out.push_str(content.trim());
+            let attrs = node.attr_block(&["level"]);
+            if !attrs.is_empty() {
+                out.push(' ');
+                out.push_str(&attrs);
+            }
+        }
+        NodeKind::ThematicBreak => out.push_str("---"),
+        NodeKind::CodeFence => render_code_fence(node, &mut out),
+        NodeKind::Blockquote => {
+            let inner = render_blocks(node).join("\n\n");
+            prefix_lines(&inner, "> ", "> ", &mut out);
+        }
+        NodeKind::Callout => {
+            let mut body = format!(
+                "[!{}]",
+                node.attr_str("kind").unwrap_or("note").to_uppercase()
+            );
+            if node.attr_bool("foldable") {
+                body.push(if node.attr_bool("open") { '+' } else { '-' });
+            }
+            if let Some(title) = node.attr_str("title") {
+                body.push(' ');
+                body.push_str(title);
+            }
+            let inner = render_blocks(node).join("\n\n");
+            if !inner.is_empty() {
+                body.push('\n');
+                body.push_str(&inner);
+            }
+            prefix_lines(&body, "> ", "> ", &mut out);
+        }
+        NodeKind::BulletList | NodeKind::OrderedList => render_list(node, &mut out),
+        NodeKind::DefinitionList => render_definition_list(node, &mut out),
+        NodeKind::Table => render_table(node, &mut out),
+        NodeKind::FootnoteDefinition => {
+            let first = format!("[^{}]: ", node.attr_str("label").unwrap_or(""));
+            prefix_lines(&render_blocks(node).join("\n"), &first, "    ", &mut out);
+        }
+        NodeKind::HtmlBlock => out = node.plain_text().trim_end_matches('\n').to_string(),
+        // Sections and custom nodes have no Markdown syntax; keep their content
+        _ => out = render_blocks(node).join("\n\n"),
+    }
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+fn render_frontmatter(node: &Node, out: &mut String) {
+    let Some(data) = node.attr("data") else {
+        return;
+    };
+    out.push_str("---\n");
+    match serde_yaml::to_string(data) {
+        Ok(yaml) if yaml.trim() != "{}" => out.push_str(&yaml),
+        _ => {}
+    }
+    out.push_str("---");
+}
+
+fn render_code_fence(node: &Node, out: &mut String) {
+    let code = node.plain_text();
+    let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+    out.push_str(&fence);
+    let lang = node.attr_str("lang").unwrap_or("");
+    out.push_str(lang);
+    let attrs = node.attr_block(&["lang", "raw_html", "syntect_debug"]);
+    if !attrs.is_empty() {
+        if !lang.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(&attrs);
+    }
+    out.push('\n');
+    out.push_str(&code);
+    if !code.is_empty() && !code.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&fence);
+}
+
+// Lists are written tight: items on consecutive lines, continuation lines
+// indented to the item content. An item holding paragraphs or code separates
+// its blocks with blank lines.
+fn render_list(node: &Node, out: &mut String) {
+    let ordered = node.kind == NodeKind::OrderedList;
+    let start = node.attr_usize("start").unwrap_or(1);
+    for (idx, item) in child_nodes(node).enumerate() {
+        let marker = if ordered {
+            format!("{}. ", start + idx)
+        } else {
+            "- ".to_string()
+        };
+        if idx > 0 {
+            out.push('\n');
+        }
+        let body = render_item(item);
+        let first = match (item.attr_bool("task"), item.attr_bool("checked")) {
+            (true, true) => format!("{}[x] ", marker),
+            (true, false) => format!("{}[ ] ", marker),
+            _ => marker.clone(),
+        };
+        if body.is_empty() {
+            out.push_str(first.trim_end());
+        } else {
+            prefix_lines(&body, &first, &" ".repeat(marker.len()), out);
+        }
+    }
+}
+
+// Each term is followed by its `: ` definitions, whose later lines are
+// indented two columns. A term after a definition needs a blank line, or it
+// would read back as a continuation of that definition.
+fn render_definition_list(node: &Node, out: &mut String) {
+    let mut after_definition = false;
+    for (idx, child) in child_nodes(node).enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        if child.kind == NodeKind::DefinitionTerm {
+            if after_definition {
+                out.push('\n');
+            }
+            let mut term = String::new();
+            render_inline_children(child, &mut term);
+            out.push_str(&escape_line_starts(term.trim()));
+            after_definition = false;
+        } else {
+            prefix_lines(&render_item(child), ": ", "  ", out);
+            after_definition = true;
+        }
+    }
+}
+
+fn render_item(item: &Node) -> String {
+    let loose =
+        child_nodes(item).any(|n| matches!(n.kind, NodeKind::Paragraph | NodeKind::CodeFence));
+    let blocks = collect_blocks(item, |n| match n.kind {
+        // A fence would end the list when read back; indented code does not
+        NodeKind::CodeFence if n.attr("lang").is_none() => {
+            let code = n.plain_text();
+            let mut out = String::new();
+            prefix_lines(code.trim_end_matches('\n'), "    ", "    ", &mut out);
+            Some(out)
+        }
+        _ => render_block(n),
+    });
+    blocks.join(if loose { "\n\n" } else { "\n" })
+}
+
+fn render_table(node: &Node, out: &mut String) {
+    let mut head: Vec<Vec<String>> = Vec::new();
+    let mut body: Vec<Vec<String>> = Vec::new();
+    for child in &node.children {
+        let Child::Node(section) = child else {
+            continue;
+        };
+        let rows = section_rows(section);
+        if section.kind == NodeKind::TableHead && head.is_empty() {
+            head = rows;
+        } else {
+            body.extend(rows);
+        }
+    }
+    // Markdown tables always have a header row; promote the first body row
+    if head.is_empty() && !body.is_empty() {
+        head.push(body.remove(0));
+    }
+    let columns = head.iter().chain(&body).map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return;
+    }
+    let align = column_alignments(node);
+    let separator: Vec<String> = (0..columns)
+        .map(|col| match align.get(col).copied().flatten() {
+            Some("left") => ":---",
+            Some("center") => ":---:",
+            Some("right") => "---:",
+            _ => "---",
+        })
+        .map(str::to_string)
+        .collect();
+    let rows = head.iter().take(1).chain([&separator]).chain(&body);
+    for (idx, row) in rows.enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        out.push('|');
+        for col in 0..columns {
+            out.push(' ');
+            out.push_str(row.get(col).map(String::as_str).unwrap_or(""));
+            out.push_str(" |");
+        }
+    }
+}
+
+// Alignment of each column, read from the cells of the first row
+fn column_alignments<'e>(table: &Node<'e>) -> Vec<Option<&'e str>> {
+    let first_row = child_nodes(table).find_map(|section| {
+        if section.kind == NodeKind::TableRow {
+            Some(section)
+        } else {
+            child_nodes(section).next()
+        }
+    });
+    first_row
+        .map(|row| {
+            child_nodes(row)
+                .map(|cell| cell.attr_str("align"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn section_rows(section: &Node) -> Vec<Vec<String>> {
+    let rows: Vec<&Node> = if section.kind == NodeKind::TableRow {
+        vec![section]
+    } else {
+        child_nodes(section).collect()
+    };
+    rows.into_iter()
+        .map(|row| {
+            child_nodes(row)
+                .map(|cell| {
+                    let mut content = String::new();
+                    render_inline_children(cell, &mut content);
+                    content.trim().to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn child_nodes<'a, 'e>(node: &'a Node<'e>) -> impl Iterator<Item = &'a Node<'e>> {
+    node.children.iter().filter_map(|child| match child {
+        Child::Node(n) => Some(n),
+        Child::Text(_) => None,
+    })
+}
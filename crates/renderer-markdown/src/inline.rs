@@ -0,0 +1,167 @@
+use pendon_core::{entity_at, is_escapable, math_region_end, NodeKind};
+
+use crate::tree::{longest_run, Child, Node};
+
+pub(crate) fn render_inline_children(node: &Node, out: &mut String) {
+    render_inline_run(&node.children, out);
+}
+
+/// Render a run of inline children, escaping their text however plugins
+/// split it into events.
+pub(crate) fn render_inline_run<'a, 'e: 'a>(
+    children: impl IntoIterator<Item = &'a Child<'e>>,
+    out: &mut String,
+) {
+    let mut text = String::new();
+    for child in children {
+        match child {
+            Child::Text(t) => text.push_str(t),
+            Child::Node(n) => {
+                escape_text(&text, out);
+                text.clear();
+                render_inline(n, out);
+            }
+        }
+    }
+    escape_text(&text, out);
+}
+
+// Backslash-escape characters that would otherwise start inline markup.
+// `$…$` and `$$…$$` math, which plugin-markdown leaves as written, is kept
+// verbatim
+fn escape_text(text: &str, out: &mut String) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut idx = 0;
+    let mut at = 0;
+    while at < chars.len() {
+        if let Some(end) = math_region_end(&chars, at) {
+            for ch in &chars[at..end] {
+                out.push(*ch);
+                idx += ch.len_utf8();
+            }
+            at = end;
+            continue;
+        }
+        let ch = chars[at];
+        let next = chars.get(at + 1).copied();
+        let escape = match ch {
+            // `\$` is the math plugins' own escape and stays as written
+            '\\' => next.is_none_or(|c| is_escapable(c) && c != '$'),
+            '*' | '_' | '`' | '[' | ']' | '|' => true,
+            '~' => next == Some('~'),
+            '<' => next.is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')),
+            '&' => entity_at(&text[idx..]).is_some(),
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(ch);
+        idx += ch.len_utf8();
+        at += 1;
+        // Nor does the `$` after it open math
+        if ch == '\\' && next == Some('$') {
+            out.push('$');
+            idx += 1;
+            at += 1;
+        }
+    }
+}
+
+/// Escape a leading character that would turn a paragraph line into another
+/// block (heading, quote, list item, thematic break, definition).
+pub(crate) fn escape_line_starts(para: &str) -> String {
+    let mut out = String::with_capacity(para.len());
+    for (idx, line) in para.split('\n').enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        let after_digits = line[digits..].chars().next();
+        let definition = line.starts_with(": ") || line.starts_with(":\t");
+        if definition || matches!(line.chars().next(), Some('#' | '>' | '-' | '+' | '=')) {
+            out.push('\\');
+        } else if digits > 0 && matches!(after_digits, Some('.' | ')')) {
+            out.push_str(&line[..digits]);
+            out.push('\\');
+            out.push_str(&line[digits..]);
+            continue;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+pub(crate) fn render_inline(node: &Node, out: &mut String) {
+    match node.kind {
+        NodeKind::Emphasis => wrap(node, "*", out),
+        NodeKind::Strong => wrap(node, "**", out),
+        NodeKind::Strikethrough => wrap(node, "~~", out),
+        NodeKind::Bold => wrap(node, "__", out),
+        NodeKind::Italic => wrap(node, "_", out),
+        NodeKind::InlineCode => {
+            let code = node.plain_text();
+            let fence = "`".repeat(longest_run(&code, '`') + 1);
+            // A space keeps a leading or trailing backtick off the fence
+            let pad = if code.starts_with('`') || code.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            out.push_str(&fence);
+            out.push_str(pad);
+            out.push_str(&code);
+            out.push_str(pad);
+            out.push_str(&fence);
+        }
+        NodeKind::Link => {
+            out.push('[');
+            render_inline_children(node, out);
+            out.push(']');
+            let href = node.attr_str("href").unwrap_or("");
+            push_target(href, node.attr_str("title"), out);
+            out.push_str(&node.attr_block(&["href", "title"]));
+        }
+        NodeKind::Image => {
+            out.push_str("![");
+            escape_text(node.attr_str("alt").unwrap_or(""), out);
+            out.push(']');
+            let src = node.attr_str("src").unwrap_or("");
+            push_target(src, node.attr_str("title"), out);
+            out.push_str(&node.attr_block(&["alt", "src", "title"]));
+        }
+        NodeKind::FootnoteReference => {
+            out.push_str("[^");
+            out.push_str(node.attr_str("label").unwrap_or(""));
+            out.push(']');
+        }
+        // Raw markup, such as rendered math a plugin left in a paragraph
+        NodeKind::HtmlInline | NodeKind::HtmlBlock => out.push_str(&node.plain_text()),
+        // Nodes without a Markdown form keep their content
+        _ => render_inline_children(node, out),
+    }
+}
+
+fn wrap(node: &Node, delim: &str, out: &mut String) {
+    out.push_str(delim);
+    render_inline_children(node, out);
+    out.push_str(delim);
+}
+
+// `(dest "title")`; destinations with spaces or parentheses are bracketed
+fn push_target(dest: &str, title: Option<&str>, out: &mut String) {
+    out.push('(');
+    if dest.contains([' ', '(', ')']) {
+        out.push('<');
+        out.push_str(dest);
+        out.push('>');
+    } else {
+        out.push_str(dest);
+    }
+    if let Some(title) = title {
+        out.push_str(" \"");
+        out.push_str(&title.replace('"', "\\\""));
+        out.push('"');
+    }
+    out.push(')');
+}
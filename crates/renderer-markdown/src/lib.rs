@@ -0,0 +1,175 @@
+use pendon_core::Event;
+
+mod block;
+mod inline;
+mod tree;
+
+/// Serialize a `markdown`-processed event stream back into normalized
+/// Markdown: ATX headings, `-` bullets, backtick fences, `---` breaks and
+/// pipe tables, with one blank line between blocks.
+///
+/// Nodes without Markdown syntax (sections, custom components) are replaced
+/// by their content. Decoded text is backslash-escaped where it would
+/// otherwise read as markup; verbatim text runs (math) are written as is.
+pub fn render_markdown(events: &[Event]) -> String {
+    let root = tree::build(events);
+    let blocks = block::render_blocks(&root);
+    if blocks.is_empty() {
+        return String::new();
+    }
+    let mut out = blocks.join("\n\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pendon_core::{parse, NodeKind, Options};
+
+    fn fmt(src: &str) -> String {
+        let events = pendon_plugin_markdown::process(&parse(src, &Options::default()));
+        render_markdown(&events)
+    }
+
+    #[test]
+    fn normalizes_block_syntax() {
+        let src = "Intro *em* and __b__\n\n* one\n* two\n   * deep\n\n3) three\n\n```\n```\n";
+        assert_eq!(
+            fmt(src),
+            "Intro *em* and __b__\n\n- one\n- two\n  - deep\n\n3. three\n\n```\n```\n"
+        );
+    }
+
+    #[test]
+    fn setext_headings_become_atx() {
+        assert_eq!(fmt("Title\n=====\n\nSub\n---\n"), "# Title\n\n## Sub\n");
+    }
+
+    #[test]
+    fn attribute_blocks_are_written_back() {
+        let src = "## Title {.lead #top}\n\nSee [a](/x){target=_blank}.\n\n```js {#demo}\n1\n```\n";
+        assert_eq!(
+            fmt(src),
+            "## Title {#top .lead}\n\nSee [a](/x){target=\"_blank\"}.\n\n```js {#demo}\n1\n```\n"
+        );
+    }
+
+    #[test]
+    fn output_is_stable_when_reparsed() {
+        let src = "# Title\n\nSee [docs](https://x.dev \"Docs\") and ![a](b.png), ~~old~~.\n\n- a `x`\n- [X] b\n- [ ] c\n\n| A | B |\n|---|---|\n| 1 | **2** |\n\n````md\n```\n````\n\n> quoted\n";
+        let once = fmt(src);
+        assert_eq!(fmt(&once), once);
+        assert!(once.contains("| 1 | **2** |"), "{once}");
+        assert!(once.contains(", ~~old~~."), "{once}");
+        assert!(once.contains("- [x] b\n- [ ] c\n"), "{once}");
+        assert!(once.contains("````md\n```\n````"), "{once}");
+    }
+
+    #[test]
+    fn footnotes_round_trip_after_the_body() {
+        let src = "[^x]: Note\n    more\n\nText[^x].\n";
+        let once = fmt(src);
+        assert_eq!(once, "Text[^x].\n\n[^x]: Note\n    more\n");
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn escapes_decoded_text_that_would_read_as_markup() {
+        let src = "\\*not em\\* \\[x\\](y) a\\\\b &amp;amp; $a*b$\n\n\\# plain\n\n1\\. plain\n";
+        let once = fmt(src);
+        assert_eq!(
+            once,
+            "\\*not em\\* \\[x\\](y) a\\b \\&amp; $a*b$\n\n\\# plain\n\n1\\. plain\n"
+        );
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn escapes_text_merged_into_longer_events() {
+        let src = "\\*not em\\* and &lt;b&gt; $a*b$\n\n$$\nx_1*y\n$$\n";
+        let events = pendon_plugin_markdown::process(&parse(src, &Options::default()));
+        // Plugins such as `latex` join adjacent text into one event
+        let mut merged: Vec<Event> = Vec::new();
+        for ev in events {
+            match (merged.last_mut(), ev) {
+                (Some(Event::Text(prev, _)), Event::Text(t, _)) => {
+                    *prev = format!("{prev}{t}").into();
+                }
+                (_, ev) => merged.push(ev),
+            }
+        }
+        assert_eq!(
+            render_markdown(&merged),
+            "\\*not em\\* and \\<b> $a*b$\n\n$$\nx_1*y\n$$\n"
+        );
+    }
+
+    #[test]
+    fn indented_code_becomes_fenced_except_inside_list_items() {
+        let src = "p\n\n    code\n\n- a\n\n  b\n\n      nested\n";
+        let once = fmt(src);
+        assert_eq!(once, "p\n\n```\ncode\n```\n\n- a\n\n  b\n\n      nested\n");
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn table_alignment_and_cell_pipes_round_trip() {
+        let src = "| L | C | R |\n|:-|:-:|-:|\n| a \\| b | `x|y` | 3 |\n";
+        let once = fmt(src);
+        assert_eq!(
+            once,
+            "| L | C | R |\n| :--- | :---: | ---: |\n| a \\| b | `x|y` | 3 |\n"
+        );
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn definition_lists_round_trip_with_block_content() {
+        let src = "Apple\n: A fruit.\n:   A company.\n\nOrange\n: Juicy,\nround.\n\n  More.\n\n  - a\n\nLoose\n\n: Def.\n";
+        let once = fmt(src);
+        assert_eq!(
+            once,
+            "Apple\n: A fruit.\n: A company.\n\nOrange\n: Juicy,\n  round.\n\n  More.\n\n  - a\n\nLoose\n: Def.\n"
+        );
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn callouts_round_trip_with_fold_sign_and_title() {
+        let opts = pendon_plugin_markdown::MarkdownOptions {
+            callouts: true,
+            ..Default::default()
+        };
+        let fmt_callouts = |src: &str| {
+            let events = pendon_plugin_markdown::process_with_options(
+                &parse(src, &Options::default()),
+                opts,
+            );
+            render_markdown(&events)
+        };
+        let once = fmt_callouts("> [!tip]- Read *me*\n> body\n\n> [!NOTE]\n");
+        assert_eq!(once, "> [!TIP]- Read *me*\n> body\n\n> [!NOTE]\n");
+        assert_eq!(fmt_callouts(&once), once);
+    }
+
+    #[test]
+    fn escapes_pipes_in_cells_and_unwraps_custom_nodes() {
+        let events: Vec<Event<'static>> = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Custom("Note".into()), None),
+            Event::StartNode(NodeKind::Table, None),
+            Event::StartNode(NodeKind::TableRow, None),
+            Event::StartNode(NodeKind::TableCell, None),
+            Event::Text("a".into(), None),
+            Event::Text("|".into(), None),
+            Event::Text("b".into(), None),
+            Event::EndNode(NodeKind::TableCell, None),
+            Event::EndNode(NodeKind::TableRow, None),
+            Event::EndNode(NodeKind::Table, None),
+            Event::EndNode(NodeKind::Custom("Note".into()), None),
+            Event::EndNode(NodeKind::Document, None),
+        ];
+        assert_eq!(render_markdown(&events), "| a\\|b |\n| --- |\n");
+    }
+}
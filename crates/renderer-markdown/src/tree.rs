@@ -0,0 +1,182 @@
+use pendon_core::{AttrValue, Event, NodeKind};
+
+// Lossless node tree; unlike the AST renderer it keeps every text run in
+// place, which the serializer needs to reproduce mixed content
+pub(crate) struct Node<'e> {
+    pub kind: NodeKind,
+    pub attrs: Vec<(&'e str, &'e AttrValue<'e>)>,
+    pub children: Vec<Child<'e>>,
+}
+
+pub(crate) enum Child<'e> {
+    Node(Node<'e>),
+    Text(&'e str),
+}
+
+impl<'e> Node<'e> {
+    fn new(kind: NodeKind) -> Self {
+        Node {
+            kind,
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Last value set for `name`, so repeated attributes behave like a map.
+    pub fn attr(&self, name: &str) -> Option<&'e AttrValue<'e>> {
+        self.attrs
+            .iter()
+            .rev()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn attr_str(&self, name: &str) -> Option<&'e str> {
+        self.attr(name).and_then(AttrValue::as_str)
+    }
+
+    /// Flag attribute, whether stored as `true` or as `"1"`.
+    pub fn attr_bool(&self, name: &str) -> bool {
+        self.attr(name).is_some_and(AttrValue::as_flag)
+    }
+
+    /// Numeric attribute, whether stored as an integer or a decimal string.
+    pub fn attr_usize(&self, name: &str) -> Option<usize> {
+        let value = self.attr(name)?;
+        match value.as_str() {
+            Some(s) => s.parse().ok(),
+            None => value.as_int().and_then(|n| usize::try_from(n).ok()),
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::Emphasis
+                | NodeKind::Strong
+                | NodeKind::Bold
+                | NodeKind::Italic
+                | NodeKind::Strikethrough
+                | NodeKind::FootnoteReference
+                | NodeKind::InlineCode
+                | NodeKind::Link
+                | NodeKind::Image
+                | NodeKind::HtmlInline
+        )
+    }
+
+    /// `{#id .class key="value"}` block for the attributes outside `known`,
+    /// or an empty string when there are none.
+    pub fn attr_block(&self, known: &[&str]) -> String {
+        let mut parts = Vec::new();
+        if let Some(id) = self.attr_str("id") {
+            parts.push(format!("#{id}"));
+        }
+        if let Some(class) = self.attr_str("class") {
+            parts.extend(class.split_whitespace().map(|c| format!(".{c}")));
+        }
+        for (idx, (name, value)) in self.attrs.iter().enumerate() {
+            let repeated = self.attrs[idx + 1..].iter().any(|(n, _)| n == name);
+            if repeated || matches!(*name, "id" | "class") || known.contains(name) {
+                continue;
+            }
+            if let Some(value) = value.as_str() {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                parts.push(format!("{name}=\"{value}\""));
+            }
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", parts.join(" "))
+        }
+    }
+
+    /// Concatenated text of every descendant.
+    pub fn plain_text(&self) -> String {
+        let mut out = String::new();
+        collect_text(self, &mut out);
+        out
+    }
+}
+
+fn collect_text(node: &Node, out: &mut String) {
+    for child in &node.children {
+        match child {
+            Child::Text(t) => out.push_str(t),
+            Child::Node(n) => collect_text(n, out),
+        }
+    }
+}
+
+/// Nest `events` under a `Document` root. Unbalanced ends are ignored and
+/// unclosed nodes are closed at the end; diagnostics are dropped.
+pub(crate) fn build<'e>(events: &'e [Event<'e>]) -> Node<'e> {
+    let mut stack = vec![Node::new(NodeKind::Document)];
+    for ev in events {
+        match ev {
+            Event::StartNode(NodeKind::Document, _) | Event::EndNode(NodeKind::Document, _) => {}
+            Event::StartNode(kind, _) => stack.push(Node::new(kind.clone())),
+            Event::EndNode(kind, _) => {
+                if stack.len() > 1 && stack.last().is_some_and(|n| n.kind == *kind) {
+                    close(&mut stack);
+                }
+            }
+            Event::Text(text, _) => push_child(&mut stack, Child::Text(text)),
+            Event::Attribute { name, value, .. } => {
+                if let Some(top) = stack.last_mut() {
+                    top.attrs.push((name, value));
+                }
+            }
+            Event::Diagnostic(_) => {}
+        }
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap_or_else(|| Node::new(NodeKind::Document))
+}
+
+fn close<'e>(stack: &mut Vec<Node<'e>>) {
+    if let Some(node) = stack.pop() {
+        push_child(stack, Child::Node(node));
+    }
+}
+
+fn push_child<'e>(stack: &mut [Node<'e>], child: Child<'e>) {
+    if let Some(top) = stack.last_mut() {
+        top.children.push(child);
+    }
+}
+
+/// Longest run of `ch` in `s`.
+pub(crate) fn longest_run(s: &str, ch: char) -> usize {
+    let mut best = 0;
+    let mut run = 0;
+    for c in s.chars() {
+        if c == ch {
+            run += 1;
+            best = best.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    best
+}
+
+/// Prefix every line of `block`; `first` replaces `rest` on the first line.
+/// Blank lines get the trimmed prefix so no trailing whitespace is written.
+pub(crate) fn prefix_lines(block: &str, first: &str, rest: &str, out: &mut String) {
+    for (idx, line) in block.split('\n').enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let prefix = if idx == 0 { first } else { rest };
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+    }
+}
@@ -53,11 +53,7 @@ pub fn render_solid_with_hints(events: &[Event], hints: Option<&SolidRenderHints
 
 fn collect_used_nodes(v: &Value, out: &mut BTreeSet<(String, Option<String>)>) {
     if let Some(kind) = v.get("type").and_then(|t| t.as_str()) {
-        let name = v
-            .get("attrs")
-            .and_then(|a| a.get("name"))
-            .and_then(|n| n.as_str())
-            .map(|s| s.to_string());
+        let name = template::node_name(v).map(|s| s.to_string());
         out.insert((kind.to_string(), name.clone()));
         if name.is_some() {
             out.insert((kind.to_string(), None));
@@ -106,42 +102,202 @@ mod tests {
     use super::*;
     use pendon_core::{Event, NodeKind};
 
-    fn html_block_events() -> Vec<Event> {
+    fn html_block_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::HtmlBlock),
-            Event::Text("<div>raw</div>".to_string()),
-            Event::EndNode(NodeKind::HtmlBlock),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::HtmlBlock, None),
+            Event::Text("<div>raw</div>".into(), None),
+            Event::EndNode(NodeKind::HtmlBlock, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
-    fn html_inline_events() -> Vec<Event> {
+    fn html_inline_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::Paragraph),
-            Event::StartNode(NodeKind::HtmlInline),
-            Event::Text("<span>inline</span>".to_string()),
-            Event::EndNode(NodeKind::HtmlInline),
-            Event::EndNode(NodeKind::Paragraph),
-            Event::EndNode(NodeKind::Document),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::HtmlInline, None),
+            Event::Text("<span>inline</span>".into(), None),
+            Event::EndNode(NodeKind::HtmlInline, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
-    fn raw_codefence_events() -> Vec<Event> {
+    fn raw_codefence_events() -> Vec<Event<'static>> {
         vec![
-            Event::StartNode(NodeKind::Document),
-            Event::StartNode(NodeKind::CodeFence),
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::CodeFence, None),
             Event::Attribute {
-                name: "raw_html".to_string(),
-                value: "1".to_string(),
+                name: "raw_html".into(),
+                value: "1".into(),
+                span: None,
             },
-            Event::Text("<p>  <b>x</b></p>".to_string()),
-            Event::EndNode(NodeKind::CodeFence),
-            Event::EndNode(NodeKind::Document),
+            Event::Text("<p>  <b>x</b></p>".into(), None),
+            Event::EndNode(NodeKind::CodeFence, None),
+            Event::EndNode(NodeKind::Document, None),
         ]
     }
 
+    fn task_list_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str| Event::Attribute {
+            name: name.into(),
+            value: true.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::BulletList, None),
+            Event::StartNode(NodeKind::ListItem, None),
+            attr("task"),
+            attr("checked"),
+            Event::Text("done".into(), None),
+            Event::EndNode(NodeKind::ListItem, None),
+            Event::StartNode(NodeKind::ListItem, None),
+            attr("task"),
+            Event::Text("todo".into(), None),
+            Event::EndNode(NodeKind::ListItem, None),
+            Event::EndNode(NodeKind::BulletList, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn footnote_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: i64| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::FootnoteReference, None),
+            attr("index", 1),
+            attr("ref", 1),
+            Event::EndNode(NodeKind::FootnoteReference, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::StartNode(NodeKind::FootnoteDefinition, None),
+            attr("index", 1),
+            attr("refs", 1),
+            Event::Text("note".into(), None),
+            Event::EndNode(NodeKind::FootnoteDefinition, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn aligned_table_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Table, None),
+            Event::StartNode(NodeKind::TableHead, None),
+            Event::StartNode(NodeKind::TableRow, None),
+            Event::StartNode(NodeKind::TableCell, None),
+            attr("header", "1"),
+            attr("align", "center"),
+            Event::Text("A".into(), None),
+            Event::EndNode(NodeKind::TableCell, None),
+            Event::EndNode(NodeKind::TableRow, None),
+            Event::EndNode(NodeKind::TableHead, None),
+            Event::StartNode(NodeKind::TableBody, None),
+            Event::StartNode(NodeKind::TableRow, None),
+            Event::StartNode(NodeKind::TableCell, None),
+            attr("align", "center"),
+            Event::Text("1".into(), None),
+            Event::EndNode(NodeKind::TableCell, None),
+            Event::EndNode(NodeKind::TableRow, None),
+            Event::EndNode(NodeKind::TableBody, None),
+            Event::EndNode(NodeKind::Table, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn attributed_events() -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Heading, None),
+            attr("level", "2"),
+            attr("id", "top"),
+            attr("class", "lead"),
+            Event::Text("Title".into(), None),
+            Event::EndNode(NodeKind::Heading, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            attr("class", "note"),
+            Event::StartNode(NodeKind::Link, None),
+            attr("href", "/x"),
+            attr("target", "_blank"),
+            Event::Text("a".into(), None),
+            Event::EndNode(NodeKind::Link, None),
+            Event::StartNode(NodeKind::Image, None),
+            attr("alt", "i"),
+            attr("src", "p.png"),
+            attr("width", "50"),
+            Event::EndNode(NodeKind::Image, None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn definition_list_events() -> Vec<Event<'static>> {
+        vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::DefinitionList, None),
+            Event::StartNode(NodeKind::DefinitionTerm, None),
+            Event::Attribute {
+                name: "class".into(),
+                value: "a".into(),
+                span: None,
+            },
+            Event::Text("Term".into(), None),
+            Event::EndNode(NodeKind::DefinitionTerm, None),
+            Event::StartNode(NodeKind::DefinitionDescription, None),
+            Event::Text("one".into(), None),
+            Event::EndNode(NodeKind::DefinitionDescription, None),
+            Event::EndNode(NodeKind::DefinitionList, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]
+    }
+
+    fn callout_events(foldable: bool) -> Vec<Event<'static>> {
+        let attr = |name: &'static str, value: &'static str| Event::Attribute {
+            name: name.into(),
+            value: value.into(),
+            span: None,
+        };
+        let mut events = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Callout, None),
+            attr("kind", "warning"),
+        ];
+        if foldable {
+            events.extend([
+                attr("title", "Mind <this>"),
+                Event::Attribute {
+                    name: "foldable".into(),
+                    value: true.into(),
+                    span: None,
+                },
+            ]);
+        }
+        events.extend([
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Text("careful".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Callout, None),
+            Event::EndNode(NodeKind::Document, None),
+        ]);
+        events
+    }
+
     #[test]
     fn html_block_passes_raw_html() {
         let output = render_solid(&html_block_events());
@@ -159,4 +315,90 @@ mod tests {
         let output = render_solid(&raw_codefence_events());
         assert!(output.contains("<pre><code innerHTML={\"<p>  <b>x</b></p>\"} /></pre>"));
     }
+
+    #[test]
+    fn task_items_render_disabled_checkboxes() {
+        let output = render_solid(&task_list_events());
+        assert!(output.contains("<li><input type=\"checkbox\" disabled checked /> done</li>"));
+        assert!(output.contains("<li><input type=\"checkbox\" disabled /> todo</li>"));
+    }
+
+    #[test]
+    fn footnotes_render_with_back_references() {
+        let output = render_solid(&footnote_events());
+        assert!(output.contains(
+            "<p><sup><a href=\"#fn-1\" id=\"fnref-1\" class=\"footnote-ref\">1</a></sup></p>"
+        ));
+        assert!(output.contains("<section class=\"footnotes\">\n<ol>\n<li id=\"fn-1\">note <a href=\"#fnref-1\" class=\"footnote-backref\">↩</a></li>\n</ol>\n</section>"));
+    }
+
+    #[test]
+    fn table_cells_render_column_alignment() {
+        let output = render_solid(&aligned_table_events());
+        assert!(output.contains("<th align=\"center\">A</th>"));
+        assert!(output.contains("<td align=\"center\">1</td>"));
+    }
+
+    #[test]
+    fn callouts_render_as_asides_or_through_templates() {
+        let output = render_solid(&callout_events(true));
+        assert!(output.contains("<aside class=\"callout callout-warning\">\n<details><summary>Mind &lt;this&gt;</summary>\n<p>careful</p>\n</details>\n</aside>"));
+
+        let hints = SolidRenderHints {
+            templates: vec![ComponentTemplate {
+                node_type: "Callout".to_string(),
+                node_name: Some("warning".to_string()),
+                template: "<Alert kind=\"{attrs.kind}\">{children}</Alert>".to_string(),
+            }],
+            ..SolidRenderHints::default()
+        };
+        let output = render_solid_with_hints(&callout_events(false), Some(&hints));
+        assert!(output.contains("<Alert kind=\"warning\"><p>careful</p>\n</Alert>"));
+
+        // Only callouts are named by their `kind`
+        let hints = SolidRenderHints {
+            templates: vec![ComponentTemplate {
+                node_type: "Paragraph".to_string(),
+                node_name: Some("warning".to_string()),
+                template: "<Warn>{children}</Warn>".to_string(),
+            }],
+            ..SolidRenderHints::default()
+        };
+        let events = vec![
+            Event::StartNode(NodeKind::Document, None),
+            Event::StartNode(NodeKind::Paragraph, None),
+            Event::Attribute {
+                name: "kind".into(),
+                value: "warning".into(),
+                span: None,
+            },
+            Event::Text("plain".into(), None),
+            Event::EndNode(NodeKind::Paragraph, None),
+            Event::EndNode(NodeKind::Document, None),
+        ];
+        let output = render_solid_with_hints(&events, Some(&hints));
+        assert!(!output.contains("<Warn>"), "output = {output}");
+    }
+
+    #[test]
+    fn attribute_blocks_render_as_props() {
+        let output = render_solid(&attributed_events());
+        assert!(
+            output.contains("<h2 class=\"lead\" id=\"top\">Title</h2>"),
+            "output = {output}"
+        );
+        assert!(
+            output.contains("<p class=\"note\"><a href=\"/x\" target=\"_blank\">a</a><img alt=\"i\" src=\"p.png\" width=\"50\" /></p>"),
+            "output = {output}"
+        );
+    }
+
+    #[test]
+    fn definition_lists_render_as_dl() {
+        let output = render_solid(&definition_list_events());
+        assert!(
+            output.contains("<dl>\n<dt class=\"a\">Term</dt>\n<dd>one</dd>\n</dl>"),
+            "output = {output}"
+        );
+    }
 }
@@ -8,8 +8,8 @@ pub fn extract_frontmatter(v: &Value) -> Option<String> {
     for ch in children {
         if ch.get("type").and_then(|t| t.as_str()) == Some("Frontmatter") {
             if let Some(attrs) = ch.get("attrs").and_then(|a| a.as_object()) {
-                if let Some(data) = attrs.get("data").and_then(|d| d.as_str()) {
-                    return Some(data.to_string());
+                if let Some(data) = attrs.get("data") {
+                    return Some(export_value(data));
                 }
             }
         }
@@ -25,11 +25,20 @@ pub fn extract_headings(v: &Value) -> Option<String> {
     for ch in children {
         if ch.get("type").and_then(|t| t.as_str()) == Some("Headings") {
             if let Some(attrs) = ch.get("attrs").and_then(|a| a.as_object()) {
-                if let Some(data) = attrs.get("data").and_then(|d| d.as_str()) {
-                    return Some(data.to_string());
+                if let Some(data) = attrs.get("data") {
+                    return Some(export_value(data));
                 }
             }
         }
     }
     None
 }
+
+// Structured data exports as a JSON literal; string payloads are taken to
+// already hold one
+fn export_value(data: &Value) -> String {
+    match data {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
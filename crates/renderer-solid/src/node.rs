@@ -14,17 +14,34 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
 
         match kind {
             "Document" => {
+                // Consecutive footnotes share one list at the end of the page
+                let mut in_notes = false;
                 if let Some(children) = v.get("children").and_then(|c| c.as_array()) {
                     for ch in children {
+                        let is_note =
+                            ch.get("type").and_then(|t| t.as_str()) == Some("FootnoteDefinition");
+                        if is_note != in_notes {
+                            in_notes = is_note;
+                            out.push_str(if in_notes {
+                                "<section class=\"footnotes\">\n<ol>\n"
+                            } else {
+                                "</ol>\n</section>\n"
+                            });
+                        }
                         render_node(ch, out, hints);
                     }
                 }
+                if in_notes {
+                    out.push_str("</ol>\n</section>\n");
+                }
             }
             "Frontmatter" | "Headings" => {
                 // Metadata only; skip emitting markup.
             }
             "Paragraph" => {
-                out.push_str("<p>");
+                out.push_str("<p");
+                extra_attrs(v, &[], out);
+                out.push('>');
                 render_children(v, out, hints);
                 out.push_str("</p>\n");
             }
@@ -33,19 +50,48 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 render_children(v, out, hints);
                 out.push_str("</blockquote>\n");
             }
+            "Callout" => {
+                let kind = attr_str(v, "kind").unwrap_or("note");
+                let foldable = attr_bool(v, "foldable");
+                out.push_str("<aside class=\"callout callout-");
+                escape_jsx(kind, out);
+                out.push_str("\">\n");
+                let title = attr_str(v, "title").map(str::to_string).unwrap_or_else(|| {
+                    let mut chars = kind.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                });
+                if foldable {
+                    out.push_str(if attr_bool(v, "open") {
+                        "<details open><summary>"
+                    } else {
+                        "<details><summary>"
+                    });
+                    escape_jsx(&title, out);
+                    out.push_str("</summary>\n");
+                } else {
+                    out.push_str("<p class=\"callout-title\">");
+                    escape_jsx(&title, out);
+                    out.push_str("</p>\n");
+                }
+                render_children(v, out, hints);
+                if foldable {
+                    out.push_str("</details>\n");
+                }
+                out.push_str("</aside>\n");
+            }
             "Heading" => {
-                let level = v
-                    .get("attrs")
-                    .and_then(|a| a.get("level"))
-                    .and_then(|l| l.as_str())
-                    .unwrap_or("1");
+                let level = attr_usize(v, "level").unwrap_or(1).to_string();
                 out.push('<');
                 out.push('h');
-                out.push_str(level);
+                out.push_str(&level);
+                extra_attrs(v, &["level"], out);
                 out.push('>');
                 render_text_or_children(v, out, hints);
                 out.push_str("</h");
-                out.push_str(level);
+                out.push_str(&level);
                 out.push_str(">\n");
             }
             "Section" => {
@@ -57,7 +103,7 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 {
                     out.push_str(" id=\"");
                     escape_jsx(id, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
                 out.push_str(">\n");
                 render_children(v, out, hints);
@@ -75,8 +121,9 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 {
                     out.push_str(" class=\"");
                     escape_jsx(class, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["class", "lang", "raw_html", "syntect_debug"], out);
                 let raw = v
                     .get("attrs")
                     .and_then(|a| a.get("raw_html"))
@@ -101,25 +148,63 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
             }
             "OrderedList" => {
                 out.push_str("<ol");
-                if let Some(attrs) = v.get("attrs") {
-                    if let Some(start) = attrs.get("start").and_then(|s| s.as_str()) {
-                        out.push_str(" start={");
-                        out.push_str(start);
-                        out.push_str("}");
-                    }
+                if let Some(start) = attr_usize(v, "start") {
+                    out.push_str(" start={");
+                    out.push_str(&start.to_string());
+                    out.push('}');
                 }
                 out.push_str(">\n");
                 render_children(v, out, hints);
                 out.push_str("</ol>\n");
             }
+            "DefinitionList" => {
+                out.push_str("<dl>\n");
+                render_children(v, out, hints);
+                out.push_str("</dl>\n");
+            }
+            "DefinitionTerm" | "DefinitionDescription" => {
+                let tag = if kind == "DefinitionTerm" { "dt" } else { "dd" };
+                out.push('<');
+                out.push_str(tag);
+                extra_attrs(v, &[], out);
+                out.push('>');
+                if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
+                    escape_jsx(text, out);
+                }
+                render_children(v, out, hints);
+                out.push_str("</");
+                out.push_str(tag);
+                out.push_str(">\n");
+            }
             "ListItem" => {
                 out.push_str("<li>");
+                if attr_bool(v, "task") {
+                    if attr_bool(v, "checked") {
+                        out.push_str("<input type=\"checkbox\" disabled checked /> ");
+                    } else {
+                        out.push_str("<input type=\"checkbox\" disabled /> ");
+                    }
+                }
                 if let Some(text) = v.get("text").and_then(|t| t.as_str()) {
                     escape_jsx(text, out);
                 }
                 render_children(v, out, hints);
                 out.push_str("</li>\n");
             }
+            "FootnoteDefinition" => {
+                let index = footnote_index(v);
+                out.push_str("<li id=\"fn-");
+                escape_jsx(&index, out);
+                out.push_str("\">");
+                render_text_or_children(v, out, hints);
+                let refs = attr_usize(v, "refs").unwrap_or(1);
+                for nth in 1..=refs {
+                    out.push_str(" <a href=\"#");
+                    escape_jsx(&footnote_ref_id(&index, nth), out);
+                    out.push_str("\" class=\"footnote-backref\">↩</a>");
+                }
+                out.push_str("</li>\n");
+            }
             "Table" => {
                 out.push_str("<table>\n");
                 render_children(v, out, hints);
@@ -150,6 +235,11 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 let tag = if is_header { "th" } else { "td" };
                 out.push('<');
                 out.push_str(tag);
+                if let Some(align) = attr_str(v, "align") {
+                    out.push_str(" align=\"");
+                    escape_jsx(align, out);
+                    out.push('"');
+                }
                 out.push('>');
                 render_children(v, out, hints);
                 out.push_str("</");
@@ -176,6 +266,22 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 render_children(v, out, hints);
                 out.push_str("</i>");
             }
+            "Strikethrough" => {
+                out.push_str("<del>");
+                render_children(v, out, hints);
+                out.push_str("</del>");
+            }
+            "FootnoteReference" => {
+                let index = footnote_index(v);
+                let nth = attr_usize(v, "ref").unwrap_or(1);
+                out.push_str("<sup><a href=\"#fn-");
+                escape_jsx(&index, out);
+                out.push_str("\" id=\"");
+                escape_jsx(&footnote_ref_id(&index, nth), out);
+                out.push_str("\" class=\"footnote-ref\">");
+                escape_jsx(&index, out);
+                out.push_str("</a></sup>");
+            }
             "InlineCode" => {
                 out.push_str("<code>");
                 render_children(v, out, hints);
@@ -187,14 +293,15 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                     if let Some(href) = attrs.get("href").and_then(|h| h.as_str()) {
                         out.push_str(" href=\"");
                         escape_jsx(href, out);
-                        out.push_str("\"");
+                        out.push('"');
                     }
                     if let Some(title) = attrs.get("title").and_then(|t| t.as_str()) {
                         out.push_str(" title=\"");
                         escape_jsx(title, out);
-                        out.push_str("\"");
+                        out.push('"');
                     }
                 }
+                extra_attrs(v, &["href", "title"], out);
                 out.push('>');
                 render_children(v, out, hints);
                 out.push_str("</a>");
@@ -208,7 +315,7 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                     .unwrap_or("");
                 out.push_str(" alt=\"");
                 escape_jsx(alt, out);
-                out.push_str("\"");
+                out.push('"');
                 if let Some(src) = v
                     .get("attrs")
                     .and_then(|a| a.get("src"))
@@ -216,8 +323,9 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
                 {
                     out.push_str(" src=\"");
                     escape_jsx(src, out);
-                    out.push_str("\"");
+                    out.push('"');
                 }
+                extra_attrs(v, &["alt", "src"], out);
                 out.push_str(" />");
             }
             "Text" => {
@@ -247,6 +355,70 @@ pub fn render_node(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>
     }
 }
 
+fn attr_str<'a>(v: &'a Value, key: &str) -> Option<&'a str> {
+    v.get("attrs")
+        .and_then(|a| a.get(key))
+        .and_then(|val| val.as_str())
+}
+
+/// Numeric attribute, whether stored as an integer or a decimal string.
+fn attr_usize(v: &Value, key: &str) -> Option<usize> {
+    match v.get("attrs").and_then(|a| a.get(key))? {
+        Value::Number(n) => n.as_u64().and_then(|n| usize::try_from(n).ok()),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+// Number of a footnote or of the note a reference points to
+fn footnote_index(v: &Value) -> String {
+    attr_usize(v, "index")
+        .map(|n| n.to_string())
+        .unwrap_or_default()
+}
+
+/// Flag attribute set to `true` or, as older plugins emit it, `"1"`.
+fn attr_bool(v: &Value, key: &str) -> bool {
+    match v.get("attrs").and_then(|a| a.get(key)) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s == "1",
+        _ => false,
+    }
+}
+
+/// Attributes beyond the ones a node's tag already renders, such as those
+/// set by a `{#id .class key=value}` block, as ` name="value"` props.
+fn extra_attrs(v: &Value, known: &[&str], out: &mut String) {
+    let Some(attrs) = v.get("attrs").and_then(|a| a.as_object()) else {
+        return;
+    };
+    for (name, value) in attrs {
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        let valid = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':'));
+        if known.contains(&name.as_str()) || !valid {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        escape_jsx(value, out);
+        out.push('"');
+    }
+}
+
+// `id` of the `nth` reference to note `index`; only repeats get a suffix
+fn footnote_ref_id(index: &str, nth: usize) -> String {
+    if nth == 1 {
+        format!("fnref-{index}")
+    } else {
+        format!("fnref-{index}-{nth}")
+    }
+}
+
 fn render_children(v: &Value, out: &mut String, hints: Option<&SolidRenderHints>) {
     if let Some(children) = v.get("children").and_then(|c| c.as_array()) {
         for ch in children {
@@ -45,13 +45,8 @@ pub fn select_template<'a>(
     node_type: &str,
     v: &Value,
 ) -> Option<&'a ComponentTemplate> {
-    let Some(h) = hints else {
-        return None;
-    };
-    let node_name = v
-        .get("attrs")
-        .and_then(|a: &Value| a.get("name"))
-        .and_then(|n| n.as_str());
+    let h = hints?;
+    let node_name = node_name(v);
 
     let mut fallback: Option<&ComponentTemplate> = None;
     for tpl in &h.templates {
@@ -69,6 +64,17 @@ pub fn select_template<'a>(
     fallback
 }
 
+/// Name templates are selected by: the `name` attribute, else a callout's
+/// `kind`.
+pub fn node_name(v: &Value) -> Option<&str> {
+    let attrs = v.get("attrs")?;
+    let callout = v.get("type").and_then(|t| t.as_str()) == Some("Callout");
+    attrs
+        .get("name")
+        .or_else(|| attrs.get("kind").filter(|_| callout))
+        .and_then(|n| n.as_str())
+}
+
 fn get_attr_value(attrs: Option<&Map<String, Value>>, key: &str) -> String {
     let Some(map) = attrs else {
         return String::new();